@@ -3,7 +3,8 @@
 import { useState } from "react";
 import { useWallet, useConnection } from "@solana/wallet-adapter-react";
 import { useMarkets } from "@/lib/useMarkets";
-import { createMarket } from "@/lib/anchor";
+import { createMarket, PYTH_SOL_USD_FEED } from "@/lib/anchor";
+import { PublicKey } from "@solana/web3.js";
 import { formatUSD } from "@/lib/format";
 import { Plus, BarChart2, Users, DollarSign, Activity, Loader2, Wallet, ShieldX } from "lucide-react";
 import { useToast } from "@/components/Toast";
@@ -27,6 +28,7 @@ export default function AdminPage() {
     description: "",
     category: "Crypto",
     resolutionSource: "Pyth: SOL/USD",
+    priceFeed: PYTH_SOL_USD_FEED.toBase58(),
     targetPrice: "",
     operator: "0",
     expiry: "",
@@ -96,6 +98,15 @@ export default function AdminPage() {
         return;
       }
 
+      let oracleAccount: PublicKey;
+      try {
+        oracleAccount = new PublicKey(form.priceFeed);
+      } catch {
+        toast("Invalid price feed address", "error");
+        setCreating(false);
+        return;
+      }
+
       const txSig = await createMarket(
         connection,
         wallet,
@@ -106,11 +117,12 @@ export default function AdminPage() {
         targetPrice,
         parseInt(form.operator),
         expiresAt,
-        liquidity
+        liquidity,
+        { oracleAccount }
       );
 
       toast("Market created!", "success", `Tx: ${txSig.slice(0, 8)}...${txSig.slice(-8)}`);
-      setForm({ question: "", description: "", category: "Crypto", resolutionSource: "Pyth: SOL/USD", targetPrice: "", operator: "0", expiry: "", liquidity: "" });
+      setForm({ question: "", description: "", category: "Crypto", resolutionSource: "Pyth: SOL/USD", priceFeed: PYTH_SOL_USD_FEED.toBase58(), targetPrice: "", operator: "0", expiry: "", liquidity: "" });
       setTimeout(refresh, 2000);
     } catch (e: unknown) {
       const msg = e instanceof Error ? e.message : "Failed to create market";
@@ -171,7 +183,7 @@ export default function AdminPage() {
               required
             />
           </div>
-          <div className="grid grid-cols-1 sm:grid-cols-3 gap-4">
+          <div className="grid grid-cols-1 sm:grid-cols-2 gap-4">
             <div>
               <label className="text-xs text-text-muted mb-1 block">Category</label>
               <select
@@ -196,6 +208,17 @@ export default function AdminPage() {
                 required
               />
             </div>
+            <div>
+              <label className="text-xs text-text-muted mb-1 block">Price Feed Account</label>
+              <input
+                type="text"
+                value={form.priceFeed}
+                onChange={(e) => setForm({ ...form, priceFeed: e.target.value })}
+                placeholder={PYTH_SOL_USD_FEED.toBase58()}
+                className="w-full bg-background border border-border rounded-lg px-4 py-2.5 font-mono text-sm text-text-primary placeholder:text-text-muted focus:outline-none focus:border-primary transition-colors"
+                required
+              />
+            </div>
             <div>
               <label className="text-xs text-text-muted mb-1 block">Operator</label>
               <select
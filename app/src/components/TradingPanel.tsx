@@ -51,20 +51,21 @@ export function TradingPanel({ market }: { market: Market }) {
     if (mode === "buy") {
       const feeAmount = (amountNum * feeBps + 9999) / 10000;
       const inputAfterFee = amountNum - feeAmount;
-      const [inputReserve, outputReserve] = side === "YES" ? [noAmt, yesAmt] : [yesAmt, noAmt];
-      const k = inputReserve * outputReserve;
-      const newInputReserve = inputReserve + inputAfterFee;
-      const newOutputReserve = k / newInputReserve;
-      const tokensOut = outputReserve - newOutputReserve;
+      // The input mints complete sets into both reserves, then the bought side returns to k
+      const [sideReserve, otherReserve] = side === "YES" ? [yesAmt, noAmt] : [noAmt, yesAmt];
+      const k = sideReserve * otherReserve;
+      const newOtherReserve = otherReserve + inputAfterFee;
+      const tokensOut = sideReserve + inputAfterFee - k / newOtherReserve;
       const effectivePrice = tokensOut > 0 ? amountNum / tokensOut : 0;
       const slippage = price > 0 ? Math.abs(effectivePrice - price) / price * 100 : 0;
       return { tokensOut, fee: feeAmount, slippage };
     } else {
-      const [inputReserve, outputReserve] = side === "YES" ? [yesAmt, noAmt] : [noAmt, yesAmt];
-      const k = inputReserve * outputReserve;
-      const newInputReserve = inputReserve + amountNum;
-      const newOutputReserve = k / newInputReserve;
-      const collateralBefore = outputReserve - newOutputReserve;
+      // The sold tokens join their reserve, then complete sets are burned while k allows
+      const [sideReserve, otherReserve] = side === "YES" ? [yesAmt, noAmt] : [noAmt, yesAmt];
+      const k = sideReserve * otherReserve;
+      const grownSide = sideReserve + amountNum;
+      const sum = grownSide + otherReserve;
+      const collateralBefore = (sum - Math.sqrt(sum * sum - 4 * (grownSide * otherReserve - k))) / 2;
       const feeAmount = (collateralBefore * feeBps + 9999) / 10000;
       const collateralOut = collateralBefore - feeAmount;
       return { tokensOut: collateralOut, fee: feeAmount, slippage: 0 };
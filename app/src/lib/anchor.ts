@@ -8,6 +8,9 @@ export const USDC_MINT = new PublicKey(
   process.env.NEXT_PUBLIC_USDC_MINT || "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU"
 );
 
+// Devnet Pyth SOL/USD price account, registered on-chain under "SOL/USD"
+export const PYTH_SOL_USD_FEED = new PublicKey("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix");
+
 // ============ Types ============
 
 export interface OnChainMarket {
@@ -109,6 +112,29 @@ export function getVaultPDA(marketId: number): [PublicKey, number] {
   return PublicKey.findProgramAddressSync([textBytes("vault"), u64LEBytes(marketId)], PROGRAM_ID);
 }
 
+export function getLpMintPDA(marketId: number): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([textBytes("lp_mint"), u64LEBytes(marketId)], PROGRAM_ID);
+}
+
+export function getLpTokensPDA(marketId: number, owner: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [textBytes("lp_tokens"), u64LEBytes(marketId), owner.toBytes()],
+    PROGRAM_ID
+  );
+}
+
+export function getCreatorBondPDA(marketId: number): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([textBytes("creator_bond"), u64LEBytes(marketId)], PROGRAM_ID);
+}
+
+export function getRolePDA(holder: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([textBytes("role"), holder.toBytes()], PROGRAM_ID);
+}
+
+export function getOracleFeedPDA(feedId: Uint8Array): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([textBytes("oracle_feed"), feedId], PROGRAM_ID);
+}
+
 export function getPositionPDA(marketId: number, user: PublicKey): [PublicKey, number] {
   return PublicKey.findProgramAddressSync(
     [textBytes("position"), u64LEBytes(marketId), user.toBytes()],
@@ -323,6 +349,41 @@ function encodeOptionU64(v: bigint | null): Uint8Array {
   return result;
 }
 
+function encodeOptionBytes32(v: Uint8Array | null): Uint8Array {
+  if (v === null) return new Uint8Array([0]);
+  if (v.length !== 32) throw new Error("Feed ID must be 32 bytes");
+  return concatBytes(new Uint8Array([1]), v);
+}
+
+function encodeOptionPriceRange(v: PriceRange | null): Uint8Array {
+  if (v === null) return new Uint8Array([0]);
+  return concatBytes(
+    new Uint8Array([1]),
+    encodeU64LE(toLamports(v.lower)),
+    encodeU64LE(toLamports(v.upper)),
+    encodeBool(v.lowerInclusive),
+    encodeBool(v.upperInclusive)
+  );
+}
+
+function encodeOptionTwap(v: TwapConfig | null): Uint8Array {
+  if (v === null) return new Uint8Array([0]);
+  const minSamples = new Uint8Array(2);
+  new DataView(minSamples.buffer).setUint16(0, v.minSamples, true);
+  return concatBytes(new Uint8Array([1]), encodeI64LE(BigInt(v.window)), minSamples);
+}
+
+function toLamports(amount: number): bigint {
+  return BigInt(Math.floor(amount * 1_000_000));
+}
+
+// Optional accounts left out of an instruction are passed as the program ID
+function optionalAccount(pubkey: PublicKey | null, isWritable: boolean = false) {
+  return pubkey
+    ? { pubkey, isSigner: false, isWritable }
+    : { pubkey: PROGRAM_ID, isSigner: false, isWritable: false };
+}
+
 function concatBytes(...arrays: Uint8Array[]): Uint8Array {
   const total = arrays.reduce((sum, a) => sum + a.length, 0);
   const result = new Uint8Array(total);
@@ -402,11 +463,8 @@ export async function buyOutcome(
   const inputAfterFee = amountLamports - feeAmount;
   const yesAmt = market.yesAmount;
   const noAmt = market.noAmount;
-  const [inputReserve, outputReserve] = isYes ? [noAmt, yesAmt] : [yesAmt, noAmt];
-  const k = inputReserve * outputReserve;
-  const newInputReserve = inputReserve + inputAfterFee;
-  const newOutputReserve = k / newInputReserve;
-  const expectedOut = outputReserve - newOutputReserve;
+  const [side, other] = isYes ? [yesAmt, noAmt] : [noAmt, yesAmt];
+  const expectedOut = fixedProductBuyOut(side, other, inputAfterFee);
   const minOut = expectedOut * BigInt(10000 - slippageBps) / 10000n;
 
   const userCollateral = getAssociatedTokenAddressSync(market.collateralMint, user);
@@ -430,7 +488,8 @@ export async function buyOutcome(
     DISC.buyOutcome,
     encodeU64LE(amountLamports),
     encodeBool(isYes),
-    encodeU64LE(minOut)
+    encodeU64LE(minOut),
+    new Uint8Array([0, 0]) // deadline, max_price_impact_bps: None
   );
 
   ixs.push(new TransactionInstruction({
@@ -481,11 +540,8 @@ export async function sellOutcome(
   const amountLamports = BigInt(Math.floor(tokenAmount * 1_000_000));
   const yesAmt = market.yesAmount;
   const noAmt = market.noAmount;
-  const [inputReserve, outputReserve] = isYes ? [yesAmt, noAmt] : [noAmt, yesAmt];
-  const k = inputReserve * outputReserve;
-  const newInputReserve = inputReserve + amountLamports;
-  const newOutputReserve = k / newInputReserve;
-  const collateralBeforeFee = outputReserve - newOutputReserve;
+  const [side, other] = isYes ? [yesAmt, noAmt] : [noAmt, yesAmt];
+  const collateralBeforeFee = fixedProductSellOut(side, other, amountLamports);
   const fee = (collateralBeforeFee * BigInt(market.feeBps) + 9999n) / 10000n;
   const expectedOut = collateralBeforeFee - fee;
   const minOut = expectedOut * BigInt(10000 - slippageBps) / 10000n;
@@ -498,7 +554,8 @@ export async function sellOutcome(
     DISC.sellOutcome,
     encodeU64LE(amountLamports),
     encodeBool(isYes),
-    encodeU64LE(minOut)
+    encodeU64LE(minOut),
+    new Uint8Array([0, 0]) // deadline, max_price_impact_bps: None
   );
 
   return sendInstruction(connection, wallet, new TransactionInstruction({
@@ -559,7 +616,34 @@ export async function redeemWinnings(
   }));
 }
 
-// Create market (admin)
+// Bounds of a "between" or scalar market, in USD
+export interface PriceRange {
+  lower: number;
+  upper: number;
+  lowerInclusive: boolean;
+  upperInclusive: boolean;
+}
+
+// TWAP settlement: average over `window` seconds before expiry, from at least `minSamples` samples
+export interface TwapConfig {
+  window: number;
+  minSamples: number;
+}
+
+export interface CreateMarketOptions {
+  // Legacy Pyth price account or Switchboard feed the market settles on
+  oracleAccount?: PublicKey;
+  // Pyth pull-oracle feed ID; required without `oracleAccount`, checked against it otherwise
+  oracleFeedId?: Uint8Array;
+  resolutionRange?: PriceRange;
+  twap?: TwapConfig;
+  // LMSR liquidity parameter, in USD; the constant-product AMM is used when unset
+  lmsrB?: number;
+}
+
+// Create market. Oracle markets must name their feed's registered symbol in `resolutionSource`
+// (e.g. "Pyth: SOL/USD"). Wallets without the creator role post the platform's creator bond and
+// wait for review.
 export async function createMarket(
   connection: Connection,
   wallet: WalletAdapter,
@@ -570,7 +654,8 @@ export async function createMarket(
   resolutionValue: number | null,
   resolutionOperator: number,
   expiresAt: number,
-  initialLiquidity: number
+  initialLiquidity: number,
+  options: CreateMarketOptions = {}
 ): Promise<string> {
   const authority = wallet.publicKey;
   const [platformPDA] = getPlatformPDA();
@@ -583,11 +668,22 @@ export async function createMarket(
   const [yesMintPDA] = getYesMintPDA(marketId);
   const [noMintPDA] = getNoMintPDA(marketId);
   const [vaultPDA] = getVaultPDA(marketId);
+  const [lpMintPDA] = getLpMintPDA(marketId);
+  const [lpTokensPDA] = getLpTokensPDA(marketId, authority);
+  const [creatorBondPDA] = getCreatorBondPDA(marketId);
+  const [rolePDA] = getRolePDA(authority);
+  const hasRole = (await connection.getAccountInfo(rolePDA)) !== null;
+
+  const { oracleAccount = null, oracleFeedId = null, resolutionRange = null, twap = null, lmsrB = null } = options;
+  // The registered symbol is looked up by the feed the market binds: the account's own address
+  // for legacy Pyth and Switchboard, the feed ID for Pyth pull
+  const boundFeedId = oracleAccount ? oracleAccount.toBytes() : oracleFeedId;
+  const oracleFeedPDA = boundFeedId ? getOracleFeedPDA(boundFeedId)[0] : null;
 
   const authorityCollateral = getAssociatedTokenAddressSync(USDC_MINT, authority);
 
-  const resVal = resolutionValue !== null ? BigInt(Math.floor(resolutionValue * 1_000_000)) : null;
-  const liquidityLamports = BigInt(Math.floor(initialLiquidity * 1_000_000));
+  const resVal = resolutionValue !== null ? toLamports(resolutionValue) : null;
+  const liquidityLamports = toLamports(initialLiquidity);
 
   const data = concatBytes(
     DISC.createMarket,
@@ -598,23 +694,34 @@ export async function createMarket(
     encodeOptionU64(resVal),
     new Uint8Array([resolutionOperator]),
     encodeI64LE(BigInt(expiresAt)),
-    encodeU64LE(liquidityLamports)
+    encodeU64LE(liquidityLamports),
+    encodeOptionBytes32(oracleFeedId),
+    encodeOptionPriceRange(resolutionRange),
+    encodeOptionTwap(twap),
+    encodeOptionU64(lmsrB !== null ? toLamports(lmsrB) : null)
   );
 
   return sendInstruction(connection, wallet, new TransactionInstruction({
     programId: PROGRAM_ID,
     keys: [
       { pubkey: platformPDA, isSigner: false, isWritable: true },
+      optionalAccount(hasRole ? rolePDA : null),
       { pubkey: marketPDA, isSigner: false, isWritable: true },
+      optionalAccount(creatorBondPDA, true),
+      optionalAccount(platform.treasury),
       { pubkey: yesMintPDA, isSigner: false, isWritable: true },
       { pubkey: noMintPDA, isSigner: false, isWritable: true },
       { pubkey: USDC_MINT, isSigner: false, isWritable: false },
       { pubkey: vaultPDA, isSigner: false, isWritable: true },
+      { pubkey: lpMintPDA, isSigner: false, isWritable: true },
+      { pubkey: lpTokensPDA, isSigner: false, isWritable: true },
       { pubkey: authorityCollateral, isSigner: false, isWritable: true },
       { pubkey: authority, isSigner: true, isWritable: true },
       { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
       { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+      optionalAccount(oracleAccount),
+      optionalAccount(oracleFeedPDA),
     ],
     data: data as any,
   }));
@@ -633,6 +740,36 @@ export function computePrices(yesAmount: bigint, noAmount: bigint): { yesPrice:
   };
 }
 
+// Tokens of `side` bought with `input` collateral: the input mints complete sets into both
+// reserves, then `side` is drawn back down to k.
+export function fixedProductBuyOut(side: bigint, other: bigint, input: bigint): bigint {
+  const newOther = other + input;
+  const newSide = (side * other + newOther - 1n) / newOther;
+  return side + input - newSide;
+}
+
+// Collateral released by selling `tokensIn` of `side`: the largest number of complete sets that
+// can be burned out of both reserves while keeping k.
+export function fixedProductSellOut(side: bigint, other: bigint, tokensIn: bigint): bigint {
+  const grown = side + tokensIn;
+  const sum = grown + other;
+  const disc = sum * sum - 4n * (grown * other - side * other);
+  let burned = (sum - isqrt(disc) + 1n) / 2n;
+  while (burned > 0n && (grown - burned) * (other - burned) < side * other) burned -= 1n;
+  return burned;
+}
+
+function isqrt(n: bigint): bigint {
+  if (n < 2n) return n;
+  let x = n;
+  let y = (x + 1n) / 2n;
+  while (y < x) {
+    x = y;
+    y = (x + n / x) / 2n;
+  }
+  return x;
+}
+
 export function toHuman(amount: bigint | number, decimals: number = 6): number {
   const val = typeof amount === "number" ? amount : Number(amount);
   return val / Math.pow(10, decimals);
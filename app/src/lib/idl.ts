@@ -18,16 +18,23 @@ export const IDL = {
       name: "createMarket",
       accounts: [
         { name: "platform", isMut: true, isSigner: false },
+        { name: "creatorRole", isMut: false, isSigner: false, isOptional: true },
         { name: "market", isMut: true, isSigner: false },
+        { name: "creatorBondVault", isMut: true, isSigner: false, isOptional: true },
+        { name: "treasury", isMut: false, isSigner: false, isOptional: true },
         { name: "yesMint", isMut: true, isSigner: false },
         { name: "noMint", isMut: true, isSigner: false },
         { name: "collateralMint", isMut: false, isSigner: false },
         { name: "vault", isMut: true, isSigner: false },
+        { name: "lpMint", isMut: true, isSigner: false },
+        { name: "authorityLpTokens", isMut: true, isSigner: false },
         { name: "authorityCollateral", isMut: true, isSigner: false },
         { name: "authority", isMut: true, isSigner: true },
         { name: "tokenProgram", isMut: false, isSigner: false },
         { name: "systemProgram", isMut: false, isSigner: false },
         { name: "rent", isMut: false, isSigner: false },
+        { name: "oracleAccount", isMut: false, isSigner: false, isOptional: true },
+        { name: "oracleFeed", isMut: false, isSigner: false, isOptional: true },
       ],
       args: [
         { name: "question", type: "string" },
@@ -38,6 +45,10 @@ export const IDL = {
         { name: "resolutionOperator", type: "u8" },
         { name: "expiresAt", type: "i64" },
         { name: "initialLiquidity", type: "u64" },
+        { name: "oracleFeedId", type: { option: { array: ["u8", 32] } } },
+        { name: "resolutionRange", type: { option: { defined: "PriceRange" } } },
+        { name: "twap", type: { option: { defined: "TwapConfig" } } },
+        { name: "lmsrB", type: { option: "u64" } },
       ],
     },
     {
@@ -114,7 +125,9 @@ export const IDL = {
       name: "cancelMarket",
       accounts: [
         { name: "market", isMut: true, isSigner: false },
+        { name: "platform", isMut: false, isSigner: false },
         { name: "authority", isMut: false, isSigner: true },
+        { name: "role", isMut: false, isSigner: false, isOptional: true },
       ],
       args: [],
     },
@@ -197,6 +210,30 @@ export const IDL = {
       },
     },
   ],
+  types: [
+    {
+      name: "PriceRange",
+      type: {
+        kind: "struct",
+        fields: [
+          { name: "lower", type: "u64" },
+          { name: "upper", type: "u64" },
+          { name: "lowerInclusive", type: "bool" },
+          { name: "upperInclusive", type: "bool" },
+        ],
+      },
+    },
+    {
+      name: "TwapConfig",
+      type: {
+        kind: "struct",
+        fields: [
+          { name: "window", type: "i64" },
+          { name: "minSamples", type: "u16" },
+        ],
+      },
+    },
+  ],
   errors: [
     { code: 6000, name: "FeeTooHigh", msg: "Fee exceeds maximum allowed" },
     { code: 6001, name: "QuestionTooLong", msg: "Question exceeds maximum length" },
@@ -1,7 +1,10 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
-use anchor_spl::token::{self, Burn, InitializeAccount, InitializeMint, Mint, MintTo, Token, TokenAccount, Transfer};
-use pyth_sdk_solana::state::SolanaPriceAccount;
+use anchor_spl::token::{self, Burn, CloseAccount, InitializeAccount, InitializeMint, Mint, MintTo, Token, TokenAccount, Transfer};
+
+mod lmsr;
+pub mod math;
+mod oracle;
 
 declare_id!("FU64EotiwqACVJ9hyhH6XA9iiqQKmWjmPTUmSF1i3ar9");
 
@@ -9,33 +12,105 @@ const MAX_QUESTION_LEN: usize = 128;
 const MAX_DESCRIPTION_LEN: usize = 200;
 const MAX_CATEGORY_LEN: usize = 32;
 const MAX_RESOLUTION_SOURCE_LEN: usize = 64;
+/// Longest symbol an oracle feed can be registered under (e.g. "SOL/USD").
+const MAX_FEED_SYMBOL_LEN: usize = 16;
 const MIN_MARKET_DURATION: i64 = 86400;
 const LOCK_BEFORE_EXPIRY: i64 = 43200;
 const MAX_FEE_BPS: u16 = 1000;
+/// Length of the windows over which `FeeSchedule::volatility_factor_bps` measures price moves.
+const VOLATILITY_WINDOW: i64 = 3600;
+/// Largest raise of a market's fee ceiling (`max_fee_bps`, or `fee_bps` without a schedule)
+/// and of its `volatility_factor_bps` in a single `set_fee_schedule` call.
+const MAX_FEE_CEILING_STEP_BPS: u16 = 500;
+const MAX_VOLATILITY_FACTOR_STEP_BPS: u16 = 5000;
+
+/// `resolution_operator` values, comparing the settlement price against the target.
+const OP_GTE: u8 = 0;
+const OP_LTE: u8 = 1;
+const OP_BETWEEN: u8 = 2;
+const OP_GT: u8 = 3;
+const OP_LT: u8 = 4;
+/// Scalar market: YES/NO mints act as LONG/SHORT and pay out linearly over `resolution_range`.
+const OP_SCALAR: u8 = 5;
 
 const STATUS_ACTIVE: u8 = 0;
 const STATUS_RESOLVED: u8 = 2;
 const STATUS_CANCELLED: u8 = 3;
+const STATUS_PROPOSED: u8 = 4;
+const STATUS_DISPUTED: u8 = 5;
+const STATUS_INVALID: u8 = 6;
+/// Trading and resolution halted by `pause_market`; `unpause_market` returns it to ACTIVE.
+const STATUS_PAUSED: u8 = 7;
+/// Created by a user without the creator role, awaiting `approve_market` / `reject_market`.
+const STATUS_PENDING_REVIEW: u8 = 8;
+/// Rejected on review (liquidity refunded, bond slashed), or withdrawn by its creator after
+/// waiting `REVIEW_TIMEOUT` (liquidity and bond refunded).
+const STATUS_REJECTED: u8 = 9;
 
 const MINT_SIZE: usize = 82;
 const TOKEN_ACCOUNT_SIZE: usize = 165;
 
-/// Grace period after expiry during which only the authority can resolve.
-/// After this period, anyone can resolve using a Pyth oracle.
+/// Grace period after expiry during which only the authority can resolve a manual market.
+/// After this period, anyone can propose an outcome (optimistic resolution).
 const RESOLUTION_GRACE_PERIOD: i64 = 86400; // 24 hours
 
-/// Maximum staleness for Pyth price data (seconds).
+/// Collateral bond posted by a proposer, and matched by a disputer.
+const RESOLUTION_BOND: u64 = 50_000_000; // 50 USDC
+
+/// Liveness window during which a proposed outcome can be disputed.
+const DISPUTE_WINDOW: i64 = 7200; // 2 hours
+
+/// Time a market can wait for review before its creator may withdraw it.
+const REVIEW_TIMEOUT: i64 = 259200; // 3 days
+
+/// Furthest a single `extend_market_expiry` call can move expiry.
+const MAX_EXPIRY_EXTENSION: i64 = 2_592_000; // 30 days
+
+/// Maximum distance between a settlement price's publish time and `expires_at` (seconds).
 const PYTH_MAX_STALENESS: u64 = 300; // 5 minutes
+const SWITCHBOARD_MAX_STALENESS: u64 = 600; // 10 minutes
 
-/// Maximum confidence interval as basis points of price.
+/// Maximum confidence interval as basis points of price (Switchboard: standard deviation).
 const PYTH_MAX_CONF_BPS: u64 = 500; // 5%
+const SWITCHBOARD_MAX_CONF_BPS: u64 = 300; // 3%
+
+/// Pyth v2 (push oracle) program that owns legacy price accounts.
+const PYTH_PROGRAM_ID: Pubkey = pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+
+/// Pyth Solana receiver (pull oracle) program that owns `PriceUpdateV2` accounts.
+const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Switchboard On-Demand program that owns pull feed accounts.
+const SWITCHBOARD_PROGRAM_ID: Pubkey = pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+
+/// Capacity of a market's TWAP price-sample ring buffer.
+const TWAP_MAX_SAMPLES: usize = 32;
+/// Share of a TWAP window its samples must cover for the market to resolve on them.
+const TWAP_MIN_COVERAGE_BPS: i64 = 7500;
+
+/// Outcome count bounds of a categorical market (binary markets use the YES/NO mint pair).
+const MIN_CATEGORICAL_OUTCOMES: u8 = 3;
+const MAX_OUTCOMES: usize = 8;
+
+/// Capacity of a market's order book (resting plus filled-but-unclaimed orders).
+const MAX_BOOK_ORDERS: usize = 32;
+/// Smallest limit order, so the book's slots cannot be filled with dust.
+const MIN_ORDER_QUANTITY: u64 = 100_000; // 0.1 outcome token
+
+/// Fixed-point scale of the reserve ratios used when quoting categorical sells.
+const RATIO_SCALE: u128 = 1_000_000_000_000_000_000;
+
+/// `resolution_source` prefixes naming the oracle a market is bound to, e.g. "Pyth: SOL/USD".
+const PYTH_SOURCE_PREFIX: &str = "pyth:";
+const SWITCHBOARD_SOURCE_PREFIX: &str = "switchboard:";
 
 #[program]
 pub mod ghostodds {
     use super::*;
 
-    pub fn initialize_platform(ctx: Context<InitializePlatform>, fee_bps: u16) -> Result<()> {
+    pub fn initialize_platform(ctx: Context<InitializePlatform>, fee_bps: u16, lp_fee_bps: u16) -> Result<()> {
         require!(fee_bps <= MAX_FEE_BPS, GhostOddsError::FeeTooHigh);
+        require!(lp_fee_bps <= fee_bps, GhostOddsError::InvalidFeeSplit);
         let platform = &mut ctx.accounts.platform;
         platform.authority = ctx.accounts.authority.key();
         platform.market_count = 0;
@@ -43,10 +118,140 @@ pub mod ghostodds {
         platform.fee_bps = fee_bps;
         platform.treasury = ctx.accounts.treasury.key();
         platform.bump = ctx.bumps.platform;
-        emit!(PlatformInitialized { authority: platform.authority, fee_bps, treasury: platform.treasury });
+        platform.lp_fee_bps = lp_fee_bps;
+        platform.pending_authority = None;
+        platform.paused = false;
+        platform.guardian = None;
+        platform.creator_bond = 0;
+        platform.creator_fee_bps = 0;
+        emit!(PlatformInitialized { authority: platform.authority, fee_bps, lp_fee_bps, treasury: platform.treasury });
+        Ok(())
+    }
+
+    /// Update the platform fees, treasury and community market terms. The new treasury must
+    /// hold the same mint as the current one; pass the current treasury as both to keep it.
+    /// Fees and bonds apply to new markets.
+    pub fn update_platform_config(
+        ctx: Context<UpdatePlatformConfig>,
+        fee_bps: u16,
+        lp_fee_bps: u16,
+        creator_bond: u64,
+        creator_fee_bps: u16,
+    ) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, GhostOddsError::FeeTooHigh);
+        require!(lp_fee_bps as u32 + creator_fee_bps as u32 <= fee_bps as u32, GhostOddsError::InvalidFeeSplit);
+        let platform = &mut ctx.accounts.platform;
+        emit!(PlatformConfigUpdated {
+            authority: platform.authority,
+            old_fee_bps: platform.fee_bps,
+            old_lp_fee_bps: platform.lp_fee_bps,
+            old_treasury: platform.treasury,
+            fee_bps,
+            lp_fee_bps,
+            treasury: ctx.accounts.treasury.key(),
+            creator_bond,
+            creator_fee_bps,
+        });
+        platform.fee_bps = fee_bps;
+        platform.lp_fee_bps = lp_fee_bps;
+        platform.creator_bond = creator_bond;
+        platform.creator_fee_bps = creator_fee_bps;
+        platform.treasury = ctx.accounts.treasury.key();
+        Ok(())
+    }
+
+    /// First step of an authority handover: nominate `new_authority`, who must accept it.
+    /// `None` withdraws a pending nomination.
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Option<Pubkey>) -> Result<()> {
+        let platform = &mut ctx.accounts.platform;
+        platform.pending_authority = new_authority;
+        emit!(AuthorityProposed { authority: platform.authority, pending_authority: new_authority });
+        Ok(())
+    }
+
+    /// Set or clear the guardian key, which can pause and unpause like the authority.
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Option<Pubkey>) -> Result<()> {
+        let platform = &mut ctx.accounts.platform;
+        platform.guardian = guardian;
+        emit!(GuardianUpdated { authority: platform.authority, guardian });
+        Ok(())
+    }
+
+    /// Halt trading and resolution on every market (authority or guardian). Unlike
+    /// `cancel_market` this is reversible, and redemptions keep working.
+    pub fn pause_platform(ctx: Context<PausePlatform>) -> Result<()> {
+        ctx.accounts.platform.paused = true;
+        emit!(PlatformPaused { by: ctx.accounts.signer.key() });
+        Ok(())
+    }
+
+    pub fn unpause_platform(ctx: Context<PausePlatform>) -> Result<()> {
+        ctx.accounts.platform.paused = false;
+        emit!(PlatformUnpaused { by: ctx.accounts.signer.key() });
+        Ok(())
+    }
+
+    /// Halt trading and resolution on an active market (authority or guardian).
+    pub fn pause_market(ctx: Context<PauseMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        market.status = STATUS_PAUSED;
+        emit!(MarketPaused { market_id: market.market_id, by: ctx.accounts.signer.key() });
+        Ok(())
+    }
+
+    pub fn unpause_market(ctx: Context<PauseMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(market.status == STATUS_PAUSED, GhostOddsError::MarketNotPaused);
+        market.status = STATUS_ACTIVE;
+        emit!(MarketUnpaused { market_id: market.market_id, by: ctx.accounts.signer.key() });
+        Ok(())
+    }
+
+    /// Second step of an authority handover, signed by the nominated authority.
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let platform = &mut ctx.accounts.platform;
+        let previous_authority = platform.authority;
+        platform.authority = ctx.accounts.new_authority.key();
+        platform.pending_authority = None;
+        emit!(AuthorityTransferred { previous_authority, new_authority: platform.authority });
+        Ok(())
+    }
+
+    /// Grant `flags` (`Role::CREATOR`, `Role::RESOLVER`, ...) to `holder`, on top
+    /// of the roles it already holds. Platform authority only.
+    pub fn grant_role(ctx: Context<GrantRole>, holder: Pubkey, flags: u8) -> Result<()> {
+        require!(flags != 0 && flags & !Role::ALL == 0, GhostOddsError::InvalidRoleFlags);
+        let role = &mut ctx.accounts.role;
+        role.holder = holder;
+        role.flags |= flags;
+        role.bump = ctx.bumps.role;
+        emit!(RoleGranted { holder, flags, role_flags: role.flags });
+        Ok(())
+    }
+
+    /// Revoke `flags` from `holder`. Platform authority only.
+    pub fn revoke_role(ctx: Context<RevokeRole>, holder: Pubkey, flags: u8) -> Result<()> {
+        require!(flags != 0 && flags & !Role::ALL == 0, GhostOddsError::InvalidRoleFlags);
+        let role = &mut ctx.accounts.role;
+        role.flags &= !flags;
+        emit!(RoleRevoked { holder, flags, role_flags: role.flags });
         Ok(())
     }
 
+    /// Register (or rename) the symbol oracle markets on `feed_id` must name in their
+    /// `resolution_source`, e.g. "SOL/USD" for "pyth:SOL/USD". Platform authority only.
+    pub fn register_oracle_feed(ctx: Context<RegisterOracleFeed>, feed_id: [u8; 32], symbol: String) -> Result<()> {
+        require!(!symbol.is_empty() && symbol.len() <= MAX_FEED_SYMBOL_LEN, GhostOddsError::InvalidFeedSymbol);
+        let oracle_feed = &mut ctx.accounts.oracle_feed;
+        oracle_feed.feed_id = feed_id;
+        oracle_feed.symbol = symbol.clone();
+        oracle_feed.bump = ctx.bumps.oracle_feed;
+        emit!(OracleFeedRegistered { feed_id, symbol });
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn create_market(
         ctx: Context<CreateMarket>,
         question: String,
@@ -57,13 +262,82 @@ pub mod ghostodds {
         resolution_operator: u8,
         expires_at: i64,
         initial_liquidity: u64,
+        oracle_feed_id: Option<[u8; 32]>,
+        resolution_range: Option<PriceRange>,
+        twap: Option<TwapConfig>,
+        lmsr_b: Option<u64>,
     ) -> Result<()> {
         require!(question.len() <= MAX_QUESTION_LEN, GhostOddsError::QuestionTooLong);
         require!(description.len() <= MAX_DESCRIPTION_LEN, GhostOddsError::DescriptionTooLong);
         require!(category.len() <= MAX_CATEGORY_LEN, GhostOddsError::CategoryTooLong);
         require!(resolution_source.len() <= MAX_RESOLUTION_SOURCE_LEN, GhostOddsError::ResolutionSourceTooLong);
-        require!(resolution_operator <= 2, GhostOddsError::InvalidOperator);
+        require!(resolution_operator <= OP_SCALAR, GhostOddsError::InvalidOperator);
+        // Scalar markets interpolate an oracle price over their range and have no target value;
+        // every other oracle market compares the price against `resolution_value`
+        require!(resolution_operator != OP_SCALAR || resolution_value.is_none(), GhostOddsError::InvalidOperator);
+        let oracle_market = resolution_value.is_some() || resolution_operator == OP_SCALAR;
+        // Range bounds are required for (and only for) oracle "between" and scalar markets
+        require!(
+            resolution_range.is_some()
+                == (resolution_operator == OP_SCALAR || (resolution_operator == OP_BETWEEN && oracle_market)),
+            GhostOddsError::InvalidRange
+        );
+        if let Some(range) = resolution_range {
+            require!(range.lower < range.upper, GhostOddsError::InvalidRange);
+        }
+        if let Some(twap) = twap {
+            require!(oracle_market, GhostOddsError::InvalidTwapConfig);
+            require!(twap.window > 0 && twap.window <= LOCK_BEFORE_EXPIRY, GhostOddsError::InvalidTwapConfig);
+            require!(
+                twap.min_samples > 0 && twap.min_samples as usize <= TWAP_MAX_SAMPLES,
+                GhostOddsError::InvalidTwapConfig
+            );
+        }
         require!(initial_liquidity > 0, GhostOddsError::ZeroAmount);
+        // An LMSR market maker can lose at most b * ln(2), which the creator funds up front
+        if let Some(b) = lmsr_b {
+            require!(b > 0, GhostOddsError::ZeroAmount);
+            require!(initial_liquidity >= lmsr::max_loss(b)?, GhostOddsError::LmsrUnderfunded);
+        }
+
+        // Oracle markets are bound either to the feed account passed at creation (legacy Pyth or
+        // Switchboard, told apart by owner), or (Pyth pull oracle) to a feed ID that every
+        // PriceUpdateV2 used at resolution must carry. Manual markets are bound to neither.
+        let (oracle_kind, oracle_feed, oracle_feed_id) = match (oracle_market, ctx.accounts.oracle_account.as_ref()) {
+            (true, Some(oracle_info)) => {
+                let (kind, feed_id) = if *oracle_info.owner == SWITCHBOARD_PROGRAM_ID {
+                    (OracleKind::Switchboard, oracle::switchboard_feed_id(oracle_info)?)
+                } else {
+                    (OracleKind::PythLegacy, oracle::pyth_legacy_feed_id(oracle_info)?)
+                };
+                if let Some(expected_feed_id) = oracle_feed_id {
+                    require!(feed_id == expected_feed_id, GhostOddsError::OracleFeedMismatch);
+                }
+                (Some(kind), Some(oracle_info.key()), Some(feed_id))
+            }
+            (true, None) => (
+                Some(OracleKind::PythPull),
+                None,
+                Some(oracle_feed_id.ok_or(GhostOddsError::OracleRequired)?),
+            ),
+            (false, oracle_info) => {
+                require!(oracle_info.is_none() && oracle_feed_id.is_none(), GhostOddsError::InvalidOracle);
+                (None, None, None)
+            }
+        };
+        // Oracle markets must name their feed by the symbol the platform registered for it
+        let symbol = match oracle_feed_id {
+            Some(feed_id) => {
+                let registered = ctx.accounts.oracle_feed.as_ref().ok_or(GhostOddsError::OracleFeedNotRegistered)?;
+                require!(registered.feed_id == feed_id, GhostOddsError::OracleFeedMismatch);
+                Some(registered.symbol.as_str())
+            }
+            None => None,
+        };
+        require!(
+            source_matches_oracle(&resolution_source, oracle_kind.zip(symbol)),
+            GhostOddsError::ResolutionSourceMismatch
+        );
 
         let clock = Clock::get()?;
         let now = clock.unix_timestamp;
@@ -73,6 +347,16 @@ pub mod ghostodds {
         );
         let lock_time = expires_at.checked_sub(LOCK_BEFORE_EXPIRY).ok_or(GhostOddsError::MathOverflow)?;
 
+        // Anyone may create a market; without the creator role it waits for a curator's review
+        // behind a bond, slashed if the market is rejected
+        let curated = ctx.accounts.platform.authority == ctx.accounts.authority.key()
+            || ctx.accounts.creator_role.as_ref().is_some_and(|role| role.has(Role::CREATOR));
+        if !curated {
+            let treasury = ctx.accounts.treasury.as_ref().ok_or(GhostOddsError::CreatorBondAccountsRequired)?;
+            require!(treasury.mint == ctx.accounts.collateral_mint.key(), GhostOddsError::InvalidTreasury);
+        }
+        let creator_bond = if curated { 0 } else { ctx.accounts.platform.creator_bond };
+
         let platform = &mut ctx.accounts.platform;
         let market_id = platform.market_count;
         platform.market_count = platform.market_count.checked_add(1).ok_or(GhostOddsError::MathOverflow)?;
@@ -143,12 +427,87 @@ pub mod ghostodds {
             initial_liquidity,
         )?;
 
-        let half = initial_liquidity.checked_div(2).ok_or(GhostOddsError::MathOverflow)?;
-        require!(half > 0, GhostOddsError::ZeroAmount);
+        // Create LP share mint via CPI
+        let lp_mint_seeds: &[&[u8]] = &[b"lp_mint", market_id_bytes.as_ref(), &[ctx.bumps.lp_mint]];
+        system_program::create_account(
+            CpiContext::new_with_signer(ctx.accounts.system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.lp_mint.to_account_info(),
+                }, &[lp_mint_seeds]),
+            rent.minimum_balance(MINT_SIZE), MINT_SIZE as u64, &ctx.accounts.token_program.key(),
+        )?;
+        token::initialize_mint(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(),
+                InitializeMint { mint: ctx.accounts.lp_mint.to_account_info(), rent: ctx.accounts.rent.to_account_info() }),
+            6, &market_key, None,
+        )?;
+
+        if creator_bond > 0 {
+            let bond_vault = ctx.accounts.creator_bond_vault.as_ref().ok_or(GhostOddsError::CreatorBondAccountsRequired)?;
+            let bond_seeds: &[&[u8]] = &[
+                b"creator_bond", market_id_bytes.as_ref(),
+                &[ctx.bumps.creator_bond_vault.ok_or(GhostOddsError::CreatorBondAccountsRequired)?],
+            ];
+            system_program::create_account(
+                CpiContext::new_with_signer(ctx.accounts.system_program.to_account_info(),
+                    system_program::CreateAccount {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: bond_vault.to_account_info(),
+                    }, &[bond_seeds]),
+                rent.minimum_balance(TOKEN_ACCOUNT_SIZE), TOKEN_ACCOUNT_SIZE as u64, &ctx.accounts.token_program.key(),
+            )?;
+            token::initialize_account(
+                CpiContext::new(ctx.accounts.token_program.to_account_info(),
+                    InitializeAccount {
+                        account: bond_vault.to_account_info(),
+                        mint: ctx.accounts.collateral_mint.to_account_info(),
+                        authority: ctx.accounts.market.to_account_info(),
+                        rent: ctx.accounts.rent.to_account_info(),
+                    }),
+            )?;
+            token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: ctx.accounts.authority_collateral.to_account_info(),
+                to: bond_vault.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            }), creator_bond)?;
+        }
+
+        // Create the creator's LP share account via CPI and mint one share per reserve unit
+        let authority_key = ctx.accounts.authority.key();
+        let lp_tokens_seeds: &[&[u8]] = &[
+            b"lp_tokens", market_id_bytes.as_ref(), authority_key.as_ref(), &[ctx.bumps.authority_lp_tokens],
+        ];
+        system_program::create_account(
+            CpiContext::new_with_signer(ctx.accounts.system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.authority_lp_tokens.to_account_info(),
+                }, &[lp_tokens_seeds]),
+            rent.minimum_balance(TOKEN_ACCOUNT_SIZE), TOKEN_ACCOUNT_SIZE as u64, &ctx.accounts.token_program.key(),
+        )?;
+        token::initialize_account(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(),
+                InitializeAccount {
+                    account: ctx.accounts.authority_lp_tokens.to_account_info(),
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                }),
+        )?;
+        let market_signer_seeds: &[&[u8]] = &[b"market", market_id_bytes.as_ref(), &[ctx.bumps.market]];
+        // One share per unit deposited. LMSR shares have no reserves behind them while the market
+        // trades; after settlement they reclaim whatever part of the subsidy was not spent
+        token::mint_to(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), MintTo {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            to: ctx.accounts.authority_lp_tokens.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, &[market_signer_seeds]), initial_liquidity)?;
 
         let market = &mut ctx.accounts.market;
         market.market_id = market_id;
-        market.authority = ctx.accounts.authority.key();
+        market.authority = platform.authority;
+        market.creator = ctx.accounts.authority.key();
         market.question = question.clone();
         market.description = description;
         market.category = category;
@@ -156,497 +515,3582 @@ pub mod ghostodds {
         market.yes_mint = ctx.accounts.yes_mint.key();
         market.no_mint = ctx.accounts.no_mint.key();
         market.vault = ctx.accounts.vault.key();
-        market.yes_amount = half;
-        market.no_amount = half;
+        market.lp_mint = ctx.accounts.lp_mint.key();
+        match lmsr_b {
+            Some(b) => {
+                market.curve = Curve::Lmsr;
+                market.lmsr_b = b;
+            }
+            // The deposit mints `initial_liquidity` complete sets, all held by the pool
+            None => {
+                market.curve = Curve::ConstantProduct;
+                market.yes_amount = initial_liquidity;
+                market.no_amount = initial_liquidity;
+            }
+        }
         market.total_liquidity = initial_liquidity;
         market.volume = 0;
         market.resolution_source = resolution_source;
         market.resolution_value = resolution_value;
         market.resolution_operator = resolution_operator;
+        market.resolution_range = resolution_range;
+        market.twap = twap;
         market.created_at = now;
         market.expires_at = expires_at;
         market.lock_time = lock_time;
         market.resolved_at = None;
         market.outcome = None;
-        market.status = STATUS_ACTIVE;
+        market.status = if curated { STATUS_ACTIVE } else { STATUS_PENDING_REVIEW };
+        market.creator_bond = creator_bond;
+        market.creator_fee_bps = if curated { 0 } else { platform.creator_fee_bps };
         market.fee_bps = platform.fee_bps;
+        // Without LP shares there is nobody to pay an LP fee to, so LMSR fees all go to the treasury
+        market.lp_fee_bps = if lmsr_b.is_some() { 0 } else { platform.lp_fee_bps };
         market.bump = ctx.bumps.market;
+        market.oracle_kind = oracle_kind;
+        market.oracle_feed = oracle_feed;
+        market.oracle_feed_id = oracle_feed_id;
+        market.outcome_count = 2;
 
         emit!(MarketCreated { market_id, question, expires_at, initial_liquidity });
+        if !curated {
+            emit!(MarketSubmitted { market_id, creator: market.creator, creator_bond });
+        }
         Ok(())
     }
 
-    pub fn buy_outcome(ctx: Context<BuyOutcome>, amount: u64, is_yes: bool, min_tokens_out: u64) -> Result<()> {
-        require!(amount > 0, GhostOddsError::ZeroAmount);
+    /// Open a market pending review for trading (platform authority or curator) and return the
+    /// creator's bond.
+    pub fn approve_market(ctx: Context<ReviewMarket>) -> Result<()> {
         let market = &ctx.accounts.market;
         let clock = Clock::get()?;
-        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(market.status == STATUS_PENDING_REVIEW, GhostOddsError::MarketNotPendingReview);
         require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
 
-        let fee = ((amount as u128)
-            .checked_mul(market.fee_bps as u128).ok_or(GhostOddsError::MathOverflow)?
-            .checked_add(9999).ok_or(GhostOddsError::MathOverflow)?
-            .checked_div(10000).ok_or(GhostOddsError::MathOverflow)?) as u64;
-        let input_after_fee = amount.checked_sub(fee).ok_or(GhostOddsError::MathOverflow)?;
-        require!(input_after_fee > 0, GhostOddsError::ZeroAmount);
+        let bond = market.creator_bond;
+        let accounts = &ctx.accounts;
+        release_creator_bond(&accounts.market, &accounts.creator_bond_vault, &accounts.creator, &accounts.token_program,
+            accounts.creator_collateral.to_account_info())?;
 
-        let (input_reserve, output_reserve) = if is_yes {
-            (market.no_amount, market.yes_amount)
-        } else {
-            (market.yes_amount, market.no_amount)
-        };
+        let market = &mut ctx.accounts.market;
+        market.creator_bond = 0;
+        market.status = STATUS_ACTIVE;
+        emit!(MarketApproved { market_id: market.market_id, curator: ctx.accounts.curator.key(), bond_returned: bond });
+        Ok(())
+    }
 
-        let k = (input_reserve as u128).checked_mul(output_reserve as u128).ok_or(GhostOddsError::MathOverflow)?;
-        let new_input_reserve = (input_reserve as u128).checked_add(input_after_fee as u128).ok_or(GhostOddsError::MathOverflow)?;
-        let new_output_reserve = k.checked_div(new_input_reserve).ok_or(GhostOddsError::MathOverflow)?;
-        let tokens_out = ((output_reserve as u128).checked_sub(new_output_reserve).ok_or(GhostOddsError::MathOverflow)?) as u64;
+    /// Reject a market pending review (platform authority or curator): its liquidity goes back
+    /// to the creator and its bond to the treasury.
+    pub fn reject_market(ctx: Context<ReviewMarket>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.status == STATUS_PENDING_REVIEW, GhostOddsError::MarketNotPendingReview);
 
-        require!(tokens_out > 0, GhostOddsError::ZeroAmount);
-        require!(tokens_out >= min_tokens_out, GhostOddsError::SlippageExceeded);
+        let accounts = &ctx.accounts;
+        let liquidity = refund_creator_liquidity(&accounts.market, &accounts.vault, &accounts.token_program,
+            accounts.creator_collateral.to_account_info())?;
+        let bond = market.creator_bond;
+        release_creator_bond(&accounts.market, &accounts.creator_bond_vault, &accounts.creator, &accounts.token_program,
+            accounts.treasury.to_account_info())?;
 
-        // Transfer net amount (after fee) to vault
-        token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
-            from: ctx.accounts.user_collateral.to_account_info(),
-            to: ctx.accounts.vault.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        }), input_after_fee)?;
+        close_unreviewed_market(&mut ctx.accounts.market);
+        emit!(MarketRejected {
+            market_id: ctx.accounts.market.market_id, curator: ctx.accounts.curator.key(), liquidity_refunded: liquidity,
+            bond_slashed: bond,
+        });
+        Ok(())
+    }
 
-        // Transfer fee directly to treasury
-        if fee > 0 {
-            token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
-                from: ctx.accounts.user_collateral.to_account_info(),
-                to: ctx.accounts.treasury.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            }), fee)?;
-        }
+    /// Withdraw a market that is still pending review `REVIEW_TIMEOUT` after creation, or at
+    /// lock, when it can no longer be approved (creator only): its liquidity and bond go back to
+    /// the creator.
+    pub fn withdraw_unreviewed_market(ctx: Context<WithdrawUnreviewedMarket>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let clock = Clock::get()?;
+        require!(market.status == STATUS_PENDING_REVIEW, GhostOddsError::MarketNotPendingReview);
+        let review_deadline = market.created_at.checked_add(REVIEW_TIMEOUT).ok_or(GhostOddsError::MathOverflow)?;
+        require!(
+            clock.unix_timestamp >= review_deadline.min(market.lock_time),
+            GhostOddsError::ReviewPeriodActive
+        );
 
-        let market_id_bytes = market.market_id.to_le_bytes();
-        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
-        let (mint_info, dest_info) = if is_yes {
-            (ctx.accounts.yes_mint.to_account_info(), ctx.accounts.user_yes_tokens.to_account_info())
-        } else {
-            (ctx.accounts.no_mint.to_account_info(), ctx.accounts.user_no_tokens.to_account_info())
-        };
-        token::mint_to(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(),
-            MintTo { mint: mint_info, to: dest_info, authority: ctx.accounts.market.to_account_info() }, signer_seeds),
-            tokens_out)?;
+        let accounts = &ctx.accounts;
+        let liquidity = refund_creator_liquidity(&accounts.market, &accounts.vault, &accounts.token_program,
+            accounts.creator_collateral.to_account_info())?;
+        let bond = market.creator_bond;
+        release_creator_bond(&accounts.market, &accounts.creator_bond_vault, &accounts.creator, &accounts.token_program,
+            accounts.creator_collateral.to_account_info())?;
+
+        close_unreviewed_market(&mut ctx.accounts.market);
+        emit!(UnreviewedMarketWithdrawn {
+            market_id: ctx.accounts.market.market_id, creator: ctx.accounts.creator.key(), liquidity_refunded: liquidity,
+            bond_returned: bond,
+        });
+        Ok(())
+    }
 
+    /// Set or clear the market's dynamic fee schedule (platform authority only). The fee ramps
+    /// from `fee_bps` at creation to `max_fee_bps` at lock, plus the volatility component. Each
+    /// call raises the ceiling and volatility factor by at most `MAX_FEE_CEILING_STEP_BPS` and
+    /// `MAX_VOLATILITY_FACTOR_STEP_BPS`.
+    pub fn set_fee_schedule(ctx: Context<SetFeeSchedule>, fee_schedule: Option<FeeSchedule>) -> Result<()> {
         let market = &mut ctx.accounts.market;
-        if is_yes {
-            market.yes_amount = new_output_reserve as u64;
-            market.no_amount = new_input_reserve as u64;
-        } else {
-            market.yes_amount = new_input_reserve as u64;
-            market.no_amount = new_output_reserve as u64;
+        require!(market.outcome_count == 2, GhostOddsError::NotBinaryMarket);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        if let Some(schedule) = fee_schedule {
+            require!(
+                schedule.max_fee_bps >= market.fee_bps && schedule.max_fee_bps <= MAX_FEE_BPS,
+                GhostOddsError::InvalidFeeSchedule
+            );
+            let (ceiling, volatility_factor) = market.fee_schedule
+                .map_or((market.fee_bps, 0), |current| (current.max_fee_bps, current.volatility_factor_bps));
+            require!(
+                schedule.max_fee_bps.saturating_sub(ceiling) <= MAX_FEE_CEILING_STEP_BPS
+                    && schedule.volatility_factor_bps.saturating_sub(volatility_factor) <= MAX_VOLATILITY_FACTOR_STEP_BPS,
+                GhostOddsError::FeeIncreaseTooLarge
+            );
         }
-        market.total_liquidity = market.total_liquidity.checked_add(input_after_fee).ok_or(GhostOddsError::MathOverflow)?;
-        market.volume = market.volume.checked_add(amount).ok_or(GhostOddsError::MathOverflow)?;
-
-        // Finding 6: increment platform total_volume
-        let platform = &mut ctx.accounts.platform;
-        platform.total_volume = platform.total_volume.checked_add(amount).ok_or(GhostOddsError::MathOverflow)?;
+        market.fee_schedule = fee_schedule;
+        // Volatility is measured from the next trade on
+        market.price_anchor_at = 0;
+        emit!(FeeScheduleUpdated { market_id: market.market_id, fee_schedule });
+        Ok(())
+    }
 
-        let position = &mut ctx.accounts.user_position;
-        position.user = ctx.accounts.user.key();
-        position.market_id = market.market_id;
-        if is_yes {
-            position.yes_tokens = position.yes_tokens.checked_add(tokens_out).ok_or(GhostOddsError::MathOverflow)?;
-        } else {
-            position.no_tokens = position.no_tokens.checked_add(tokens_out).ok_or(GhostOddsError::MathOverflow)?;
-        }
-        position.total_deposited = position.total_deposited.checked_add(amount).ok_or(GhostOddsError::MathOverflow)?;
-        position.bump = ctx.bumps.user_position;
+    /// Corrects the market's text fields until it settles, under the same limits as creation
+    /// (platform authority or curator).
+    pub fn update_market_metadata(
+        ctx: Context<UpdateMarket>,
+        question: String,
+        description: String,
+        category: String,
+    ) -> Result<()> {
+        require!(question.len() <= MAX_QUESTION_LEN, GhostOddsError::QuestionTooLong);
+        require!(description.len() <= MAX_DESCRIPTION_LEN, GhostOddsError::DescriptionTooLong);
+        require!(category.len() <= MAX_CATEGORY_LEN, GhostOddsError::CategoryTooLong);
+        let market = &mut ctx.accounts.market;
+        require!(
+            matches!(market.status, STATUS_ACTIVE | STATUS_PAUSED | STATUS_PENDING_REVIEW),
+            GhostOddsError::MarketNotActive
+        );
+        market.question = question.clone();
+        market.description = description.clone();
+        market.category = category.clone();
+        emit!(MarketMetadataUpdated { market_id: market.market_id, question, description, category });
+        Ok(())
+    }
 
-        emit!(OutcomePurchased { market_id: market.market_id, user: ctx.accounts.user.key(), is_yes, amount_in: amount, tokens_out, fee });
+    /// Postpones expiry for a delayed real-world event by at most `MAX_EXPIRY_EXTENSION`; only
+    /// possible before trading locks (platform authority or curator).
+    pub fn extend_market_expiry(ctx: Context<UpdateMarket>, expires_at: i64) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(
+            matches!(market.status, STATUS_ACTIVE | STATUS_PAUSED | STATUS_PENDING_REVIEW),
+            GhostOddsError::MarketNotActive
+        );
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
+        require!(expires_at > market.expires_at, GhostOddsError::ExpiryNotExtended);
+        require!(
+            expires_at - market.expires_at <= MAX_EXPIRY_EXTENSION,
+            GhostOddsError::ExpiryExtensionTooLong
+        );
+        market.expires_at = expires_at;
+        market.lock_time = expires_at.checked_sub(LOCK_BEFORE_EXPIRY).ok_or(GhostOddsError::MathOverflow)?;
+        emit!(MarketExpiryExtended { market_id: market.market_id, expires_at, lock_time: market.lock_time });
         Ok(())
     }
 
-    pub fn sell_outcome(ctx: Context<SellOutcome>, amount: u64, is_yes: bool, min_collateral_out: u64) -> Result<()> {
+    /// Resting asks priced below the AMM's marginal price are filled first, at the market's
+    /// trading fee, when the order book accounts are passed; the rest of `amount` trades against
+    /// the AMM.
+    /// `deadline` (unix timestamp) and `max_price_impact_bps` (relative move of the bought
+    /// outcome's price on the AMM) are optional guards against delayed landing and sandwiching.
+    pub fn buy_outcome(
+        ctx: Context<BuyOutcome>,
+        amount: u64,
+        is_yes: bool,
+        min_tokens_out: u64,
+        deadline: Option<i64>,
+        max_price_impact_bps: Option<u16>,
+    ) -> Result<()> {
         require!(amount > 0, GhostOddsError::ZeroAmount);
         let market = &ctx.accounts.market;
         let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
         require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
         require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
+        if let Some(deadline) = deadline {
+            require!(clock.unix_timestamp <= deadline, GhostOddsError::DeadlineExceeded);
+        }
 
-        let (input_reserve, output_reserve) = if is_yes {
-            (market.yes_amount, market.no_amount)
-        } else {
-            (market.no_amount, market.yes_amount)
-        };
-
-        let k = (input_reserve as u128).checked_mul(output_reserve as u128).ok_or(GhostOddsError::MathOverflow)?;
-        let new_input_reserve = (input_reserve as u128).checked_add(amount as u128).ok_or(GhostOddsError::MathOverflow)?;
-        let new_output_reserve = k.checked_div(new_input_reserve).ok_or(GhostOddsError::MathOverflow)?;
-        let collateral_before_fee = ((output_reserve as u128).checked_sub(new_output_reserve).ok_or(GhostOddsError::MathOverflow)?) as u64;
-
-        let fee = ((collateral_before_fee as u128)
-            .checked_mul(market.fee_bps as u128).ok_or(GhostOddsError::MathOverflow)?
-            .checked_add(9999).ok_or(GhostOddsError::MathOverflow)?
-            .checked_div(10000).ok_or(GhostOddsError::MathOverflow)?) as u64;
-        let collateral_out = collateral_before_fee.checked_sub(fee).ok_or(GhostOddsError::MathOverflow)?;
-        require!(collateral_out > 0, GhostOddsError::ZeroAmount);
-        require!(collateral_out >= min_collateral_out, GhostOddsError::SlippageExceeded);
+        let pool = ctx.accounts.market.trade_pool(clock.unix_timestamp)?;
+        let amm_price = math::price_yes(&pool)?;
+        let amm_price = if is_yes { amm_price } else { math::PRICE_SCALE - amm_price };
+        // Highest ask price strictly below the AMM's marginal price
+        let limit_price_bps = (amm_price * 10000).div_ceil(math::PRICE_SCALE).saturating_sub(1) as u16;
+        let (book_tokens, book_spent) =
+            fill_resting_asks(ctx.accounts, ctx.bumps.user_position, &pool, is_yes, amount, limit_price_bps)?;
+        // The LP fee of book fills joins the reserves
+        let market = &ctx.accounts.market;
+        let pool = math::Pool { yes_amount: market.yes_amount, no_amount: market.no_amount, ..pool };
 
-        let (mint_info, from_info) = if is_yes {
-            (ctx.accounts.yes_mint.to_account_info(), ctx.accounts.user_yes_tokens.to_account_info())
-        } else {
-            (ctx.accounts.no_mint.to_account_info(), ctx.accounts.user_no_tokens.to_account_info())
+        let remaining = amount.checked_sub(book_spent).ok_or(GhostOddsError::MathOverflow)?;
+        let quote = match math::quote_buy(&pool, is_yes, remaining) {
+            Ok(quote) => Some(quote),
+            // Dust left over after book fills stays with the buyer
+            Err(_) if book_tokens > 0 => None,
+            Err(err) => return Err(err),
         };
-        token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(),
-            Burn { mint: mint_info, from: from_info, authority: ctx.accounts.user.to_account_info() }), amount)?;
+        let amm_tokens = quote.map_or(0, |quote| quote.tokens_out);
+        require!(
+            book_tokens.checked_add(amm_tokens).ok_or(GhostOddsError::MathOverflow)? >= min_tokens_out,
+            GhostOddsError::SlippageExceeded
+        );
+        let Some(quote) = quote else { return Ok(()) };
+        if let Some(max_price_impact_bps) = max_price_impact_bps {
+            let after = math::Pool { yes_amount: quote.yes_amount, no_amount: quote.no_amount, ..pool };
+            require!(
+                math::price_impact_bps(&pool, &after, is_yes)? <= max_price_impact_bps as u64,
+                GhostOddsError::PriceImpactTooHigh
+            );
+        }
+        execute_buy(ctx.accounts, ctx.bumps.user_position, is_yes, quote)
+    }
 
-        let market_id_bytes = market.market_id.to_le_bytes();
-        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+    /// Buy exactly `tokens_out` YES or NO tokens, paying at most `max_amount_in` collateral
+    /// (fee included).
+    pub fn buy_outcome_exact_out(ctx: Context<BuyOutcome>, tokens_out: u64, is_yes: bool, max_amount_in: u64) -> Result<()> {
+        require!(tokens_out > 0, GhostOddsError::ZeroAmount);
+        let market = &ctx.accounts.market;
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
 
-        // Transfer collateral to user
-        token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
-            from: ctx.accounts.vault.to_account_info(),
-            to: ctx.accounts.user_collateral.to_account_info(),
-            authority: ctx.accounts.market.to_account_info(),
-        }, signer_seeds), collateral_out)?;
+        let pool = ctx.accounts.market.trade_pool(clock.unix_timestamp)?;
+        let quote = math::quote_buy_exact_out(&pool, is_yes, tokens_out)?;
+        require!(quote.amount_in <= max_amount_in, GhostOddsError::SlippageExceeded);
+        execute_buy(ctx.accounts, ctx.bumps.user_position, is_yes, quote)
+    }
 
-        // Transfer fee from vault to treasury
-        if fee > 0 {
-            token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
-                from: ctx.accounts.vault.to_account_info(),
-                to: ctx.accounts.treasury.to_account_info(),
-                authority: ctx.accounts.market.to_account_info(),
-            }, signer_seeds), fee)?;
+    /// `deadline` and `max_price_impact_bps` guard the sale as in `buy_outcome`.
+    pub fn sell_outcome(
+        ctx: Context<SellOutcome>,
+        amount: u64,
+        is_yes: bool,
+        min_collateral_out: u64,
+        deadline: Option<i64>,
+        max_price_impact_bps: Option<u16>,
+    ) -> Result<()> {
+        require!(amount > 0, GhostOddsError::ZeroAmount);
+        let market = &ctx.accounts.market;
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
+        if let Some(deadline) = deadline {
+            require!(clock.unix_timestamp <= deadline, GhostOddsError::DeadlineExceeded);
         }
 
-        let market = &mut ctx.accounts.market;
-        if is_yes {
-            market.yes_amount = new_input_reserve as u64;
-            market.no_amount = new_output_reserve as u64;
-        } else {
-            market.no_amount = new_input_reserve as u64;
-            market.yes_amount = new_output_reserve as u64;
+        let pool = ctx.accounts.market.trade_pool(clock.unix_timestamp)?;
+        let quote = math::quote_sell(&pool, is_yes, amount)?;
+        require!(quote.collateral_out >= min_collateral_out, GhostOddsError::SlippageExceeded);
+        if let Some(max_price_impact_bps) = max_price_impact_bps {
+            let after = math::Pool { yes_amount: quote.yes_amount, no_amount: quote.no_amount, ..pool };
+            require!(
+                math::price_impact_bps(&pool, &after, is_yes)? <= max_price_impact_bps as u64,
+                GhostOddsError::PriceImpactTooHigh
+            );
         }
-        market.volume = market.volume.checked_add(collateral_before_fee).ok_or(GhostOddsError::MathOverflow)?;
+        execute_sell(ctx.accounts, is_yes, quote)
+    }
 
-        // Finding 7: decrement total_liquidity on sell
-        market.total_liquidity = market.total_liquidity.checked_sub(collateral_out).ok_or(GhostOddsError::MathOverflow)?;
+    /// Sell YES or NO tokens for exactly `collateral_out` (after fees), burning at most
+    /// `max_tokens_in`.
+    pub fn sell_outcome_exact_out(ctx: Context<SellOutcome>, collateral_out: u64, is_yes: bool, max_tokens_in: u64) -> Result<()> {
+        require!(collateral_out > 0, GhostOddsError::ZeroAmount);
+        let market = &ctx.accounts.market;
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
 
-        // Finding 6: increment platform total_volume
-        let platform = &mut ctx.accounts.platform;
-        platform.total_volume = platform.total_volume.checked_add(collateral_before_fee).ok_or(GhostOddsError::MathOverflow)?;
+        let pool = ctx.accounts.market.trade_pool(clock.unix_timestamp)?;
+        let quote = math::quote_sell_exact_out(&pool, is_yes, collateral_out)?;
+        require!(quote.tokens_in <= max_tokens_in, GhostOddsError::SlippageExceeded);
+        execute_sell(ctx.accounts, is_yes, quote)
+    }
+
+    /// Deposit `amount` collateral into the vault for `amount` YES and `amount` NO tokens.
+    /// Unlike `buy_outcome`, this does not move the AMM reserves.
+    pub fn mint_complete_set(ctx: Context<MintCompleteSet>, amount: u64) -> Result<()> {
+        require!(amount > 0, GhostOddsError::ZeroAmount);
+        let market = &ctx.accounts.market;
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
+
+        token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.user_collateral.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        }), amount)?;
+
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        token::mint_to(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), MintTo {
+            mint: ctx.accounts.yes_mint.to_account_info(),
+            to: ctx.accounts.user_yes_tokens.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), amount)?;
+        token::mint_to(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), MintTo {
+            mint: ctx.accounts.no_mint.to_account_info(),
+            to: ctx.accounts.user_no_tokens.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), amount)?;
 
+        let market = &ctx.accounts.market;
         let position = &mut ctx.accounts.user_position;
-        if is_yes {
-            position.yes_tokens = position.yes_tokens.checked_sub(amount).ok_or(GhostOddsError::MathOverflow)?;
-        } else {
-            position.no_tokens = position.no_tokens.checked_sub(amount).ok_or(GhostOddsError::MathOverflow)?;
-        }
-        position.total_withdrawn = position.total_withdrawn.checked_add(collateral_out).ok_or(GhostOddsError::MathOverflow)?;
+        position.user = ctx.accounts.user.key();
+        position.market_id = market.market_id;
+        position.yes_tokens = position.yes_tokens.checked_add(amount).ok_or(GhostOddsError::MathOverflow)?;
+        position.no_tokens = position.no_tokens.checked_add(amount).ok_or(GhostOddsError::MathOverflow)?;
+        position.total_deposited = position.total_deposited.checked_add(amount).ok_or(GhostOddsError::MathOverflow)?;
+        position.bump = ctx.bumps.user_position;
 
-        emit!(OutcomeSold { market_id: market.market_id, user: ctx.accounts.user.key(), is_yes, tokens_in: amount, collateral_out, fee });
+        emit!(CompleteSetMinted { market_id: market.market_id, user: ctx.accounts.user.key(), amount });
         Ok(())
     }
 
-    pub fn resolve_market(ctx: Context<ResolveMarket>, outcome: bool) -> Result<()> {
-        let market = &mut ctx.accounts.market;
-        let clock = Clock::get()?;
+    /// Burn `amount` YES and `amount` NO tokens and withdraw `amount` collateral from the vault.
+    pub fn merge_complete_set(ctx: Context<MergeCompleteSet>, amount: u64) -> Result<()> {
+        require!(amount > 0, GhostOddsError::ZeroAmount);
+        let market = &ctx.accounts.market;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
 
-        // Finding 4: remove status == 1 reference (never set)
+        token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(), Burn {
+            mint: ctx.accounts.yes_mint.to_account_info(),
+            from: ctx.accounts.user_yes_tokens.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        }), amount)?;
+        token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(), Burn {
+            mint: ctx.accounts.no_mint.to_account_info(),
+            from: ctx.accounts.user_no_tokens.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        }), amount)?;
+
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.user_collateral.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), amount)?;
+
+        let position = &mut ctx.accounts.user_position;
+        position.yes_tokens = position.yes_tokens.checked_sub(amount).ok_or(GhostOddsError::MathOverflow)?;
+        position.no_tokens = position.no_tokens.checked_sub(amount).ok_or(GhostOddsError::MathOverflow)?;
+        position.total_withdrawn = position.total_withdrawn.checked_add(amount).ok_or(GhostOddsError::MathOverflow)?;
+
+        emit!(CompleteSetMerged { market_id: market.market_id, user: ctx.accounts.user.key(), amount });
+        Ok(())
+    }
+
+    /// Deposit `amount` collateral as liquidity. The larger reserve grows by `amount` and the
+    /// other in proportion, keeping the price; the outcome tokens of the unmatched difference are
+    /// minted to the LP. LP shares are issued pro rata to the larger reserve, one per unit like at
+    /// creation when the pool has been emptied.
+    pub fn add_liquidity(ctx: Context<AddLiquidity>, amount: u64, min_shares_out: u64) -> Result<()> {
+        require!(amount > 0, GhostOddsError::ZeroAmount);
+        let market = &ctx.accounts.market;
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
         require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
-        require!(clock.unix_timestamp >= market.expires_at, GhostOddsError::MarketNotExpired);
+        require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
+        require!(market.curve == Curve::ConstantProduct, GhostOddsError::UnsupportedCurve);
 
-        let grace_deadline = market.expires_at
-            .checked_add(RESOLUTION_GRACE_PERIOD)
-            .ok_or(GhostOddsError::MathOverflow)?;
-        let within_grace = clock.unix_timestamp < grace_deadline;
+        let max_reserve = market.yes_amount.max(market.no_amount) as u128;
+        let lp_supply = ctx.accounts.lp_mint.supply as u128;
+        let (yes_added, no_added, shares) = if max_reserve == 0 || lp_supply == 0 {
+            // Every share has been withdrawn: reseed the pool at even odds, like `create_market`
+            (amount, amount, amount)
+        } else {
+            (
+                ((amount as u128).checked_mul(market.yes_amount as u128).ok_or(GhostOddsError::MathOverflow)?
+                    .checked_div(max_reserve).ok_or(GhostOddsError::MathOverflow)?) as u64,
+                ((amount as u128).checked_mul(market.no_amount as u128).ok_or(GhostOddsError::MathOverflow)?
+                    .checked_div(max_reserve).ok_or(GhostOddsError::MathOverflow)?) as u64,
+                ((amount as u128).checked_mul(lp_supply).ok_or(GhostOddsError::MathOverflow)?
+                    .checked_div(max_reserve).ok_or(GhostOddsError::MathOverflow)?) as u64,
+            )
+        };
+        require!(shares > 0, GhostOddsError::ZeroAmount);
+        require!(shares >= min_shares_out, GhostOddsError::SlippageExceeded);
+        let yes_returned = amount.checked_sub(yes_added).ok_or(GhostOddsError::MathOverflow)?;
+        let no_returned = amount.checked_sub(no_added).ok_or(GhostOddsError::MathOverflow)?;
 
-        // Within grace period: only authority can resolve
-        // After grace period: anyone can resolve (permissionless fallback)
-        if within_grace {
-            require!(
-                ctx.accounts.resolver.key() == market.authority,
-                GhostOddsError::Unauthorized
-            );
+        token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.user_collateral.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        }), amount)?;
+
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        token::mint_to(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), MintTo {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            to: ctx.accounts.user_lp_tokens.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), shares)?;
+        if yes_returned > 0 {
+            token::mint_to(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), MintTo {
+                mint: ctx.accounts.yes_mint.to_account_info(),
+                to: ctx.accounts.user_yes_tokens.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), yes_returned)?;
+        }
+        if no_returned > 0 {
+            token::mint_to(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), MintTo {
+                mint: ctx.accounts.no_mint.to_account_info(),
+                to: ctx.accounts.user_no_tokens.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), no_returned)?;
         }
 
-        // Determine outcome: use oracle for markets with resolution_value, manual otherwise
-        let resolved_outcome = if let Some(resolution_value) = market.resolution_value {
-            // Oracle-resolved market: require Pyth price account
-            let pyth_info = ctx.accounts.pyth_price_account
-                .as_ref()
-                .ok_or(GhostOddsError::OracleRequired)?;
+        let market = &mut ctx.accounts.market;
+        market.yes_amount = market.yes_amount.checked_add(yes_added).ok_or(GhostOddsError::MathOverflow)?;
+        market.no_amount = market.no_amount.checked_add(no_added).ok_or(GhostOddsError::MathOverflow)?;
+        market.total_liquidity = market.total_liquidity.checked_add(amount).ok_or(GhostOddsError::MathOverflow)?;
+
+        let position = &mut ctx.accounts.user_position;
+        position.user = ctx.accounts.user.key();
+        position.market_id = market.market_id;
+        position.yes_tokens = position.yes_tokens.checked_add(yes_returned).ok_or(GhostOddsError::MathOverflow)?;
+        position.no_tokens = position.no_tokens.checked_add(no_returned).ok_or(GhostOddsError::MathOverflow)?;
+        position.total_deposited = position.total_deposited.checked_add(amount).ok_or(GhostOddsError::MathOverflow)?;
+        position.bump = ctx.bumps.user_position;
 
-            // Validate owner is the Pyth v2 program
-            let pyth_program_id: Pubkey = "FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH"
-                .parse().unwrap();
-            require!(
-                *pyth_info.owner == pyth_program_id,
-                GhostOddsError::InvalidOracle
-            );
+        emit!(LiquidityAdded {
+            market_id: market.market_id, provider: ctx.accounts.user.key(), amount, shares, yes_returned, no_returned,
+        });
+        Ok(())
+    }
 
-            let price_feed = SolanaPriceAccount::account_info_to_feed(&pyth_info)
-                .map_err(|_| GhostOddsError::InvalidOracle)?;
-            let current_price = price_feed
-                .get_price_no_older_than(clock.unix_timestamp, PYTH_MAX_STALENESS)
-                .ok_or(GhostOddsError::StalePriceData)?;
-
-            // Validate confidence: conf / |price| <= 5%
-            let abs_price = (current_price.price as i128).unsigned_abs();
-            require!(abs_price > 0, GhostOddsError::InvalidOracle);
-            let conf_bps = (current_price.conf as u128)
-                .checked_mul(10000)
+    /// Burn `shares` LP shares. While the market trades, they are paid their pro-rata part of both
+    /// reserves: matched YES/NO pairs as collateral (one unit per pair, like `merge_complete_set`)
+    /// and the unmatched remainder as outcome tokens. Once the market has settled, they are paid
+    /// their pro-rata part of the vault left after every outstanding YES and NO token is valued at
+    /// its payout, so LPs and token holders can withdraw in any order.
+    pub fn remove_liquidity(ctx: Context<RemoveLiquidity>, shares: u64, min_collateral_out: u64) -> Result<()> {
+        require!(shares > 0, GhostOddsError::ZeroAmount);
+        let market = &ctx.accounts.market;
+        let lp_supply = ctx.accounts.lp_mint.supply as u128;
+        let (collateral_out, yes_out, no_out) = if matches!(market.status, STATUS_RESOLVED | STATUS_INVALID | STATUS_CANCELLED) {
+            let (yes_weight, no_weight, scale) = market.settlement_weights()?;
+            let liabilities = (ctx.accounts.yes_mint.supply as u128).checked_mul(yes_weight).ok_or(GhostOddsError::MathOverflow)?
+                .checked_add((ctx.accounts.no_mint.supply as u128).checked_mul(no_weight).ok_or(GhostOddsError::MathOverflow)?)
                 .ok_or(GhostOddsError::MathOverflow)?
-                .checked_div(abs_price)
-                .ok_or(GhostOddsError::MathOverflow)?;
-            require!(conf_bps <= PYTH_MAX_CONF_BPS as u128, GhostOddsError::PriceConfidenceTooWide);
+                .div_ceil(scale);
+            let surplus = (ctx.accounts.vault.amount as u128).saturating_sub(liabilities);
+            let collateral_out = surplus.checked_mul(shares as u128).ok_or(GhostOddsError::MathOverflow)?
+                .checked_div(lp_supply).ok_or(GhostOddsError::MathOverflow)? as u64;
+            (collateral_out, 0, 0)
+        } else {
+            // Settled markets pay out regardless of the pause, like redemptions; live pools do not
+            require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+            require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+            require!(Clock::get()?.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
+            require!(market.curve == Curve::ConstantProduct, GhostOddsError::UnsupportedCurve);
+            let yes_out = ((market.yes_amount as u128).checked_mul(shares as u128).ok_or(GhostOddsError::MathOverflow)?
+                .checked_div(lp_supply).ok_or(GhostOddsError::MathOverflow)?) as u64;
+            let no_out = ((market.no_amount as u128).checked_mul(shares as u128).ok_or(GhostOddsError::MathOverflow)?
+                .checked_div(lp_supply).ok_or(GhostOddsError::MathOverflow)?) as u64;
+            (yes_out.min(no_out), yes_out, no_out)
+        };
+        // Settled reserves no longer price anything, so nothing is left unmatched
+        let yes_returned = yes_out.saturating_sub(collateral_out);
+        let no_returned = no_out.saturating_sub(collateral_out);
+        require!(collateral_out >= min_collateral_out, GhostOddsError::SlippageExceeded);
 
-            // Normalize price to compare with resolution_value (u64, 6 decimals assumed)
-            let exponent = current_price.expo;
-            let raw_price = current_price.price;
-            require!(raw_price > 0, GhostOddsError::InvalidOracle);
+        token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(), Burn {
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            from: ctx.accounts.user_lp_tokens.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        }), shares)?;
 
-            let normalized_price: u64 = if exponent >= 0 {
-                (raw_price as u128)
-                    .checked_mul(10u128.pow(6u32.checked_add(exponent as u32).ok_or(GhostOddsError::MathOverflow)?))
-                    .ok_or(GhostOddsError::MathOverflow)? as u64
-            } else {
-                let neg_exp = (-exponent) as u32;
-                if neg_exp <= 6 {
-                    (raw_price as u128)
-                        .checked_mul(10u128.pow(6 - neg_exp))
-                        .ok_or(GhostOddsError::MathOverflow)? as u64
-                } else {
-                    (raw_price as u128)
-                        .checked_div(10u128.pow(neg_exp - 6))
-                        .ok_or(GhostOddsError::MathOverflow)? as u64
-                }
-            };
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        if collateral_out > 0 {
+            token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.user_collateral.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), collateral_out)?;
+        }
+        if yes_returned > 0 {
+            token::mint_to(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), MintTo {
+                mint: ctx.accounts.yes_mint.to_account_info(),
+                to: ctx.accounts.user_yes_tokens.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), yes_returned)?;
+        }
+        if no_returned > 0 {
+            token::mint_to(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), MintTo {
+                mint: ctx.accounts.no_mint.to_account_info(),
+                to: ctx.accounts.user_no_tokens.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), no_returned)?;
+        }
 
-            // Compare using resolution_operator: 0 = >=, 1 = <=, 2 = ==
-            match market.resolution_operator {
-                0 => normalized_price >= resolution_value,
-                1 => normalized_price <= resolution_value,
-                2 => normalized_price == resolution_value,
-                _ => return Err(GhostOddsError::InvalidOperator.into()),
-            }
-        } else {
-            // Manual resolution: only authority can resolve (no permissionless fallback)
-            if !within_grace {
-                require!(
-                    ctx.accounts.resolver.key() == market.authority,
-                    GhostOddsError::Unauthorized
-                );
-            }
-            outcome
-        };
+        let market = &mut ctx.accounts.market;
+        market.yes_amount = market.yes_amount.checked_sub(yes_out).ok_or(GhostOddsError::MathOverflow)?;
+        market.no_amount = market.no_amount.checked_sub(no_out).ok_or(GhostOddsError::MathOverflow)?;
+        // Trades move the reserves, so an LP can withdraw more than was deposited
+        market.total_liquidity = market.total_liquidity.saturating_sub(collateral_out);
 
-        market.outcome = Some(resolved_outcome);
-        market.resolved_at = Some(clock.unix_timestamp);
-        market.status = STATUS_RESOLVED;
-        emit!(MarketResolved { market_id: market.market_id, outcome: resolved_outcome, resolved_at: clock.unix_timestamp });
+        let position = &mut ctx.accounts.user_position;
+        position.user = ctx.accounts.user.key();
+        position.market_id = market.market_id;
+        position.yes_tokens = position.yes_tokens.checked_add(yes_returned).ok_or(GhostOddsError::MathOverflow)?;
+        position.no_tokens = position.no_tokens.checked_add(no_returned).ok_or(GhostOddsError::MathOverflow)?;
+        position.total_withdrawn = position.total_withdrawn.checked_add(collateral_out).ok_or(GhostOddsError::MathOverflow)?;
+        position.bump = ctx.bumps.user_position;
+
+        emit!(LiquidityRemoved {
+            market_id: market.market_id, provider: ctx.accounts.user.key(), shares, collateral_out, yes_returned, no_returned,
+        });
         Ok(())
     }
 
-    pub fn redeem_winnings(ctx: Context<RedeemWinnings>) -> Result<()> {
+    /// Create the order book of a binary market and its escrow accounts, next to the vault.
+    /// Permissionless: the payer only funds rent.
+    pub fn create_order_book(ctx: Context<CreateOrderBook>) -> Result<()> {
+        require!(ctx.accounts.market.outcome_count == 2, GhostOddsError::NotBinaryMarket);
+        let order_book = &mut ctx.accounts.order_book;
+        order_book.market_id = ctx.accounts.market.market_id;
+        order_book.next_order_id = 0;
+        order_book.orders = Vec::new();
+        order_book.claims = Vec::new();
+        order_book.bump = ctx.bumps.order_book;
+        emit!(OrderBookCreated { market_id: order_book.market_id });
+        Ok(())
+    }
+
+    /// Place a limit order for `quantity` YES or NO tokens at `price_bps` collateral per token.
+    /// The order first trades with crossing resting orders at their prices (best price, then
+    /// oldest first), paying the market's trading fee on those fills; the unfilled rest is
+    /// escrowed and rests on the book. Makers' fully filled orders leave the book for their
+    /// owner's claim.
+    pub fn place_order(ctx: Context<PlaceOrder>, is_yes: bool, is_bid: bool, price_bps: u16, quantity: u64) -> Result<()> {
+        require!(quantity > 0, GhostOddsError::ZeroAmount);
+        require!(quantity >= MIN_ORDER_QUANTITY, GhostOddsError::OrderTooSmall);
+        require!(price_bps > 0 && price_bps < 10000, GhostOddsError::InvalidOrderPrice);
         let market = &ctx.accounts.market;
-        require!(market.status == STATUS_RESOLVED, GhostOddsError::MarketNotResolved);
-        let outcome = market.outcome.ok_or(GhostOddsError::MarketNotResolved)?;
-        let winning_amount = if outcome { ctx.accounts.user_yes_tokens.amount } else { ctx.accounts.user_no_tokens.amount };
-        require!(winning_amount > 0, GhostOddsError::NoWinnings);
-        let total_winning_supply = if outcome { ctx.accounts.yes_mint.supply } else { ctx.accounts.no_mint.supply };
-        let vault_balance = ctx.accounts.vault.amount;
-        let payout = ((winning_amount as u128).checked_mul(vault_balance as u128).ok_or(GhostOddsError::MathOverflow)?
-            .checked_div(total_winning_supply as u128).ok_or(GhostOddsError::MathOverflow)?) as u64;
-        require!(payout > 0, GhostOddsError::NoWinnings);
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
+        let market_id = market.market_id;
+        let user = ctx.accounts.user.key();
+        let pool = ctx.accounts.market.trade_pool(clock.unix_timestamp)?;
+        // The escrow transfer proves ownership of an ask's tokens; the position only gives up
+        // the ones it tracks, as for conditional sells
+        let tracked = if is_yes { ctx.accounts.user_position.yes_tokens } else { ctx.accounts.user_position.no_tokens };
+        let position_debited = if is_bid { 0 } else { quantity.min(tracked) };
 
-        let (mint_info, from_info) = if outcome {
-            (ctx.accounts.yes_mint.to_account_info(), ctx.accounts.user_yes_tokens.to_account_info())
+        // Taker bids pay resting asks (rounded up); taker asks are paid by resting bids' escrow
+        let order_book = &mut ctx.accounts.order_book;
+        let mut unfilled = quantity;
+        let mut filled_collateral = 0u64;
+        while unfilled > 0 {
+            let Some(index) = order_book.best_match(is_yes, is_bid, price_bps) else { break };
+            let maker = &mut order_book.orders[index];
+            let fill = unfilled.min(maker.remaining);
+            if is_bid {
+                let cost = math::collateral_at_price(fill, maker.price_bps, true)?;
+                maker.escrowed = maker.escrowed.checked_sub(fill).ok_or(GhostOddsError::MathOverflow)?;
+                maker.proceeds = maker.proceeds.checked_add(cost).ok_or(GhostOddsError::MathOverflow)?;
+                filled_collateral = filled_collateral.checked_add(cost).ok_or(GhostOddsError::MathOverflow)?;
+            } else {
+                let payment = math::collateral_at_price(fill, maker.price_bps, false)?.min(maker.escrowed);
+                maker.escrowed -= payment;
+                maker.proceeds = maker.proceeds.checked_add(fill).ok_or(GhostOddsError::MathOverflow)?;
+                filled_collateral = filled_collateral.checked_add(payment).ok_or(GhostOddsError::MathOverflow)?;
+            }
+            maker.remaining -= fill;
+            unfilled -= fill;
+            emit!(OrderFilled {
+                market_id, order_id: maker.order_id, maker: maker.owner, taker: user, is_yes,
+                maker_is_bid: maker.is_bid, price_bps: maker.price_bps, quantity: fill,
+            });
+            order_book.retire_if_filled(index)?;
+        }
+
+        let mut order_id = None;
+        let mut escrowed = 0;
+        if unfilled > 0 {
+            require!(order_book.orders.len() < MAX_BOOK_ORDERS, GhostOddsError::OrderBookFull);
+            escrowed = if is_bid { math::collateral_at_price(unfilled, price_bps, true)? } else { unfilled };
+            let id = order_book.next_order_id;
+            order_book.next_order_id = id.checked_add(1).ok_or(GhostOddsError::MathOverflow)?;
+            order_book.orders.push(Order {
+                order_id: id, owner: user, is_yes, is_bid, price_bps, remaining: unfilled, escrowed, proceeds: 0,
+                position_debited: position_debited.min(unfilled),
+            });
+            order_id = Some(id);
+        }
+        let filled = quantity - unfilled;
+
+        let market_id_bytes = market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[ctx.accounts.market.bump]]];
+        let (user_outcome_tokens, outcome_escrow) = if is_yes {
+            (ctx.accounts.user_yes_tokens.to_account_info(), ctx.accounts.yes_escrow.to_account_info())
         } else {
-            (ctx.accounts.no_mint.to_account_info(), ctx.accounts.user_no_tokens.to_account_info())
+            (ctx.accounts.user_no_tokens.to_account_info(), ctx.accounts.no_escrow.to_account_info())
         };
-        token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(),
-            Burn { mint: mint_info, from: from_info, authority: ctx.accounts.user.to_account_info() }), winning_amount)?;
+        let (collateral_in, collateral_out, tokens_in, tokens_out) = if is_bid {
+            (filled_collateral.checked_add(escrowed).ok_or(GhostOddsError::MathOverflow)?, 0, 0, filled)
+        } else {
+            (0, filled_collateral, quantity, 0)
+        };
+        if collateral_in > 0 {
+            token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: ctx.accounts.user_collateral.to_account_info(),
+                to: ctx.accounts.order_escrow.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            }), collateral_in)?;
+        }
+        if tokens_in > 0 {
+            token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: user_outcome_tokens.clone(),
+                to: outcome_escrow.clone(),
+                authority: ctx.accounts.user.to_account_info(),
+            }), tokens_in)?;
+        }
+        if collateral_out > 0 {
+            token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: ctx.accounts.order_escrow.to_account_info(),
+                to: ctx.accounts.user_collateral.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), collateral_out)?;
+        }
+        if tokens_out > 0 {
+            token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: outcome_escrow,
+                to: user_outcome_tokens,
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), tokens_out)?;
+        }
+        // Takers pay the trading fee on their fills, as when `buy_outcome` fills resting asks
+        let accounts = &mut *ctx.accounts;
+        let (fee, ..) = charge_book_fill_fee(
+            &mut accounts.market, &pool, filled_collateral, &accounts.user_collateral, &accounts.user, &accounts.vault,
+            &accounts.treasury, accounts.creator_collateral.as_deref(), &accounts.token_program,
+        )?;
+
+        let market = &mut ctx.accounts.market;
+        market.volume = market.volume.checked_add(filled_collateral).ok_or(GhostOddsError::MathOverflow)?;
+
+        let position = &mut ctx.accounts.user_position;
+        position.user = user;
+        position.market_id = market_id;
+        let outcome_tokens = if is_yes { &mut position.yes_tokens } else { &mut position.no_tokens };
+        *outcome_tokens = outcome_tokens.checked_add(tokens_out).ok_or(GhostOddsError::MathOverflow)? - position_debited;
+        let (deposited, withdrawn) = if is_bid {
+            (collateral_in.checked_add(fee).ok_or(GhostOddsError::MathOverflow)?, 0)
+        } else {
+            (0, collateral_out - fee)
+        };
+        position.total_deposited = position.total_deposited.checked_add(deposited).ok_or(GhostOddsError::MathOverflow)?;
+        position.total_withdrawn = position.total_withdrawn.checked_add(withdrawn).ok_or(GhostOddsError::MathOverflow)?;
+        position.bump = ctx.bumps.user_position;
+
+        emit!(OrderPlaced { market_id, order_id, owner: user, is_yes, is_bid, price_bps, quantity, filled });
+        Ok(())
+    }
+
+    /// Cancel one of the caller's orders: refund its escrow and pay out its unclaimed fills.
+    /// Also how makers collect fully filled orders, so it is allowed in any market status.
+    pub fn cancel_order(ctx: Context<CancelOrder>, order_id: u64) -> Result<()> {
+        let order_book = &mut ctx.accounts.order_book;
+        let index = order_book.orders.iter().position(|order| order.order_id == order_id)
+            .ok_or(GhostOddsError::OrderNotFound)?;
+        require!(order_book.orders[index].owner == ctx.accounts.user.key(), GhostOddsError::Unauthorized);
+        let order = order_book.orders.remove(index);
 
+        let (collateral_out, tokens_out) = order.payout();
+        let market = &ctx.accounts.market;
         let market_id_bytes = market.market_id.to_le_bytes();
         let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
-        token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
-            from: ctx.accounts.vault.to_account_info(), to: ctx.accounts.user_collateral.to_account_info(),
-            authority: ctx.accounts.market.to_account_info(),
-        }, signer_seeds), payout)?;
+        if collateral_out > 0 {
+            token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: ctx.accounts.order_escrow.to_account_info(),
+                to: ctx.accounts.user_collateral.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), collateral_out)?;
+        }
+        if tokens_out > 0 {
+            let (from, to) = if order.is_yes {
+                (ctx.accounts.yes_escrow.to_account_info(), ctx.accounts.user_yes_tokens.to_account_info())
+            } else {
+                (ctx.accounts.no_escrow.to_account_info(), ctx.accounts.user_no_tokens.to_account_info())
+            };
+            token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                from, to, authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), tokens_out)?;
+        }
 
         let position = &mut ctx.accounts.user_position;
-        if outcome { position.yes_tokens = 0; } else { position.no_tokens = 0; }
-        position.total_withdrawn = position.total_withdrawn.checked_add(payout).ok_or(GhostOddsError::MathOverflow)?;
-        emit!(WinningsRedeemed { market_id: market.market_id, user: ctx.accounts.user.key(), payout });
+        let outcome_tokens = if order.is_yes { &mut position.yes_tokens } else { &mut position.no_tokens };
+        // An ask only gives back to the position what placing it took off
+        let tracked = if order.is_bid { tokens_out } else { tokens_out.min(order.position_debited) };
+        *outcome_tokens = outcome_tokens.checked_add(tracked).ok_or(GhostOddsError::MathOverflow)?;
+        position.total_withdrawn = position.total_withdrawn.checked_add(collateral_out).ok_or(GhostOddsError::MathOverflow)?;
+
+        emit!(OrderCancelled {
+            market_id: market.market_id, order_id, owner: order.owner, unfilled: order.remaining, collateral_out, tokens_out,
+        });
         Ok(())
     }
 
-    pub fn cancel_market(ctx: Context<CancelMarket>) -> Result<()> {
-        let market = &mut ctx.accounts.market;
-        require!(ctx.accounts.authority.key() == market.authority, GhostOddsError::Unauthorized);
-        // Finding 4: remove status == 1 reference
-        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
-        require!(market.outcome.is_none(), GhostOddsError::AlreadyResolved);
-        market.status = STATUS_CANCELLED;
-        emit!(MarketCancelled { market_id: market.market_id });
+    /// Collect the caller's payouts of fully filled orders, including filled orders still on the
+    /// book because the claims list was full. Allowed in any market status, like `cancel_order`.
+    pub fn claim_order_proceeds(ctx: Context<CancelOrder>) -> Result<()> {
+        let order_book = &mut ctx.accounts.order_book;
+        let owner = ctx.accounts.user.key();
+        let mut claim = match order_book.claims.iter().position(|claim| claim.owner == owner) {
+            Some(index) => order_book.claims.remove(index),
+            None => OrderClaim { owner, ..Default::default() },
+        };
+        while let Some(index) = order_book.orders.iter().position(|order| order.owner == owner && order.remaining == 0) {
+            let order = order_book.orders.remove(index);
+            claim.add(&order)?;
+        }
+        require!(claim.collateral > 0 || claim.yes_tokens > 0 || claim.no_tokens > 0, GhostOddsError::NothingToClaim);
+
+        let market = &ctx.accounts.market;
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        for (from, to, amount) in [
+            (ctx.accounts.order_escrow.to_account_info(), ctx.accounts.user_collateral.to_account_info(), claim.collateral),
+            (ctx.accounts.yes_escrow.to_account_info(), ctx.accounts.user_yes_tokens.to_account_info(), claim.yes_tokens),
+            (ctx.accounts.no_escrow.to_account_info(), ctx.accounts.user_no_tokens.to_account_info(), claim.no_tokens),
+        ] {
+            if amount > 0 {
+                token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                    from, to, authority: ctx.accounts.market.to_account_info(),
+                }, signer_seeds), amount)?;
+            }
+        }
+
+        let position = &mut ctx.accounts.user_position;
+        position.yes_tokens = position.yes_tokens.checked_add(claim.yes_tokens).ok_or(GhostOddsError::MathOverflow)?;
+        position.no_tokens = position.no_tokens.checked_add(claim.no_tokens).ok_or(GhostOddsError::MathOverflow)?;
+        position.total_withdrawn = position.total_withdrawn.checked_add(claim.collateral).ok_or(GhostOddsError::MathOverflow)?;
+
+        emit!(OrderProceedsClaimed {
+            market_id: market.market_id, owner, collateral_out: claim.collateral, yes_tokens_out: claim.yes_tokens,
+            no_tokens_out: claim.no_tokens,
+        });
         Ok(())
     }
 
-    pub fn redeem_cancelled(ctx: Context<RedeemCancelled>) -> Result<()> {
+    /// Leave a conditional order on a binary market: buy YES or NO with `amount` collateral, or
+    /// sell `amount` of them, once the AMM price of that outcome crosses `trigger_price_bps`.
+    /// The collateral or tokens are escrowed and `keeper_tip` lamports are held on the order
+    /// for whoever executes it. One order per user and market.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_conditional_order(
+        ctx: Context<PlaceConditionalOrder>,
+        kind: ConditionalKind,
+        is_yes: bool,
+        is_buy: bool,
+        trigger_price_bps: u16,
+        amount: u64,
+        min_out: u64,
+        keeper_tip: u64,
+    ) -> Result<()> {
+        require!(amount > 0, GhostOddsError::ZeroAmount);
+        require!(trigger_price_bps > 0 && trigger_price_bps < 10000, GhostOddsError::InvalidOrderPrice);
+        require!(!is_buy || kind == ConditionalKind::Limit, GhostOddsError::InvalidConditionalOrder);
         let market = &ctx.accounts.market;
-        require!(market.status == STATUS_CANCELLED, GhostOddsError::MarketNotCancelled);
-        let yes_amount = ctx.accounts.user_yes_tokens.amount;
-        let no_amount = ctx.accounts.user_no_tokens.amount;
-        require!(yes_amount > 0 || no_amount > 0, GhostOddsError::NoWinnings);
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(market.outcome_count == 2, GhostOddsError::NotBinaryMarket);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
 
-        if yes_amount > 0 {
-            token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(), Burn {
-                mint: ctx.accounts.yes_mint.to_account_info(), from: ctx.accounts.user_yes_tokens.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            }), yes_amount)?;
+        token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.user_source.to_account_info(),
+            to: ctx.accounts.escrow.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        }), amount)?;
+        if keeper_tip > 0 {
+            system_program::transfer(CpiContext::new(ctx.accounts.system_program.to_account_info(), system_program::Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.conditional_order.to_account_info(),
+            }), keeper_tip)?;
         }
-        if no_amount > 0 {
-            token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(), Burn {
-                mint: ctx.accounts.no_mint.to_account_info(), from: ctx.accounts.user_no_tokens.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            }), no_amount)?;
+
+        let position = &mut ctx.accounts.user_position;
+        position.user = ctx.accounts.user.key();
+        position.market_id = market.market_id;
+        // The escrow transfer already proves ownership of the tokens; the position does not
+        // track tokens received by plain transfer, so it may hold fewer than are sold
+        let mut position_debited = 0;
+        if is_buy {
+            position.total_deposited = position.total_deposited.checked_add(amount).ok_or(GhostOddsError::MathOverflow)?;
+        } else {
+            let tracked = if is_yes { &mut position.yes_tokens } else { &mut position.no_tokens };
+            position_debited = amount.min(*tracked);
+            *tracked -= position_debited;
         }
+        position.bump = ctx.bumps.user_position;
 
-        ctx.accounts.yes_mint.reload()?;
-        ctx.accounts.no_mint.reload()?;
-        let total_tokens = ctx.accounts.yes_mint.supply.checked_add(yes_amount).ok_or(GhostOddsError::MathOverflow)?
-            .checked_add(ctx.accounts.no_mint.supply).ok_or(GhostOddsError::MathOverflow)?
-            .checked_add(no_amount).ok_or(GhostOddsError::MathOverflow)?;
-        let user_tokens = yes_amount.checked_add(no_amount).ok_or(GhostOddsError::MathOverflow)?;
-        ctx.accounts.vault.reload()?;
-        let vault_balance = ctx.accounts.vault.amount;
-        let refund = ((user_tokens as u128).checked_mul(vault_balance as u128).ok_or(GhostOddsError::MathOverflow)?
-            .checked_div(total_tokens as u128).ok_or(GhostOddsError::MathOverflow)?) as u64;
-        require!(refund > 0, GhostOddsError::NoWinnings);
+        let order = &mut ctx.accounts.conditional_order;
+        order.market_id = market.market_id;
+        order.owner = ctx.accounts.user.key();
+        order.kind = kind;
+        order.is_yes = is_yes;
+        order.is_buy = is_buy;
+        order.trigger_price_bps = trigger_price_bps;
+        order.amount = amount;
+        order.min_out = min_out;
+        order.keeper_tip = keeper_tip;
+        order.position_debited = position_debited;
+        order.bump = ctx.bumps.conditional_order;
 
+        emit!(ConditionalOrderPlaced {
+            market_id: order.market_id, owner: order.owner, kind, is_yes, is_buy, trigger_price_bps, amount, min_out, keeper_tip,
+        });
+        Ok(())
+    }
+
+    /// Cancel the caller's conditional order, returning its escrow and the keeper tip.
+    /// Allowed in any market status.
+    pub fn cancel_conditional_order(ctx: Context<CancelConditionalOrder>) -> Result<()> {
+        let order = &ctx.accounts.conditional_order;
+        let market = &ctx.accounts.market;
         let market_id_bytes = market.market_id.to_le_bytes();
         let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
         token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
-            from: ctx.accounts.vault.to_account_info(), to: ctx.accounts.user_collateral.to_account_info(),
+            from: ctx.accounts.escrow.to_account_info(),
+            to: ctx.accounts.user_destination.to_account_info(),
             authority: ctx.accounts.market.to_account_info(),
-        }, signer_seeds), refund)?;
+        }, signer_seeds), order.amount)?;
+        token::close_account(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), CloseAccount {
+            account: ctx.accounts.escrow.to_account_info(),
+            destination: ctx.accounts.user.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds))?;
 
-        emit!(CancelledRedeemed { market_id: market.market_id, user: ctx.accounts.user.key(), refund });
+        let position = &mut ctx.accounts.user_position;
+        if order.is_buy {
+            position.total_withdrawn = position.total_withdrawn.checked_add(order.amount).ok_or(GhostOddsError::MathOverflow)?;
+        } else if order.is_yes {
+            position.yes_tokens = position.yes_tokens.checked_add(order.position_debited).ok_or(GhostOddsError::MathOverflow)?;
+        } else {
+            position.no_tokens = position.no_tokens.checked_add(order.position_debited).ok_or(GhostOddsError::MathOverflow)?;
+        }
+
+        emit!(ConditionalOrderCancelled { market_id: order.market_id, owner: order.owner });
         Ok(())
     }
-}
-
-// ============ Accounts ============
-#[account]
-pub struct Platform {
-    pub authority: Pubkey,
-    pub market_count: u64,
-    pub total_volume: u64,
-    pub fee_bps: u16,
-    pub treasury: Pubkey,
-    pub bump: u8,
-}
-impl Platform { pub const LEN: usize = 8 + 32 + 8 + 8 + 2 + 32 + 1; }
 
+    /// Execute a conditional order against the AMM once its trigger is reached. Permissionless:
+    /// the keeper earns the order's tip, and the order's rent goes back to its owner.
+    pub fn execute_conditional_order(ctx: Context<ExecuteConditionalOrder>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
+
+        let pool = ctx.accounts.market.trade_pool(clock.unix_timestamp)?;
+        let market = &ctx.accounts.market;
+        let order = &ctx.accounts.conditional_order;
+        let price_yes = math::price_yes(&pool)?;
+        let price = if order.is_yes { price_yes } else { math::PRICE_SCALE - price_yes };
+        let price_bps = (price * 10000 / math::PRICE_SCALE) as u16;
+        let triggered = match order.kind {
+            ConditionalKind::Limit if order.is_buy => price_bps <= order.trigger_price_bps,
+            ConditionalKind::Limit | ConditionalKind::TakeProfit => price_bps >= order.trigger_price_bps,
+            ConditionalKind::StopLoss => price_bps <= order.trigger_price_bps,
+        };
+        require!(triggered, GhostOddsError::TriggerNotReached);
+
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        let mint_info = if order.is_yes { ctx.accounts.yes_mint.to_account_info() } else { ctx.accounts.no_mint.to_account_info() };
+        let (volume, amount_out) = if order.is_buy {
+            let quote = math::quote_buy(&pool, order.is_yes, order.amount)?;
+            require!(quote.tokens_out >= order.min_out, GhostOddsError::SlippageExceeded);
+            token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: ctx.accounts.escrow.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), quote.input_after_fee.checked_add(quote.lp_fee).ok_or(GhostOddsError::MathOverflow)?)?;
+            if quote.protocol_fee > 0 {
+                token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                    authority: ctx.accounts.market.to_account_info(),
+                }, signer_seeds), quote.protocol_fee)?;
+            }
+            if quote.creator_fee > 0 {
+                let creator_collateral = ctx.accounts.creator_collateral.as_ref().ok_or(GhostOddsError::CreatorFeeAccountRequired)?;
+                token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    to: creator_collateral.to_account_info(),
+                    authority: ctx.accounts.market.to_account_info(),
+                }, signer_seeds), quote.creator_fee)?;
+            }
+            token::mint_to(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), MintTo {
+                mint: mint_info,
+                to: ctx.accounts.owner_outcome_tokens.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), quote.tokens_out)?;
+            emit!(OutcomePurchased {
+                market_id: market.market_id, user: order.owner, is_yes: order.is_yes, amount_in: quote.amount_in,
+                tokens_out: quote.tokens_out, fee_bps: quote.fee_bps, fee: quote.fee, lp_fee: quote.lp_fee,
+                creator_fee: quote.creator_fee, protocol_fee: quote.protocol_fee,
+            });
+            ctx.accounts.market.yes_amount = quote.yes_amount;
+            ctx.accounts.market.no_amount = quote.no_amount;
+            (quote.amount_in, quote.tokens_out)
+        } else {
+            let quote = math::quote_sell(&pool, order.is_yes, order.amount)?;
+            require!(quote.collateral_out >= order.min_out, GhostOddsError::SlippageExceeded);
+            token::burn(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Burn {
+                mint: mint_info,
+                from: ctx.accounts.escrow.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), quote.tokens_in)?;
+            token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.owner_collateral.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), quote.collateral_out)?;
+            if quote.protocol_fee > 0 {
+                token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                    authority: ctx.accounts.market.to_account_info(),
+                }, signer_seeds), quote.protocol_fee)?;
+            }
+            if quote.creator_fee > 0 {
+                let creator_collateral = ctx.accounts.creator_collateral.as_ref().ok_or(GhostOddsError::CreatorFeeAccountRequired)?;
+                token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: creator_collateral.to_account_info(),
+                    authority: ctx.accounts.market.to_account_info(),
+                }, signer_seeds), quote.creator_fee)?;
+            }
+            emit!(OutcomeSold {
+                market_id: market.market_id, user: order.owner, is_yes: order.is_yes, tokens_in: quote.tokens_in,
+                collateral_out: quote.collateral_out, fee_bps: quote.fee_bps, fee: quote.fee, lp_fee: quote.lp_fee,
+                creator_fee: quote.creator_fee, protocol_fee: quote.protocol_fee,
+            });
+            ctx.accounts.market.yes_amount = quote.yes_amount;
+            ctx.accounts.market.no_amount = quote.no_amount;
+            (quote.collateral_before_fee, quote.collateral_out)
+        };
+        token::close_account(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), CloseAccount {
+            account: ctx.accounts.escrow.to_account_info(),
+            destination: ctx.accounts.owner.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds))?;
+
+        let market = &mut ctx.accounts.market;
+        market.volume = market.volume.checked_add(volume).ok_or(GhostOddsError::MathOverflow)?;
+        let platform = &mut ctx.accounts.platform;
+        platform.total_volume = platform.total_volume.checked_add(volume).ok_or(GhostOddsError::MathOverflow)?;
+
+        let order = &ctx.accounts.conditional_order;
+        let position = &mut ctx.accounts.owner_position;
+        if !order.is_buy {
+            position.total_withdrawn = position.total_withdrawn.checked_add(amount_out).ok_or(GhostOddsError::MathOverflow)?;
+        } else if order.is_yes {
+            position.yes_tokens = position.yes_tokens.checked_add(amount_out).ok_or(GhostOddsError::MathOverflow)?;
+        } else {
+            position.no_tokens = position.no_tokens.checked_add(amount_out).ok_or(GhostOddsError::MathOverflow)?;
+        }
+
+        // Pay the tip out of the order's lamports; the rest goes to the owner when it closes
+        let keeper_tip = order.keeper_tip;
+        if keeper_tip > 0 {
+            **ctx.accounts.conditional_order.to_account_info().try_borrow_mut_lamports()? -= keeper_tip;
+            **ctx.accounts.keeper.to_account_info().try_borrow_mut_lamports()? += keeper_tip;
+        }
+
+        let order = &ctx.accounts.conditional_order;
+        emit!(ConditionalOrderExecuted {
+            market_id: order.market_id, owner: order.owner, keeper: ctx.accounts.keeper.key(), price_bps,
+            amount_in: order.amount, amount_out, keeper_tip,
+        });
+        Ok(())
+    }
+
+    /// Resolve a binary market after expiry: oracle markets from their feed (permissionless),
+    /// manual markets with `outcome` (platform authority or resolver). Spot markets bound to a
+    /// legacy Pyth or Switchboard feed fall back to manual resolution once
+    /// `oracle::settlement_window_closed`, as their feed no longer holds a price from expiry.
+    pub fn resolve_market(ctx: Context<ResolveMarket>, outcome: bool) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        // Finding 4: remove status == 1 reference (never set)
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(market.outcome_count == 2, GhostOddsError::NotBinaryMarket);
+        let manual = match market.oracle_kind {
+            None => true,
+            Some(kind) => {
+                market.twap.is_none()
+                    && oracle::settlement_window_closed(kind, market.expires_at, clock.unix_timestamp)?
+            }
+        };
+        // Manual resolution: only the platform authority or a resolver can resolve (no
+        // permissionless fallback)
+        if manual {
+            require!(
+                ctx.accounts.resolver.key() == ctx.accounts.platform.authority
+                    || ctx.accounts.resolver_role.as_ref().is_some_and(|role| role.has(Role::RESOLVER)),
+                GhostOddsError::Unauthorized
+            );
+        }
+        require!(clock.unix_timestamp >= market.expires_at, GhostOddsError::MarketNotExpired);
+
+        // Determine outcome: use the oracle bound at creation if any, manual otherwise.
+        // Oracle markets settle on prices published around expiry, so anyone may resolve them.
+        let (resolved_outcome, settlement) = if !manual {
+            let (price, conf, publish_time) = if let Some(twap) = market.twap {
+                // TWAP market: average the samples recorded during the window before expiry
+                let price_samples = ctx.accounts.price_samples
+                    .as_ref()
+                    .ok_or(GhostOddsError::PriceSamplesRequired)?;
+                require!(price_samples.len >= twap.min_samples, GhostOddsError::InsufficientPriceSamples);
+                // Samples bunched into part of the window would let a short price spike set the average
+                let covered = price_samples.coverage(market.expires_at, twap.max_sample_hold())?;
+                require!(
+                    covered.checked_mul(10000).ok_or(GhostOddsError::MathOverflow)?
+                        >= twap.window.checked_mul(TWAP_MIN_COVERAGE_BPS).ok_or(GhostOddsError::MathOverflow)?,
+                    GhostOddsError::InsufficientTwapCoverage
+                );
+                price_samples.twap(market.expires_at)?
+            } else {
+                // Spot market: require the feed account bound at creation (the account constraint
+                // rejects any other feed), or a PriceUpdateV2 for the bound feed ID
+                let oracle_info = ctx.accounts.oracle_account
+                    .as_ref()
+                    .ok_or(GhostOddsError::OracleRequired)?;
+                let kind = market.oracle_kind.ok_or(GhostOddsError::OracleRequired)?;
+                let settlement_price = read_market_oracle(market, oracle_info)?;
+
+                // Only accept a price published within the backend's staleness bound of expiry
+                let window = kind.max_staleness() as i64;
+                require!(
+                    settlement_price.publish_time >= market.expires_at.checked_sub(window).ok_or(GhostOddsError::MathOverflow)?
+                        && settlement_price.publish_time <= market.expires_at.checked_add(window).ok_or(GhostOddsError::MathOverflow)?,
+                    GhostOddsError::SettlementPriceOutOfWindow
+                );
+                let (price, conf) = normalize_oracle_price(&settlement_price, kind.max_conf_bps())?;
+                (price, conf, settlement_price.publish_time)
+            };
+            (evaluate_outcome(market, price)?, Some((price, conf, publish_time)))
+        } else {
+            (outcome, None)
+        };
+
+        market.outcome = Some(resolved_outcome);
+        market.resolved_at = Some(clock.unix_timestamp);
+        market.status = STATUS_RESOLVED;
+        market.settlement_price = settlement.map(|(price, _, _)| price);
+        market.settlement_conf = settlement.map(|(_, conf, _)| conf);
+        market.settlement_publish_time = settlement.map(|(_, _, publish_time)| publish_time);
+        emit!(MarketResolved {
+            market_id: market.market_id,
+            outcome: resolved_outcome,
+            resolved_at: clock.unix_timestamp,
+            settlement_price: market.settlement_price,
+            settlement_conf: market.settlement_conf,
+            settlement_publish_time: market.settlement_publish_time,
+        });
+        Ok(())
+    }
+
+    /// Permissionless: record an oracle observation for a TWAP market. Only prices published
+    /// during the averaging window before expiry are accepted, at least
+    /// `TwapConfig::min_sample_gap` apart so the buffer always spans the whole window.
+    pub fn record_price_sample(ctx: Context<RecordPriceSample>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        let twap = market.twap.ok_or(GhostOddsError::TwapNotEnabled)?;
+
+        let kind = market.oracle_kind.ok_or(GhostOddsError::OracleRequired)?;
+        let oracle_price = read_market_oracle(market, &ctx.accounts.oracle_account)?;
+        let window_start = market.expires_at.checked_sub(twap.window).ok_or(GhostOddsError::MathOverflow)?;
+        require!(
+            oracle_price.publish_time >= window_start && oracle_price.publish_time <= market.expires_at,
+            GhostOddsError::OutsideTwapWindow
+        );
+        let (price, conf) = normalize_oracle_price(&oracle_price, kind.max_conf_bps())?;
+
+        let price_samples = &mut ctx.accounts.price_samples;
+        if price_samples.len == 0 {
+            price_samples.market_id = market.market_id;
+            price_samples.bump = ctx.bumps.price_samples;
+        } else {
+            let last_publish_time = price_samples.last().publish_time;
+            require!(oracle_price.publish_time > last_publish_time, GhostOddsError::DuplicatePriceSample);
+            require!(
+                oracle_price.publish_time - last_publish_time >= twap.min_sample_gap(),
+                GhostOddsError::PriceSampleTooSoon
+            );
+        }
+        price_samples.push(PriceSample { price, conf, publish_time: oracle_price.publish_time });
+
+        emit!(PriceSampleRecorded {
+            market_id: market.market_id,
+            price,
+            conf,
+            publish_time: oracle_price.publish_time,
+            sample_count: price_samples.len,
+        });
+        Ok(())
+    }
+
+    /// Optimistic resolution of a manual market: once the authority's grace period has passed,
+    /// anyone can propose an outcome by posting `RESOLUTION_BOND`.
+    pub fn propose_outcome(ctx: Context<ProposeOutcome>, outcome: bool) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(market.oracle_kind.is_none(), GhostOddsError::NotManualMarket);
+        require!(market.outcome_count == 2, GhostOddsError::NotBinaryMarket);
+        let grace_deadline = market.expires_at
+            .checked_add(RESOLUTION_GRACE_PERIOD)
+            .ok_or(GhostOddsError::MathOverflow)?;
+        require!(clock.unix_timestamp >= grace_deadline, GhostOddsError::GracePeriodActive);
+
+        token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.proposer_collateral.to_account_info(),
+            to: ctx.accounts.bond_vault.to_account_info(),
+            authority: ctx.accounts.proposer.to_account_info(),
+        }), RESOLUTION_BOND)?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.market_id = market.market_id;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.outcome = outcome;
+        proposal.proposed_at = clock.unix_timestamp;
+        proposal.disputer = None;
+        proposal.disputed_at = None;
+        proposal.bond = RESOLUTION_BOND;
+        proposal.bump = ctx.bumps.proposal;
+
+        let market = &mut ctx.accounts.market;
+        market.status = STATUS_PROPOSED;
+        let dispute_deadline = clock.unix_timestamp.checked_add(DISPUTE_WINDOW).ok_or(GhostOddsError::MathOverflow)?;
+        emit!(OutcomeProposed {
+            market_id: market.market_id,
+            proposer: proposal.proposer,
+            outcome,
+            bond: RESOLUTION_BOND,
+            dispute_deadline,
+        });
+        Ok(())
+    }
+
+    /// Challenge a proposed outcome within the liveness window by posting a matching bond.
+    /// The dispute escalates to the platform authority or a resolver (`settle_dispute`).
+    /// Open while the platform is paused: the liveness window keeps running, and a pause must
+    /// not let an undisputed proposal finalize once it is lifted.
+    pub fn dispute_outcome(ctx: Context<DisputeOutcome>) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(ctx.accounts.market.status == STATUS_PROPOSED, GhostOddsError::NoPendingProposal);
+        let dispute_deadline = proposal.proposed_at
+            .checked_add(DISPUTE_WINDOW)
+            .ok_or(GhostOddsError::MathOverflow)?;
+        require!(clock.unix_timestamp < dispute_deadline, GhostOddsError::DisputeWindowClosed);
+
+        token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.disputer_collateral.to_account_info(),
+            to: ctx.accounts.bond_vault.to_account_info(),
+            authority: ctx.accounts.disputer.to_account_info(),
+        }), proposal.bond)?;
+
+        proposal.disputer = Some(ctx.accounts.disputer.key());
+        proposal.disputed_at = Some(clock.unix_timestamp);
+        ctx.accounts.market.status = STATUS_DISPUTED;
+        emit!(OutcomeDisputed { market_id: proposal.market_id, disputer: ctx.accounts.disputer.key(), bond: proposal.bond });
+        Ok(())
+    }
+
+    /// Permissionless: resolve a market with its undisputed proposal once the liveness window
+    /// has passed, returning the proposer's bond.
+    pub fn finalize_outcome(ctx: Context<FinalizeOutcome>) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &ctx.accounts.proposal;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(ctx.accounts.market.status == STATUS_PROPOSED, GhostOddsError::NoPendingProposal);
+        let dispute_deadline = proposal.proposed_at
+            .checked_add(DISPUTE_WINDOW)
+            .ok_or(GhostOddsError::MathOverflow)?;
+        require!(clock.unix_timestamp >= dispute_deadline, GhostOddsError::DisputeWindowOpen);
+
+        let market = &ctx.accounts.market;
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.bond_vault.to_account_info(),
+            to: ctx.accounts.proposer_collateral.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), proposal.bond)?;
+        token::close_account(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), CloseAccount {
+            account: ctx.accounts.bond_vault.to_account_info(),
+            destination: ctx.accounts.proposer.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds))?;
+
+        let outcome = proposal.outcome;
+        let market = &mut ctx.accounts.market;
+        market.outcome = Some(outcome);
+        market.resolved_at = Some(clock.unix_timestamp);
+        market.status = STATUS_RESOLVED;
+        emit!(MarketResolved {
+            market_id: market.market_id,
+            outcome,
+            resolved_at: clock.unix_timestamp,
+            settlement_price: None,
+            settlement_conf: None,
+            settlement_publish_time: None,
+        });
+        Ok(())
+    }
+
+    /// Platform authority or a resolver rules on a disputed proposal. The side that was right gets its bond
+    /// back; the other bond is slashed to the treasury.
+    pub fn settle_dispute(ctx: Context<SettleDispute>, outcome: bool) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.platform.authority
+                || ctx.accounts.resolver_role.as_ref().is_some_and(|role| role.has(Role::RESOLVER)),
+            GhostOddsError::Unauthorized
+        );
+        require!(ctx.accounts.market.status == STATUS_DISPUTED, GhostOddsError::NotDisputed);
+        let proposal = &ctx.accounts.proposal;
+        let bond = proposal.bond;
+        let proposer_was_right = proposal.outcome == outcome;
+
+        let market = &ctx.accounts.market;
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        let winner_collateral = if proposer_was_right {
+            ctx.accounts.proposer_collateral.to_account_info()
+        } else {
+            ctx.accounts.disputer_collateral.to_account_info()
+        };
+        token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.bond_vault.to_account_info(),
+            to: winner_collateral,
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), bond)?;
+        token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.bond_vault.to_account_info(),
+            to: ctx.accounts.treasury.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), bond)?;
+        token::close_account(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), CloseAccount {
+            account: ctx.accounts.bond_vault.to_account_info(),
+            destination: ctx.accounts.proposer.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds))?;
+
+        let winner = if proposer_was_right { proposal.proposer } else { ctx.accounts.disputer_collateral.owner };
+        let market = &mut ctx.accounts.market;
+        market.outcome = Some(outcome);
+        market.resolved_at = Some(clock.unix_timestamp);
+        market.status = STATUS_RESOLVED;
+        emit!(DisputeSettled { market_id: market.market_id, outcome, winner, slashed: bond });
+        emit!(MarketResolved {
+            market_id: market.market_id,
+            outcome,
+            resolved_at: clock.unix_timestamp,
+            settlement_price: None,
+            settlement_conf: None,
+            settlement_publish_time: None,
+        });
+        Ok(())
+    }
+
+    /// Resolve an ambiguous market as INVALID. YES and NO holders then redeem at
+    /// `yes_price_bps` / `10000 - yes_price_bps`, defaulting to the last AMM price.
+    pub fn resolve_invalid(ctx: Context<ResolveInvalid>, yes_price_bps: Option<u16>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.platform.authority
+                || ctx.accounts.resolver_role.as_ref().is_some_and(|role| role.has(Role::RESOLVER)),
+            GhostOddsError::Unauthorized
+        );
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(market.outcome_count == 2, GhostOddsError::NotBinaryMarket);
+        require!(clock.unix_timestamp >= market.expires_at, GhostOddsError::MarketNotExpired);
+
+        let yes_price_bps = match yes_price_bps {
+            Some(bps) => bps,
+            None => (math::price_yes(&market.pool())? * 10000 / math::PRICE_SCALE) as u16,
+        };
+        require!(yes_price_bps <= 10000, GhostOddsError::InvalidSettlementPrice);
+
+        market.invalid_yes_price_bps = Some(yes_price_bps);
+        market.resolved_at = Some(clock.unix_timestamp);
+        market.status = STATUS_INVALID;
+        emit!(MarketInvalidated { market_id: market.market_id, yes_price_bps, resolved_at: clock.unix_timestamp });
+        Ok(())
+    }
+
+    pub fn redeem_winnings(ctx: Context<RedeemWinnings>) -> Result<()> {
+        let market_id = ctx.accounts.market.market_id;
+        if ctx.accounts.market.status == STATUS_INVALID {
+            let (yes_price_bps, no_price_bps, scale) = ctx.accounts.market.settlement_weights()?;
+            let (yes_tokens, no_tokens, payout) = redeem_weighted(ctx.accounts, yes_price_bps, no_price_bps, scale)?;
+            emit!(InvalidRedeemed { market_id, user: ctx.accounts.user.key(), yes_tokens, no_tokens, payout });
+            return Ok(());
+        }
+        require!(ctx.accounts.market.status == STATUS_RESOLVED, GhostOddsError::MarketNotResolved);
+        if ctx.accounts.market.resolution_operator == OP_SCALAR {
+            let (long_weight, short_weight, width) = ctx.accounts.market.settlement_weights()?;
+            let (long_tokens, short_tokens, payout) = redeem_weighted(ctx.accounts, long_weight, short_weight, width)?;
+            emit!(ScalarRedeemed { market_id, user: ctx.accounts.user.key(), long_tokens, short_tokens, payout });
+            return Ok(());
+        }
+
+        let market = &ctx.accounts.market;
+        let outcome = market.outcome.ok_or(GhostOddsError::MarketNotResolved)?;
+        let winning_amount = if outcome { ctx.accounts.user_yes_tokens.amount } else { ctx.accounts.user_no_tokens.amount };
+        require!(winning_amount > 0, GhostOddsError::NoWinnings);
+        // Every winning token was minted against one unit of collateral in the vault
+        let payout = winning_amount;
+
+        let (mint_info, from_info) = if outcome {
+            (ctx.accounts.yes_mint.to_account_info(), ctx.accounts.user_yes_tokens.to_account_info())
+        } else {
+            (ctx.accounts.no_mint.to_account_info(), ctx.accounts.user_no_tokens.to_account_info())
+        };
+        token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(),
+            Burn { mint: mint_info, from: from_info, authority: ctx.accounts.user.to_account_info() }), winning_amount)?;
+
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.vault.to_account_info(), to: ctx.accounts.user_collateral.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), payout)?;
+
+        let position = &mut ctx.accounts.user_position;
+        if outcome { position.yes_tokens = 0; } else { position.no_tokens = 0; }
+        position.total_withdrawn = position.total_withdrawn.checked_add(payout).ok_or(GhostOddsError::MathOverflow)?;
+        emit!(WinningsRedeemed { market_id: market.market_id, user: ctx.accounts.user.key(), payout });
+        Ok(())
+    }
+
+    pub fn cancel_market(ctx: Context<CancelMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.platform.authority
+                || ctx.accounts.role.as_ref().is_some_and(|role| role.has(Role::RESOLVER)),
+            GhostOddsError::Unauthorized
+        );
+        // Finding 4: remove status == 1 reference
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(market.outcome.is_none(), GhostOddsError::AlreadyResolved);
+        market.status = STATUS_CANCELLED;
+        emit!(MarketCancelled { market_id: market.market_id });
+        Ok(())
+    }
+
+    /// Refund the YES and NO tokens of a cancelled market at half a unit of collateral each (one
+    /// unit per complete set).
+    pub fn redeem_cancelled(ctx: Context<RedeemCancelled>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.status == STATUS_CANCELLED, GhostOddsError::MarketNotCancelled);
+        let yes_amount = ctx.accounts.user_yes_tokens.amount;
+        let no_amount = ctx.accounts.user_no_tokens.amount;
+        require!(yes_amount > 0 || no_amount > 0, GhostOddsError::NoWinnings);
+        let (yes_weight, no_weight, scale) = market.settlement_weights()?;
+        let refund = ((yes_amount as u128).checked_mul(yes_weight).ok_or(GhostOddsError::MathOverflow)?
+            .checked_add((no_amount as u128).checked_mul(no_weight).ok_or(GhostOddsError::MathOverflow)?)
+            .ok_or(GhostOddsError::MathOverflow)?
+            / scale) as u64;
+        require!(refund > 0, GhostOddsError::NoWinnings);
+
+        if yes_amount > 0 {
+            token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(), Burn {
+                mint: ctx.accounts.yes_mint.to_account_info(), from: ctx.accounts.user_yes_tokens.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            }), yes_amount)?;
+        }
+        if no_amount > 0 {
+            token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(), Burn {
+                mint: ctx.accounts.no_mint.to_account_info(), from: ctx.accounts.user_no_tokens.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            }), no_amount)?;
+        }
+
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.vault.to_account_info(), to: ctx.accounts.user_collateral.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), refund)?;
+
+        emit!(CancelledRedeemed { market_id: market.market_id, user: ctx.accounts.user.key(), refund });
+        Ok(())
+    }
+
+    /// Create a manually resolved market with `outcome_count` mutually exclusive outcomes.
+    /// The outcome mints (PDAs: seeds = [b"outcome_mint", market_id, index]) are passed in index
+    /// order as remaining accounts.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_categorical_market<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateCategoricalMarket<'info>>,
+        question: String,
+        description: String,
+        category: String,
+        resolution_source: String,
+        outcome_count: u8,
+        expires_at: i64,
+        initial_liquidity: u64,
+    ) -> Result<()> {
+        require!(question.len() <= MAX_QUESTION_LEN, GhostOddsError::QuestionTooLong);
+        require!(description.len() <= MAX_DESCRIPTION_LEN, GhostOddsError::DescriptionTooLong);
+        require!(category.len() <= MAX_CATEGORY_LEN, GhostOddsError::CategoryTooLong);
+        require!(resolution_source.len() <= MAX_RESOLUTION_SOURCE_LEN, GhostOddsError::ResolutionSourceTooLong);
+        require!(source_matches_oracle(&resolution_source, None), GhostOddsError::ResolutionSourceMismatch);
+        require!(
+            outcome_count >= MIN_CATEGORICAL_OUTCOMES && outcome_count as usize <= MAX_OUTCOMES,
+            GhostOddsError::InvalidOutcomeCount
+        );
+        require!(ctx.remaining_accounts.len() == outcome_count as usize, GhostOddsError::InvalidOutcomeCount);
+        require!(initial_liquidity > 0, GhostOddsError::ZeroAmount);
+
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        require!(
+            expires_at >= now.checked_add(MIN_MARKET_DURATION).ok_or(GhostOddsError::MathOverflow)?,
+            GhostOddsError::ExpiryTooSoon
+        );
+        let lock_time = expires_at.checked_sub(LOCK_BEFORE_EXPIRY).ok_or(GhostOddsError::MathOverflow)?;
+
+        let platform = &mut ctx.accounts.platform;
+        let market_id = platform.market_count;
+        platform.market_count = platform.market_count.checked_add(1).ok_or(GhostOddsError::MathOverflow)?;
+
+        let rent = Rent::get()?;
+        let market_key = ctx.accounts.market.key();
+        let market_id_bytes = market_id.to_le_bytes();
+
+        // Create outcome mints via CPI
+        for (index, mint_info) in ctx.remaining_accounts.iter().enumerate() {
+            let index_bytes = [index as u8];
+            let (expected_mint, mint_bump) = Pubkey::find_program_address(
+                &[b"outcome_mint", market_id_bytes.as_ref(), index_bytes.as_ref()], ctx.program_id);
+            require_keys_eq!(mint_info.key(), expected_mint, GhostOddsError::InvalidOutcomeMint);
+            let mint_seeds: &[&[u8]] = &[b"outcome_mint", market_id_bytes.as_ref(), index_bytes.as_ref(), &[mint_bump]];
+            system_program::create_account(
+                CpiContext::new_with_signer(ctx.accounts.system_program.to_account_info(),
+                    system_program::CreateAccount {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: mint_info.clone(),
+                    }, &[mint_seeds]),
+                rent.minimum_balance(MINT_SIZE), MINT_SIZE as u64, &ctx.accounts.token_program.key(),
+            )?;
+            token::initialize_mint(
+                CpiContext::new(ctx.accounts.token_program.to_account_info(),
+                    InitializeMint { mint: mint_info.clone(), rent: ctx.accounts.rent.to_account_info() }),
+                6, &market_key, None,
+            )?;
+        }
+
+        // Create vault via CPI
+        let vault_seeds: &[&[u8]] = &[b"vault", market_id_bytes.as_ref(), &[ctx.bumps.vault]];
+        system_program::create_account(
+            CpiContext::new_with_signer(ctx.accounts.system_program.to_account_info(),
+                system_program::CreateAccount {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                }, &[vault_seeds]),
+            rent.minimum_balance(TOKEN_ACCOUNT_SIZE), TOKEN_ACCOUNT_SIZE as u64, &ctx.accounts.token_program.key(),
+        )?;
+        token::initialize_account(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(),
+                InitializeAccount {
+                    account: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.collateral_mint.to_account_info(),
+                    authority: ctx.accounts.market.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                }),
+        )?;
+
+        // Transfer initial liquidity
+        token::transfer(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: ctx.accounts.authority_collateral.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            }),
+            initial_liquidity,
+        )?;
+
+        let pool = &mut ctx.accounts.outcome_pool;
+        pool.market_id = market_id;
+        pool.outcome_count = outcome_count;
+        // The deposit mints `initial_liquidity` complete sets, all held by the pool
+        pool.reserves = [0; MAX_OUTCOMES];
+        pool.reserves[..outcome_count as usize].fill(initial_liquidity);
+        pool.supplies = [0; MAX_OUTCOMES];
+        pool.winning_index = None;
+        pool.bump = ctx.bumps.outcome_pool;
+
+        let market = &mut ctx.accounts.market;
+        market.market_id = market_id;
+        market.authority = platform.authority;
+        market.creator = ctx.accounts.authority.key();
+        market.question = question.clone();
+        market.description = description;
+        market.category = category;
+        market.collateral_mint = ctx.accounts.collateral_mint.key();
+        market.vault = ctx.accounts.vault.key();
+        market.total_liquidity = initial_liquidity;
+        market.resolution_source = resolution_source;
+        market.created_at = now;
+        market.expires_at = expires_at;
+        market.lock_time = lock_time;
+        market.status = STATUS_ACTIVE;
+        market.fee_bps = platform.fee_bps;
+        market.bump = ctx.bumps.market;
+        market.outcome_count = outcome_count;
+
+        emit!(CategoricalMarketCreated { market_id, question, outcome_count, expires_at, initial_liquidity });
+        Ok(())
+    }
+
+    /// Buy outcome `outcome_index` of a categorical market. The whole trading fee goes to the
+    /// treasury: unlike binary markets, there is no LP or creator share.
+    pub fn buy_categorical(ctx: Context<BuyCategorical>, outcome_index: u8, amount: u64, min_tokens_out: u64) -> Result<()> {
+        require!(amount > 0, GhostOddsError::ZeroAmount);
+        let market = &ctx.accounts.market;
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
+
+        let fee = math::fee_for(amount, market.fee_bps)?;
+        let input_after_fee = amount.checked_sub(fee).ok_or(GhostOddsError::MathOverflow)?;
+        require!(input_after_fee > 0, GhostOddsError::ZeroAmount);
+
+        let tokens_out = ctx.accounts.outcome_pool.buy(outcome_index, input_after_fee)?;
+        require!(tokens_out > 0, GhostOddsError::ZeroAmount);
+        require!(tokens_out >= min_tokens_out, GhostOddsError::SlippageExceeded);
+
+        // Transfer net amount (after fee) to vault
+        token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.user_collateral.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        }), input_after_fee)?;
+
+        // Transfer fee directly to treasury
+        if fee > 0 {
+            token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: ctx.accounts.user_collateral.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            }), fee)?;
+        }
+
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        token::mint_to(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.outcome_mint.to_account_info(),
+                to: ctx.accounts.user_outcome_tokens.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds),
+            tokens_out)?;
+
+        let market = &mut ctx.accounts.market;
+        market.volume = market.volume.checked_add(amount).ok_or(GhostOddsError::MathOverflow)?;
+        let platform = &mut ctx.accounts.platform;
+        platform.total_volume = platform.total_volume.checked_add(amount).ok_or(GhostOddsError::MathOverflow)?;
+
+        emit!(CategoricalPurchased {
+            market_id: market.market_id, user: ctx.accounts.user.key(), outcome_index, amount_in: amount, tokens_out, fee,
+        });
+        Ok(())
+    }
+
+    /// Sell outcome `outcome_index` of a categorical market back to the pool. As on buys, the
+    /// whole trading fee goes to the treasury.
+    pub fn sell_categorical(ctx: Context<SellCategorical>, outcome_index: u8, amount: u64, min_collateral_out: u64) -> Result<()> {
+        require!(amount > 0, GhostOddsError::ZeroAmount);
+        let market = &ctx.accounts.market;
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(clock.unix_timestamp < market.lock_time, GhostOddsError::MarketLocked);
+
+        let collateral_before_fee = ctx.accounts.outcome_pool.sell(outcome_index, amount)?;
+        let fee = math::fee_for(collateral_before_fee, market.fee_bps)?;
+        let collateral_out = collateral_before_fee.checked_sub(fee).ok_or(GhostOddsError::MathOverflow)?;
+        require!(collateral_out > 0, GhostOddsError::ZeroAmount);
+        require!(collateral_out >= min_collateral_out, GhostOddsError::SlippageExceeded);
+
+        token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(), Burn {
+            mint: ctx.accounts.outcome_mint.to_account_info(),
+            from: ctx.accounts.user_outcome_tokens.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        }), amount)?;
+
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+
+        // Transfer collateral to user
+        token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.user_collateral.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), collateral_out)?;
+
+        // Transfer fee from vault to treasury
+        if fee > 0 {
+            token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+                authority: ctx.accounts.market.to_account_info(),
+            }, signer_seeds), fee)?;
+        }
+
+        let market = &mut ctx.accounts.market;
+        market.volume = market.volume.checked_add(collateral_before_fee).ok_or(GhostOddsError::MathOverflow)?;
+        let platform = &mut ctx.accounts.platform;
+        platform.total_volume = platform.total_volume.checked_add(collateral_before_fee).ok_or(GhostOddsError::MathOverflow)?;
+
+        emit!(CategoricalSold {
+            market_id: market.market_id, user: ctx.accounts.user.key(), outcome_index, tokens_in: amount, collateral_out, fee,
+        });
+        Ok(())
+    }
+
+    /// The platform authority or a resolver selects the winning outcome of a categorical market
+    /// after expiry.
+    pub fn resolve_categorical(ctx: Context<ResolveCategorical>, winning_index: u8) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.platform.paused, GhostOddsError::PlatformPaused);
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.platform.authority
+                || ctx.accounts.resolver_role.as_ref().is_some_and(|role| role.has(Role::RESOLVER)),
+            GhostOddsError::Unauthorized
+        );
+        require!(market.status == STATUS_ACTIVE, GhostOddsError::MarketNotActive);
+        require!(clock.unix_timestamp >= market.expires_at, GhostOddsError::MarketNotExpired);
+        require!(winning_index < market.outcome_count, GhostOddsError::InvalidOutcomeIndex);
+
+        ctx.accounts.outcome_pool.winning_index = Some(winning_index);
+        market.resolved_at = Some(clock.unix_timestamp);
+        market.status = STATUS_RESOLVED;
+        emit!(CategoricalResolved { market_id: market.market_id, winning_index, resolved_at: clock.unix_timestamp });
+        Ok(())
+    }
+
+    pub fn redeem_categorical_winnings(ctx: Context<RedeemCategorical>, outcome_index: u8) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.status == STATUS_RESOLVED, GhostOddsError::MarketNotResolved);
+        let winning_index = ctx.accounts.outcome_pool.winning_index.ok_or(GhostOddsError::MarketNotResolved)?;
+        require!(outcome_index == winning_index, GhostOddsError::NotWinningOutcome);
+        let winning_amount = ctx.accounts.user_outcome_tokens.amount;
+        require!(winning_amount > 0, GhostOddsError::NoWinnings);
+        let total_winning_supply = ctx.accounts.outcome_pool.supplies[outcome_index as usize];
+        // Every winning token was minted as part of a complete set backed by one unit in the vault
+        let payout = winning_amount;
+
+        token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(), Burn {
+            mint: ctx.accounts.outcome_mint.to_account_info(),
+            from: ctx.accounts.user_outcome_tokens.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        }), winning_amount)?;
+
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.vault.to_account_info(), to: ctx.accounts.user_collateral.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), payout)?;
+
+        let pool = &mut ctx.accounts.outcome_pool;
+        pool.supplies[outcome_index as usize] = total_winning_supply.checked_sub(winning_amount).ok_or(GhostOddsError::MathOverflow)?;
+        emit!(WinningsRedeemed { market_id: market.market_id, user: ctx.accounts.user.key(), payout });
+        Ok(())
+    }
+
+    /// Refund one outcome's tokens of a cancelled categorical market at 1 / `outcome_count`
+    /// collateral each (one unit per complete set). The refund is rounded down; the remainder
+    /// stays in the vault and goes to the creator through `withdraw_categorical_surplus`.
+    pub fn redeem_categorical_cancelled(ctx: Context<RedeemCategorical>, outcome_index: u8) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.status == STATUS_CANCELLED, GhostOddsError::MarketNotCancelled);
+        let amount = ctx.accounts.user_outcome_tokens.amount;
+        require!(amount > 0, GhostOddsError::NoWinnings);
+        let refund = amount / market.outcome_count as u64;
+        require!(refund > 0, GhostOddsError::NoWinnings);
+
+        token::burn(CpiContext::new(ctx.accounts.token_program.to_account_info(), Burn {
+            mint: ctx.accounts.outcome_mint.to_account_info(),
+            from: ctx.accounts.user_outcome_tokens.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        }), amount)?;
+
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.vault.to_account_info(), to: ctx.accounts.user_collateral.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), refund)?;
+
+        let pool = &mut ctx.accounts.outcome_pool;
+        let supply = &mut pool.supplies[outcome_index as usize];
+        *supply = supply.checked_sub(amount).ok_or(GhostOddsError::MathOverflow)?;
+        emit!(CancelledRedeemed { market_id: market.market_id, user: ctx.accounts.user.key(), refund });
+        Ok(())
+    }
+
+    /// Pay the creator of a settled categorical market what the vault holds beyond the claims of
+    /// outstanding outcome tokens: the pool's reserve of the winning outcome, plus rounding dust
+    /// left by trades and cancelled refunds. Safe to call any number of times, before or after
+    /// holders redeem.
+    pub fn withdraw_categorical_surplus(ctx: Context<WithdrawCategoricalSurplus>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let owed = ctx.accounts.outcome_pool.owed(market.status)?;
+        let amount = ctx.accounts.vault.amount.saturating_sub(owed);
+        require!(amount > 0, GhostOddsError::ZeroAmount);
+
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        token::transfer(CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), Transfer {
+            from: ctx.accounts.vault.to_account_info(), to: ctx.accounts.authority_collateral.to_account_info(),
+            authority: ctx.accounts.market.to_account_info(),
+        }, signer_seeds), amount)?;
+
+        let market = &mut ctx.accounts.market;
+        market.total_liquidity = market.total_liquidity.saturating_sub(amount);
+        emit!(CategoricalSurplusWithdrawn { market_id: market.market_id, authority: ctx.accounts.authority.key(), amount });
+        Ok(())
+    }
+}
+
+/// Pay a reviewed market's creator bond to `to` and close its account, returning the rent to
+/// the creator. Does nothing for markets created without a bond.
+fn release_creator_bond<'info>(
+    market: &Account<'info, Market>,
+    creator_bond_vault: &UncheckedAccount<'info>,
+    creator: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    to: AccountInfo<'info>,
+) -> Result<()> {
+    if market.creator_bond == 0 {
+        return Ok(());
+    }
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+    token::transfer(CpiContext::new_with_signer(token_program.to_account_info(), Transfer {
+        from: creator_bond_vault.to_account_info(),
+        to,
+        authority: market.to_account_info(),
+    }, signer_seeds), market.creator_bond)?;
+    token::close_account(CpiContext::new_with_signer(token_program.to_account_info(), CloseAccount {
+        account: creator_bond_vault.to_account_info(),
+        destination: creator.clone(),
+        authority: market.to_account_info(),
+    }, signer_seeds))
+}
+
+/// Pay the liquidity deposited into a market that never traded (`total_liquidity`) to `to`.
+/// Returns the amount refunded.
+fn refund_creator_liquidity<'info>(
+    market: &Account<'info, Market>,
+    vault: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    to: AccountInfo<'info>,
+) -> Result<u64> {
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+    token::transfer(CpiContext::new_with_signer(token_program.to_account_info(), Transfer {
+        from: vault.to_account_info(),
+        to,
+        authority: market.to_account_info(),
+    }, signer_seeds), market.total_liquidity)?;
+    Ok(market.total_liquidity)
+}
+
+/// Close a market that left review without trading. Nothing is left to trade or withdraw: LP
+/// shares are worth nothing from here on.
+fn close_unreviewed_market(market: &mut Market) {
+    market.yes_amount = 0;
+    market.no_amount = 0;
+    market.total_liquidity = 0;
+    market.creator_bond = 0;
+    market.status = STATUS_REJECTED;
+}
+
+/// Fill resting asks on YES (`is_yes`) or NO priced at most `limit_price_bps`, cheapest first,
+/// spending at most `budget` collateral. Does nothing without the order book accounts.
+/// Returns the tokens bought and the collateral spent.
+fn fill_resting_asks(
+    accounts: &mut BuyOutcome,
+    position_bump: u8,
+    pool: &math::Pool,
+    is_yes: bool,
+    budget: u64,
+    limit_price_bps: u16,
+) -> Result<(u64, u64)> {
+    let Some(order_book) = accounts.order_book.as_mut() else { return Ok((0, 0)) };
+    let market_id = accounts.market.market_id;
+    // Fills cost at most `budget` less its fee, so that their own fee keeps the total within it
+    let fill_budget = budget.checked_sub(math::fee_for(budget, pool.fee_bps)?).ok_or(GhostOddsError::MathOverflow)?;
+    let mut tokens = 0u64;
+    let mut cost = 0u64;
+    while let Some(index) = order_book.best_match(is_yes, true, limit_price_bps) {
+        let order = &mut order_book.orders[index];
+        let affordable = ((fill_budget - cost) as u128 * 10000 / order.price_bps as u128).min(u64::MAX as u128) as u64;
+        let fill = order.remaining.min(affordable);
+        if fill == 0 {
+            break;
+        }
+        let fill_cost = math::collateral_at_price(fill, order.price_bps, true)?;
+        order.remaining -= fill;
+        order.escrowed = order.escrowed.checked_sub(fill).ok_or(GhostOddsError::MathOverflow)?;
+        order.proceeds = order.proceeds.checked_add(fill_cost).ok_or(GhostOddsError::MathOverflow)?;
+        tokens = tokens.checked_add(fill).ok_or(GhostOddsError::MathOverflow)?;
+        cost = cost.checked_add(fill_cost).ok_or(GhostOddsError::MathOverflow)?;
+        emit!(OrderFilled {
+            market_id, order_id: order.order_id, maker: order.owner, taker: accounts.user.key(), is_yes,
+            maker_is_bid: false, price_bps: order.price_bps, quantity: fill,
+        });
+        order_book.retire_if_filled(index)?;
+    }
+    if tokens == 0 {
+        return Ok((0, 0));
+    }
+
+    let order_escrow = accounts.order_escrow.as_ref().ok_or(GhostOddsError::OrderBookAccountsRequired)?;
+    let (outcome_escrow, user_outcome_tokens) = if is_yes {
+        (accounts.yes_escrow.as_ref(), accounts.user_yes_tokens.to_account_info())
+    } else {
+        (accounts.no_escrow.as_ref(), accounts.user_no_tokens.to_account_info())
+    };
+    let outcome_escrow = outcome_escrow.ok_or(GhostOddsError::OrderBookAccountsRequired)?;
+    token::transfer(CpiContext::new(accounts.token_program.to_account_info(), Transfer {
+        from: accounts.user_collateral.to_account_info(),
+        to: order_escrow.to_account_info(),
+        authority: accounts.user.to_account_info(),
+    }), cost)?;
+    let (fee, lp_fee, creator_fee, protocol_fee) = charge_book_fill_fee(
+        &mut accounts.market, pool, cost, &accounts.user_collateral, &accounts.user, &accounts.vault,
+        &accounts.treasury, accounts.creator_collateral.as_deref(), &accounts.token_program,
+    )?;
+    let spent = cost.checked_add(fee).ok_or(GhostOddsError::MathOverflow)?;
+    let market_id_bytes = market_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[accounts.market.bump]]];
+    token::transfer(CpiContext::new_with_signer(accounts.token_program.to_account_info(), Transfer {
+        from: outcome_escrow.to_account_info(),
+        to: user_outcome_tokens,
+        authority: accounts.market.to_account_info(),
+    }, signer_seeds), tokens)?;
+
+    let market = &mut accounts.market;
+    market.volume = market.volume.checked_add(spent).ok_or(GhostOddsError::MathOverflow)?;
+    let platform = &mut accounts.platform;
+    platform.total_volume = platform.total_volume.checked_add(spent).ok_or(GhostOddsError::MathOverflow)?;
+
+    let position = &mut accounts.user_position;
+    position.user = accounts.user.key();
+    position.market_id = market_id;
+    if is_yes {
+        position.yes_tokens = position.yes_tokens.checked_add(tokens).ok_or(GhostOddsError::MathOverflow)?;
+    } else {
+        position.no_tokens = position.no_tokens.checked_add(tokens).ok_or(GhostOddsError::MathOverflow)?;
+    }
+    position.total_deposited = position.total_deposited.checked_add(spent).ok_or(GhostOddsError::MathOverflow)?;
+    position.bump = position_bump;
+
+    emit!(OutcomePurchased {
+        market_id, user: accounts.user.key(), is_yes, amount_in: spent, tokens_out: tokens, fee_bps: pool.fee_bps, fee,
+        lp_fee, creator_fee, protocol_fee,
+    });
+    Ok((tokens, spent))
+}
+
+/// Charge the taker of book fills worth `cost` the market's trading fee from `user_collateral`,
+/// split as on the AMM: the LP fee is minted as complete sets into the reserves, the rest goes to
+/// the treasury and the creator. Returns (fee, lp_fee, creator_fee, protocol_fee).
+#[allow(clippy::too_many_arguments)]
+fn charge_book_fill_fee<'info>(
+    market: &mut Account<'info, Market>,
+    pool: &math::Pool,
+    cost: u64,
+    user_collateral: &Account<'info, TokenAccount>,
+    user: &Signer<'info>,
+    vault: &Account<'info, TokenAccount>,
+    treasury: &Account<'info, TokenAccount>,
+    creator_collateral: Option<&Account<'info, TokenAccount>>,
+    token_program: &Program<'info, Token>,
+) -> Result<(u64, u64, u64, u64)> {
+    let fee = math::fee_for(cost, pool.fee_bps)?;
+    let (lp_fee, creator_fee, protocol_fee) = pool.split_fee(fee)?;
+    for (to, amount) in [(Some(vault), lp_fee), (Some(treasury), protocol_fee), (creator_collateral, creator_fee)] {
+        if amount > 0 {
+            let to = to.ok_or(GhostOddsError::CreatorFeeAccountRequired)?;
+            token::transfer(CpiContext::new(token_program.to_account_info(), Transfer {
+                from: user_collateral.to_account_info(),
+                to: to.to_account_info(),
+                authority: user.to_account_info(),
+            }), amount)?;
+        }
+    }
+    let (yes_amount, no_amount) = pool.amounts_after(true, market.yes_amount, market.no_amount, lp_fee)?;
+    market.yes_amount = yes_amount;
+    market.no_amount = no_amount;
+    Ok((fee, lp_fee, creator_fee, protocol_fee))
+}
+
+/// Move collateral and mint outcome tokens for a quoted buy, then record it on the market,
+/// platform and position.
+fn execute_buy(accounts: &mut BuyOutcome, position_bump: u8, is_yes: bool, quote: math::BuyQuote) -> Result<()> {
+    let math::BuyQuote { amount_in, input_after_fee, fee_bps, fee, lp_fee, creator_fee, protocol_fee, tokens_out, yes_amount, no_amount } = quote;
+    let market = &accounts.market;
+
+    // Transfer net amount (after fee) and the LP fee to vault
+    token::transfer(CpiContext::new(accounts.token_program.to_account_info(), Transfer {
+        from: accounts.user_collateral.to_account_info(),
+        to: accounts.vault.to_account_info(),
+        authority: accounts.user.to_account_info(),
+    }), input_after_fee.checked_add(lp_fee).ok_or(GhostOddsError::MathOverflow)?)?;
+
+    // Transfer protocol fee directly to treasury
+    if protocol_fee > 0 {
+        token::transfer(CpiContext::new(accounts.token_program.to_account_info(), Transfer {
+            from: accounts.user_collateral.to_account_info(),
+            to: accounts.treasury.to_account_info(),
+            authority: accounts.user.to_account_info(),
+        }), protocol_fee)?;
+    }
+    if creator_fee > 0 {
+        let creator_collateral = accounts.creator_collateral.as_ref().ok_or(GhostOddsError::CreatorFeeAccountRequired)?;
+        token::transfer(CpiContext::new(accounts.token_program.to_account_info(), Transfer {
+            from: accounts.user_collateral.to_account_info(),
+            to: creator_collateral.to_account_info(),
+            authority: accounts.user.to_account_info(),
+        }), creator_fee)?;
+    }
+
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+    let (mint_info, dest_info) = if is_yes {
+        (accounts.yes_mint.to_account_info(), accounts.user_yes_tokens.to_account_info())
+    } else {
+        (accounts.no_mint.to_account_info(), accounts.user_no_tokens.to_account_info())
+    };
+    token::mint_to(CpiContext::new_with_signer(accounts.token_program.to_account_info(),
+        MintTo { mint: mint_info, to: dest_info, authority: accounts.market.to_account_info() }, signer_seeds),
+        tokens_out)?;
+
+    let market = &mut accounts.market;
+    market.yes_amount = yes_amount;
+    market.no_amount = no_amount;
+    market.volume = market.volume.checked_add(amount_in).ok_or(GhostOddsError::MathOverflow)?;
+
+    // Finding 6: increment platform total_volume
+    let platform = &mut accounts.platform;
+    platform.total_volume = platform.total_volume.checked_add(amount_in).ok_or(GhostOddsError::MathOverflow)?;
+
+    let position = &mut accounts.user_position;
+    position.user = accounts.user.key();
+    position.market_id = market.market_id;
+    if is_yes {
+        position.yes_tokens = position.yes_tokens.checked_add(tokens_out).ok_or(GhostOddsError::MathOverflow)?;
+    } else {
+        position.no_tokens = position.no_tokens.checked_add(tokens_out).ok_or(GhostOddsError::MathOverflow)?;
+    }
+    position.total_deposited = position.total_deposited.checked_add(amount_in).ok_or(GhostOddsError::MathOverflow)?;
+    position.bump = position_bump;
+
+    emit!(OutcomePurchased {
+        market_id: market.market_id, user: accounts.user.key(), is_yes, amount_in, tokens_out, fee_bps, fee, lp_fee, creator_fee, protocol_fee,
+    });
+    Ok(())
+}
+
+/// Burn outcome tokens and pay out collateral for a quoted sell, then record it on the market,
+/// platform and position.
+fn execute_sell(accounts: &mut SellOutcome, is_yes: bool, quote: math::SellQuote) -> Result<()> {
+    let math::SellQuote { tokens_in, collateral_before_fee, fee_bps, fee, lp_fee, creator_fee, protocol_fee, collateral_out, yes_amount, no_amount } = quote;
+    let market = &accounts.market;
+
+    let (mint_info, from_info) = if is_yes {
+        (accounts.yes_mint.to_account_info(), accounts.user_yes_tokens.to_account_info())
+    } else {
+        (accounts.no_mint.to_account_info(), accounts.user_no_tokens.to_account_info())
+    };
+    token::burn(CpiContext::new(accounts.token_program.to_account_info(),
+        Burn { mint: mint_info, from: from_info, authority: accounts.user.to_account_info() }), tokens_in)?;
+
+    let market_id_bytes = market.market_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+
+    // Transfer collateral to user
+    token::transfer(CpiContext::new_with_signer(accounts.token_program.to_account_info(), Transfer {
+        from: accounts.vault.to_account_info(),
+        to: accounts.user_collateral.to_account_info(),
+        authority: accounts.market.to_account_info(),
+    }, signer_seeds), collateral_out)?;
+
+    // Transfer protocol fee from vault to treasury; the LP fee stays in the vault
+    if protocol_fee > 0 {
+        token::transfer(CpiContext::new_with_signer(accounts.token_program.to_account_info(), Transfer {
+            from: accounts.vault.to_account_info(),
+            to: accounts.treasury.to_account_info(),
+            authority: accounts.market.to_account_info(),
+        }, signer_seeds), protocol_fee)?;
+    }
+    if creator_fee > 0 {
+        let creator_collateral = accounts.creator_collateral.as_ref().ok_or(GhostOddsError::CreatorFeeAccountRequired)?;
+        token::transfer(CpiContext::new_with_signer(accounts.token_program.to_account_info(), Transfer {
+            from: accounts.vault.to_account_info(),
+            to: creator_collateral.to_account_info(),
+            authority: accounts.market.to_account_info(),
+        }, signer_seeds), creator_fee)?;
+    }
+
+    let market = &mut accounts.market;
+    market.yes_amount = yes_amount;
+    market.no_amount = no_amount;
+    market.volume = market.volume.checked_add(collateral_before_fee).ok_or(GhostOddsError::MathOverflow)?;
+    // Finding 7: `total_liquidity` only tracks LP deposits and withdrawals, not trades
+
+    // Finding 6: increment platform total_volume
+    let platform = &mut accounts.platform;
+    platform.total_volume = platform.total_volume.checked_add(collateral_before_fee).ok_or(GhostOddsError::MathOverflow)?;
+
+    let position = &mut accounts.user_position;
+    if is_yes {
+        position.yes_tokens = position.yes_tokens.checked_sub(tokens_in).ok_or(GhostOddsError::MathOverflow)?;
+    } else {
+        position.no_tokens = position.no_tokens.checked_sub(tokens_in).ok_or(GhostOddsError::MathOverflow)?;
+    }
+    position.total_withdrawn = position.total_withdrawn.checked_add(collateral_out).ok_or(GhostOddsError::MathOverflow)?;
+
+    emit!(OutcomeSold {
+        market_id: market.market_id, user: accounts.user.key(), is_yes, tokens_in, collateral_out, fee_bps, fee, lp_fee, creator_fee, protocol_fee,
+    });
+    Ok(())
+}
+
+/// Burn all of the user's YES and NO tokens, paying `yes_weight / scale` collateral per YES token
+/// and `no_weight / scale` per NO token (INVALID and scalar markets). The weights of a pair add
+/// up to `scale`, so every outstanding pair is backed by its unit of collateral in the vault.
+/// Returns the YES and NO amounts burned and the collateral paid out.
+fn redeem_weighted(accounts: &mut RedeemWinnings, yes_weight: u128, no_weight: u128, scale: u128) -> Result<(u64, u64, u64)> {
+    let market = &accounts.market;
+    let yes_amount = accounts.user_yes_tokens.amount;
+    let no_amount = accounts.user_no_tokens.amount;
+    require!(yes_amount > 0 || no_amount > 0, GhostOddsError::NoWinnings);
+
+    let user_value = (yes_amount as u128).checked_mul(yes_weight).ok_or(GhostOddsError::MathOverflow)?
+        .checked_add((no_amount as u128).checked_mul(no_weight).ok_or(GhostOddsError::MathOverflow)?)
+        .ok_or(GhostOddsError::MathOverflow)?;
+    let payout = u64::try_from(user_value.checked_div(scale).ok_or(GhostOddsError::MathOverflow)?)
+        .map_err(|_| GhostOddsError::MathOverflow)?;
+
+    if yes_amount > 0 {
+        token::burn(CpiContext::new(accounts.token_program.to_account_info(), Burn {
+            mint: accounts.yes_mint.to_account_info(), from: accounts.user_yes_tokens.to_account_info(),
+            authority: accounts.user.to_account_info(),
+        }), yes_amount)?;
+    }
+    if no_amount > 0 {
+        token::burn(CpiContext::new(accounts.token_program.to_account_info(), Burn {
+            mint: accounts.no_mint.to_account_info(), from: accounts.user_no_tokens.to_account_info(),
+            authority: accounts.user.to_account_info(),
+        }), no_amount)?;
+    }
+
+    if payout > 0 {
+        let market_id_bytes = market.market_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[b"market", market_id_bytes.as_ref(), &[market.bump]]];
+        token::transfer(CpiContext::new_with_signer(accounts.token_program.to_account_info(), Transfer {
+            from: accounts.vault.to_account_info(), to: accounts.user_collateral.to_account_info(),
+            authority: accounts.market.to_account_info(),
+        }, signer_seeds), payout)?;
+    }
+
+    let position = &mut accounts.user_position;
+    position.yes_tokens = 0;
+    position.no_tokens = 0;
+    position.total_withdrawn = position.total_withdrawn.checked_add(payout).ok_or(GhostOddsError::MathOverflow)?;
+    Ok((yes_amount, no_amount, payout))
+}
+
+/// Read the oracle feed bound to `market` with its backend's reader.
+fn read_market_oracle(market: &Market, info: &AccountInfo) -> Result<oracle::OraclePrice> {
+    let kind = market.oracle_kind.ok_or(GhostOddsError::OracleRequired)?;
+    let feed_id = market.oracle_feed_id.ok_or(GhostOddsError::OracleRequired)?;
+    oracle::read_price(kind, info, &feed_id)
+}
+
+/// Check an oracle price's confidence and scale price and confidence to 6 decimals.
+fn normalize_oracle_price(oracle_price: &oracle::OraclePrice, max_conf_bps: u64) -> Result<(u64, u64)> {
+    // Validate confidence: conf / |price| <= max_conf_bps
+    let abs_price = (oracle_price.price as i128).unsigned_abs();
+    require!(abs_price > 0, GhostOddsError::InvalidOracle);
+    let conf_bps = (oracle_price.conf as u128)
+        .checked_mul(10000)
+        .ok_or(GhostOddsError::MathOverflow)?
+        .checked_div(abs_price)
+        .ok_or(GhostOddsError::MathOverflow)?;
+    require!(conf_bps <= max_conf_bps as u128, GhostOddsError::PriceConfidenceTooWide);
+
+    // Normalize price to compare with resolution_value (u64, 6 decimals assumed)
+    require!(oracle_price.price > 0, GhostOddsError::InvalidOracle);
+    Ok((
+        normalize_price(oracle_price.price as u64, oracle_price.expo)?,
+        normalize_price(oracle_price.conf, oracle_price.expo)?,
+    ))
+}
+
+/// Compare a normalized settlement price against the market's target using `resolution_operator`.
+/// Scalar markets report whether LONG settles at least as high as SHORT.
+fn evaluate_outcome(market: &Market, price: u64) -> Result<bool> {
+    let target = || market.resolution_value.ok_or(GhostOddsError::InvalidOperator);
+    let range = || market.resolution_range.ok_or(GhostOddsError::InvalidRange);
+    Ok(match market.resolution_operator {
+        OP_GTE => price >= target()?,
+        OP_LTE => price <= target()?,
+        OP_BETWEEN => range()?.contains(price),
+        OP_GT => price > target()?,
+        OP_LT => price < target()?,
+        OP_SCALAR => {
+            let (long_weight, short_weight) = range()?.scalar_weights(price);
+            long_weight >= short_weight
+        }
+        _ => return Err(GhostOddsError::InvalidOperator.into()),
+    })
+}
+
+/// Scale a Pyth value with exponent `expo` to the 6-decimal fixed point used by `resolution_value`.
+fn normalize_price(raw: u64, expo: i32) -> Result<u64> {
+    let scaled = if expo >= 0 {
+        (raw as u128)
+            .checked_mul(10u128.pow(6u32.checked_add(expo as u32).ok_or(GhostOddsError::MathOverflow)?))
+            .ok_or(GhostOddsError::MathOverflow)?
+    } else {
+        let neg_exp = expo.unsigned_abs();
+        if neg_exp <= 6 {
+            (raw as u128)
+                .checked_mul(10u128.pow(6 - neg_exp))
+                .ok_or(GhostOddsError::MathOverflow)?
+        } else {
+            (raw as u128)
+                .checked_div(10u128.pow(neg_exp - 6))
+                .ok_or(GhostOddsError::MathOverflow)?
+        }
+    };
+    Ok(u64::try_from(scaled).map_err(|_| GhostOddsError::MathOverflow)?)
+}
+
+/// Whether a `resolution_source` is the provider prefix of `kind` followed by the feed's
+/// registered symbol, e.g. "pyth:SOL/USD" (case-insensitive, spaces allowed after the prefix).
+/// Manual markets must not name any oracle provider.
+fn source_matches_oracle(source: &str, oracle: Option<(OracleKind, &str)>) -> bool {
+    let strip_prefix = |prefix: &str| {
+        source.get(..prefix.len()).filter(|head| head.eq_ignore_ascii_case(prefix)).map(|_| &source[prefix.len()..])
+    };
+    match oracle {
+        Some((kind, symbol)) => {
+            strip_prefix(kind.source_prefix()).is_some_and(|rest| rest.trim_start().eq_ignore_ascii_case(symbol))
+        }
+        None => strip_prefix(PYTH_SOURCE_PREFIX).is_none() && strip_prefix(SWITCHBOARD_SOURCE_PREFIX).is_none(),
+    }
+}
+
+// ============ Accounts ============
+#[account]
+pub struct Platform {
+    pub authority: Pubkey,
+    pub market_count: u64,
+    pub total_volume: u64,
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub bump: u8,
+    /// Part of `fee_bps` that stays in the pool for liquidity providers.
+    pub lp_fee_bps: u16,
+    /// Authority nominated by `propose_authority`, pending its `accept_authority`.
+    pub pending_authority: Option<Pubkey>,
+    /// Halts trading and resolution on every market; redemptions stay open.
+    pub paused: bool,
+    /// Key allowed to pause and unpause alongside the authority.
+    pub guardian: Option<Pubkey>,
+    /// Collateral bond posted by users creating markets without the creator role.
+    pub creator_bond: u64,
+    /// Part of `fee_bps` paid to the creator of such markets once approved.
+    pub creator_fee_bps: u16,
+}
+impl Platform {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 2 + 32 + 1 + 2 + (1 + 32) + 1 + (1 + 32) + 8 + 2;
+
+    /// Whether `key`, holding `role` if any, may pause or unpause the platform and its markets.
+    pub fn can_pause(&self, key: Pubkey, role: Option<&Role>) -> bool {
+        key == self.authority || self.guardian == Some(key) || role.is_some_and(|role| role.has(Role::GUARDIAN))
+    }
+}
+
+/// Permissions delegated by the platform authority (PDA: seeds = [b"role", holder]).
+#[account]
+pub struct Role {
+    pub holder: Pubkey,
+    /// Bit set of `Role::CREATOR`, `Role::RESOLVER`, `Role::GUARDIAN` and `Role::CURATOR`.
+    pub flags: u8,
+    pub bump: u8,
+}
+impl Role {
+    pub const LEN: usize = 8 + 32 + 1 + 1;
+    /// Create markets without a curator's review. Listing grants no resolution rights.
+    pub const CREATOR: u8 = 1 << 0;
+    /// Resolve, invalidate and cancel any market, and settle disputes.
+    pub const RESOLVER: u8 = 1 << 1;
+    /// Pause and unpause the platform and its markets.
+    pub const GUARDIAN: u8 = 1 << 2;
+    /// Approve or reject markets created by users without the creator role.
+    pub const CURATOR: u8 = 1 << 3;
+    pub const ALL: u8 = Self::CREATOR | Self::RESOLVER | Self::GUARDIAN | Self::CURATOR;
+
+    pub fn has(&self, flag: u8) -> bool {
+        self.flags & flag != 0
+    }
+}
+
+/// Symbol an oracle feed is listed under (PDA: seeds = [b"oracle_feed", feed_id]).
+#[account]
+pub struct OracleFeed {
+    pub feed_id: [u8; 32],
+    pub symbol: String,
+    pub bump: u8,
+}
+impl OracleFeed { pub const LEN: usize = 8 + 32 + (4 + MAX_FEED_SYMBOL_LEN) + 1; }
+
+#[account]
+pub struct Market {
+    pub market_id: u64,
+    /// Platform authority at creation. Resolution, invalidation and cancellation are checked
+    /// against the platform's current authority or `Role::RESOLVER`, never the creator.
+    pub authority: Pubkey,
+    pub question: String,
+    pub description: String,
+    pub category: String,
+    pub collateral_mint: Pubkey,
+    pub yes_mint: Pubkey,
+    pub no_mint: Pubkey,
+    pub vault: Pubkey,
+    pub yes_amount: u64,
+    pub no_amount: u64,
+    /// Collateral deposited by liquidity providers (`initial_liquidity` and `add_liquidity`),
+    /// net of collateral withdrawn through `remove_liquidity`.
+    pub total_liquidity: u64,
+    pub volume: u64,
+    pub resolution_source: String,
+    pub resolution_value: Option<u64>,
+    /// 0 = >=, 1 = <=, 2 = between `resolution_range`, 3 = >, 4 = <, 5 = scalar over `resolution_range`
+    /// (no `resolution_value`; `outcome` then reports LONG >= SHORT and payouts follow
+    /// `PriceRange::scalar_weights`)
+    pub resolution_operator: u8,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub lock_time: i64,
+    pub resolved_at: Option<i64>,
+    pub outcome: Option<bool>,
+    pub status: u8,
+    pub fee_bps: u16,
+    pub bump: u8,
+    /// Feed account the market must settle against (None for Pyth pull-oracle and manual markets).
+    pub oracle_feed: Option<Pubkey>,
+    /// Feed ID the settlement price must carry (None for manual markets).
+    pub oracle_feed_id: Option<[u8; 32]>,
+    /// Oracle price used at settlement (6 decimals, same scale as `resolution_value`).
+    pub settlement_price: Option<u64>,
+    /// Confidence interval of `settlement_price` (6 decimals).
+    pub settlement_conf: Option<u64>,
+    /// Publish time of `settlement_price`, within the oracle's staleness bound of `expires_at`.
+    pub settlement_publish_time: Option<i64>,
+    /// Price range for "between" markets (6 decimals, same scale as `resolution_value`).
+    pub resolution_range: Option<PriceRange>,
+    /// Settle on a time-weighted average of recorded samples instead of a single price.
+    pub twap: Option<TwapConfig>,
+    /// Oracle backend the market resolves against (None for manual markets).
+    pub oracle_kind: Option<OracleKind>,
+    /// Redemption price of a YES token in an INVALID market, in bps (NO gets the rest).
+    pub invalid_yes_price_bps: Option<u16>,
+    /// 2 for binary YES/NO markets; 3..=`MAX_OUTCOMES` for categorical markets, whose
+    /// reserves and mints live in the `OutcomePool`.
+    pub outcome_count: u8,
+    /// LP share mint (PDA: seeds = [b"lp_mint", market_id]); one share per unit of
+    /// `initial_liquidity` at creation. Default for categorical markets, which have no LP shares.
+    pub lp_mint: Pubkey,
+    /// Part of `fee_bps` that stays in the pool for liquidity providers.
+    pub lp_fee_bps: u16,
+    /// Pricing curve of `buy_outcome` and `sell_outcome`. For `Curve::Lmsr`, `yes_amount` and
+    /// `no_amount` are the quantities sold by the market maker rather than virtual reserves.
+    pub curve: Curve,
+    /// LMSR liquidity parameter (collateral units); 0 for constant-product markets.
+    pub lmsr_b: u64,
+    /// Fee rising from `fee_bps` towards lock (set by `set_fee_schedule`); None charges `fee_bps`.
+    pub fee_schedule: Option<FeeSchedule>,
+    /// YES price in bps at the start of the current volatility window, and when it started.
+    pub price_anchor_bps: u16,
+    pub price_anchor_at: i64,
+    /// Bond held in `[b"creator_bond", market_id]` while the market is pending review.
+    pub creator_bond: u64,
+    /// Part of the fee paid to `creator` on each trade (markets created without the creator role).
+    pub creator_fee_bps: u16,
+    /// Key that created the market: receives creator fees, bond and liquidity refunds.
+    pub creator: Pubkey,
+}
+impl Market {
+    pub const LEN: usize = 8 + 8 + 32
+        + (4 + MAX_QUESTION_LEN) + (4 + MAX_DESCRIPTION_LEN) + (4 + MAX_CATEGORY_LEN)
+        + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8
+        + (4 + MAX_RESOLUTION_SOURCE_LEN) + (1 + 8) + 1
+        + 8 + 8 + 8 + (1 + 8) + (1 + 1) + 1 + 2 + 1
+        + (1 + 32) + (1 + 32)
+        + (1 + 8) + (1 + 8) + (1 + 8)
+        + (1 + PriceRange::LEN) + (1 + TwapConfig::LEN) + (1 + 1)
+        + (1 + 2) + 1 + 32 + 2 + 1 + 8
+        + (1 + FeeSchedule::LEN) + 2 + 8
+        + 8 + 2 + 32;
+
+    /// Collateral paid per YES and per NO token of a settled binary market, as
+    /// (yes_weight, no_weight, scale): 1 per winning token, the scalar or INVALID weights, and half
+    /// a unit per token of a cancelled market. A YES/NO pair is always worth `scale`.
+    pub fn settlement_weights(&self) -> Result<(u128, u128, u128)> {
+        match self.status {
+            STATUS_RESOLVED if self.resolution_operator == OP_SCALAR => {
+                let range = self.resolution_range.ok_or(GhostOddsError::InvalidRange)?;
+                let price = self.settlement_price.ok_or(GhostOddsError::MarketNotResolved)?;
+                let (long_weight, short_weight) = range.scalar_weights(price);
+                Ok((long_weight, short_weight, (range.upper - range.lower) as u128))
+            }
+            STATUS_RESOLVED => Ok(if self.outcome.ok_or(GhostOddsError::MarketNotResolved)? { (1, 0, 1) } else { (0, 1, 1) }),
+            STATUS_INVALID => {
+                let yes_price_bps = self.invalid_yes_price_bps.ok_or(GhostOddsError::MarketNotInvalid)? as u128;
+                Ok((yes_price_bps, 10000u128.checked_sub(yes_price_bps).ok_or(GhostOddsError::MathOverflow)?, 10000))
+            }
+            STATUS_CANCELLED => Ok((1, 1, 2)),
+            _ => Err(GhostOddsError::MarketNotResolved.into()),
+        }
+    }
+
+    /// Pricing state of a binary market, for the quotes in `math`.
+    pub fn pool(&self) -> math::Pool {
+        math::Pool {
+            curve: self.curve,
+            lmsr_b: self.lmsr_b,
+            yes_amount: self.yes_amount,
+            no_amount: self.no_amount,
+            fee_bps: self.fee_bps,
+            lp_fee_bps: self.lp_fee_bps,
+            creator_fee_bps: self.creator_fee_bps,
+        }
+    }
+
+    /// `pool()` at the fee of a trade at `now`, following the fee schedule if any. The LP and
+    /// creator parts of the fee scale with it. Starts a new volatility window once the current one is over.
+    pub fn trade_pool(&mut self, now: i64) -> Result<math::Pool> {
+        let pool = self.pool();
+        let Some(schedule) = self.fee_schedule else { return Ok(pool) };
+        let price_bps = (math::price_yes(&pool)? * 10000 / math::PRICE_SCALE) as u16;
+        if now.saturating_sub(self.price_anchor_at) >= VOLATILITY_WINDOW {
+            self.price_anchor_bps = price_bps;
+            self.price_anchor_at = now;
+        }
+        let fee_bps = math::dynamic_fee_bps(
+            self.fee_bps, &schedule, self.created_at, self.lock_time, now, price_bps.abs_diff(self.price_anchor_bps),
+        );
+        let scale = |part_bps: u16| {
+            if self.fee_bps == 0 { 0 } else { (part_bps as u32 * fee_bps as u32 / self.fee_bps as u32) as u16 }
+        };
+        Ok(math::Pool {
+            fee_bps,
+            lp_fee_bps: scale(self.lp_fee_bps),
+            creator_fee_bps: scale(self.creator_fee_bps),
+            ..pool
+        })
+    }
+}
+
+/// Dynamic fee of a binary market; the base fee is `Market::fee_bps`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeSchedule {
+    /// Fee reached at `lock_time`, before the volatility component.
+    pub max_fee_bps: u16,
+    /// Extra fee per bps the YES price moved during the current `VOLATILITY_WINDOW`, in bps
+    /// (10000 = one bps of fee per bps of price move).
+    pub volatility_factor_bps: u16,
+}
+impl FeeSchedule { pub const LEN: usize = 2 + 2; }
+
+/// Pricing curves of binary markets.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    /// Fixed-product market maker: x * y = k over virtual YES/NO reserves, backed by LP shares.
+    /// Buys mint and sells burn complete sets through both reserves, so the vault always holds
+    /// one unit of collateral per outstanding YES/NO pair.
+    ConstantProduct,
+    /// Logarithmic market scoring rule with liquidity parameter `Market::lmsr_b`.
+    Lmsr,
+}
+
+/// Oracle backends a market can resolve against.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OracleKind {
+    /// Pyth v2 push-oracle price account, bound by address.
+    PythLegacy,
+    /// Pyth receiver `PriceUpdateV2` accounts, bound by feed ID.
+    PythPull,
+    /// Switchboard On-Demand pull feed, bound by address.
+    Switchboard,
+}
+impl OracleKind {
+    /// Maximum distance between a settlement price's publish time and `expires_at` (seconds).
+    pub fn max_staleness(self) -> u64 {
+        match self {
+            OracleKind::PythLegacy | OracleKind::PythPull => PYTH_MAX_STALENESS,
+            OracleKind::Switchboard => SWITCHBOARD_MAX_STALENESS,
+        }
+    }
+
+    /// Maximum confidence interval as basis points of price.
+    pub fn max_conf_bps(self) -> u64 {
+        match self {
+            OracleKind::PythLegacy | OracleKind::PythPull => PYTH_MAX_CONF_BPS,
+            OracleKind::Switchboard => SWITCHBOARD_MAX_CONF_BPS,
+        }
+    }
+
+    /// `resolution_source` prefix naming this backend.
+    pub fn source_prefix(self) -> &'static str {
+        match self {
+            OracleKind::PythLegacy | OracleKind::PythPull => PYTH_SOURCE_PREFIX,
+            OracleKind::Switchboard => SWITCHBOARD_SOURCE_PREFIX,
+        }
+    }
+}
+
+/// Bounds of a "between" market. YES wins when the settlement price falls inside the range.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceRange {
+    pub lower: u64,
+    pub upper: u64,
+    pub lower_inclusive: bool,
+    pub upper_inclusive: bool,
+}
+impl PriceRange {
+    pub const LEN: usize = 8 + 8 + 1 + 1;
+
+    pub fn contains(&self, price: u64) -> bool {
+        let above_lower = if self.lower_inclusive { price >= self.lower } else { price > self.lower };
+        let below_upper = if self.upper_inclusive { price <= self.upper } else { price < self.upper };
+        above_lower && below_upper
+    }
+
+    /// LONG and SHORT shares of a unit at `price` in a scalar market: `(price - lower)` and
+    /// `(upper - price)` out of `(upper - lower)`, with `price` clamped to the range.
+    pub fn scalar_weights(&self, price: u64) -> (u128, u128) {
+        let clamped = price.clamp(self.lower, self.upper);
+        ((clamped - self.lower) as u128, (self.upper - clamped) as u128)
+    }
+}
+
+/// TWAP settlement parameters of a market.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TwapConfig {
+    /// Averaging window ending at `expires_at` (seconds).
+    pub window: i64,
+    /// Minimum number of recorded samples required to resolve.
+    pub min_samples: u16,
+}
+impl TwapConfig {
+    pub const LEN: usize = 8 + 2;
+
+    /// Shortest spacing between recorded samples: `window / TWAP_MAX_SAMPLES`, at least 1s.
+    fn min_sample_gap(&self) -> i64 {
+        (self.window / TWAP_MAX_SAMPLES as i64).max(1)
+    }
+
+    /// Longest stretch a single sample counts towards coverage: the spacing of `min_samples`
+    /// evenly spread over the window.
+    fn max_sample_hold(&self) -> i64 {
+        self.window / self.min_samples.max(1) as i64
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PriceSample {
+    /// Normalized price (6 decimals).
+    pub price: u64,
+    /// Normalized confidence interval (6 decimals).
+    pub conf: u64,
+    pub publish_time: i64,
+}
+impl PriceSample { pub const LEN: usize = 8 + 8 + 8; }
+
+/// Ring buffer of oracle samples for a TWAP market (PDA: seeds = [b"price_samples", market_id]).
+#[account]
+pub struct PriceSamples {
+    pub market_id: u64,
+    /// Number of valid samples (at most `TWAP_MAX_SAMPLES`).
+    pub len: u16,
+    /// Slot the next sample is written to.
+    pub head: u16,
+    pub samples: [PriceSample; TWAP_MAX_SAMPLES],
+    pub bump: u8,
+}
+impl PriceSamples {
+    pub const LEN: usize = 8 + 8 + 2 + 2 + PriceSample::LEN * TWAP_MAX_SAMPLES + 1;
+
+    /// The `i`-th oldest retained sample.
+    fn get(&self, i: u16) -> PriceSample {
+        let start = (self.head as usize + TWAP_MAX_SAMPLES - self.len as usize) % TWAP_MAX_SAMPLES;
+        self.samples[(start + i as usize) % TWAP_MAX_SAMPLES]
+    }
+
+    fn last(&self) -> PriceSample {
+        self.get(self.len - 1)
+    }
+
+    /// Append a sample, overwriting the oldest once the buffer is full.
+    fn push(&mut self, sample: PriceSample) {
+        self.samples[self.head as usize] = sample;
+        self.head = ((self.head as usize + 1) % TWAP_MAX_SAMPLES) as u16;
+        if (self.len as usize) < TWAP_MAX_SAMPLES {
+            self.len += 1;
+        }
+    }
+
+    /// Seconds up to `until` backed by a sample, counting each sample until the next one but for
+    /// at most `max_hold`.
+    fn coverage(&self, until: i64, max_hold: i64) -> Result<i64> {
+        let mut covered: i64 = 0;
+        for i in 0..self.len {
+            let sample = self.get(i);
+            let next = if i + 1 < self.len { self.get(i + 1).publish_time } else { until };
+            let held = next.checked_sub(sample.publish_time).ok_or(GhostOddsError::MathOverflow)?;
+            covered = covered.checked_add(held.clamp(0, max_hold)).ok_or(GhostOddsError::MathOverflow)?;
+        }
+        Ok(covered)
+    }
+
+    /// Time-weighted average price and confidence up to `until`; each sample holds until the next.
+    /// Returns (price, conf, publish time of the latest sample).
+    fn twap(&self, until: i64) -> Result<(u64, u64, i64)> {
+        require!(self.len > 0, GhostOddsError::InsufficientPriceSamples);
+        let first = self.get(0);
+        let last = self.last();
+        let duration = until.checked_sub(first.publish_time).ok_or(GhostOddsError::MathOverflow)?;
+        if duration <= 0 {
+            return Ok((last.price, last.conf, last.publish_time));
+        }
+
+        let mut weighted_price: u128 = 0;
+        let mut weighted_conf: u128 = 0;
+        for i in 0..self.len {
+            let sample = self.get(i);
+            let next = if i + 1 < self.len { self.get(i + 1).publish_time } else { until };
+            let weight = next.checked_sub(sample.publish_time).ok_or(GhostOddsError::MathOverflow)? as u128;
+            weighted_price = weighted_price
+                .checked_add((sample.price as u128).checked_mul(weight).ok_or(GhostOddsError::MathOverflow)?)
+                .ok_or(GhostOddsError::MathOverflow)?;
+            weighted_conf = weighted_conf
+                .checked_add((sample.conf as u128).checked_mul(weight).ok_or(GhostOddsError::MathOverflow)?)
+                .ok_or(GhostOddsError::MathOverflow)?;
+        }
+        Ok((
+            (weighted_price / duration as u128) as u64,
+            (weighted_conf / duration as u128) as u64,
+            last.publish_time,
+        ))
+    }
+}
+
+/// Pending optimistic-resolution proposal of a manual market
+/// (PDA: seeds = [b"proposal", market_id]). Bonds sit in the `bond_vault` PDA.
+#[account]
+pub struct Proposal {
+    pub market_id: u64,
+    pub proposer: Pubkey,
+    pub outcome: bool,
+    pub proposed_at: i64,
+    pub disputer: Option<Pubkey>,
+    pub disputed_at: Option<i64>,
+    /// Bond posted by each side.
+    pub bond: u64,
+    pub bump: u8,
+}
+impl Proposal { pub const LEN: usize = 8 + 8 + 32 + 1 + 8 + (1 + 32) + (1 + 8) + 8 + 1; }
+
+/// Reserves and outstanding supplies of a categorical market's outcomes
+/// (PDA: seeds = [b"outcome_pool", market_id]).
+///
+/// The pool generalizes the binary fixed-product curve: buying outcome `i` with `x` mints `x`
+/// complete sets into every reserve, then takes from `r_i` the tokens that bring the product of
+/// all reserves back to where it was; selling burns complete sets out of every reserve the same
+/// way. The vault therefore always holds `r_i + supplies[i]` for every outcome `i`, enough to pay
+/// each winning token one unit. The marginal price of outcome `i` is `(1 / r_i) / sum_j(1 / r_j)`.
+#[account]
+pub struct OutcomePool {
+    pub market_id: u64,
+    pub outcome_count: u8,
+    pub reserves: [u64; MAX_OUTCOMES],
+    /// Outstanding token supply of each outcome mint.
+    pub supplies: [u64; MAX_OUTCOMES],
+    pub winning_index: Option<u8>,
+    pub bump: u8,
+}
+impl OutcomePool {
+    pub const LEN: usize = 8 + 8 + 1 + 8 * MAX_OUTCOMES + 8 * MAX_OUTCOMES + (1 + 1) + 1;
+
+    /// Buy outcome `index` with `amount` collateral (after fees). Returns the tokens minted.
+    /// The new reserve is rounded up so rounding dust stays in the pool.
+    fn buy(&mut self, index: u8, amount: u64) -> Result<u64> {
+        let index = index as usize;
+        require!(index < self.outcome_count as usize, GhostOddsError::InvalidOutcomeIndex);
+        // r_i * prod(r_j / (r_j + amount)) over the other outcomes
+        let mut new_reserve = self.reserves[index] as u128;
+        for j in (0..self.outcome_count as usize).filter(|&j| j != index) {
+            let reserve = self.reserves[j] as u128;
+            let grown = reserve.checked_add(amount as u128).ok_or(GhostOddsError::MathOverflow)?;
+            new_reserve = new_reserve.checked_mul(reserve).ok_or(GhostOddsError::MathOverflow)?
+                .div_ceil(grown);
+            self.reserves[j] = u64::try_from(grown).map_err(|_| GhostOddsError::MathOverflow)?;
+        }
+        let grown = (self.reserves[index] as u128).checked_add(amount as u128).ok_or(GhostOddsError::MathOverflow)?;
+        let tokens_out = u64::try_from(grown.checked_sub(new_reserve).ok_or(GhostOddsError::MathOverflow)?)
+            .map_err(|_| GhostOddsError::MathOverflow)?;
+        self.reserves[index] = new_reserve as u64;
+        self.supplies[index] = self.supplies[index].checked_add(tokens_out).ok_or(GhostOddsError::MathOverflow)?;
+        Ok(tokens_out)
+    }
+
+    /// Sell `amount` tokens of outcome `index`. Returns the collateral released (before fees):
+    /// once the sold tokens join their reserve, the largest number `c` of complete sets that can
+    /// be burned out of every reserve while keeping the reserve product.
+    fn sell(&mut self, index: u8, amount: u64) -> Result<u64> {
+        let index = index as usize;
+        require!(index < self.outcome_count as usize, GhostOddsError::InvalidOutcomeIndex);
+        let reserve = self.reserves[index] as u128;
+        let grown = reserve.checked_add(amount as u128).ok_or(GhostOddsError::MathOverflow)?;
+        let target = reserve.checked_mul(RATIO_SCALE).ok_or(GhostOddsError::MathOverflow)?
+            .div_ceil(grown);
+        let others = || (0..self.outcome_count as usize).filter(move |&j| j != index);
+
+        // Product of (grown - c) / grown and (r_j - c) / r_j over the other outcomes, rounded down
+        let ratio = |c: u64| -> u128 {
+            let others_ratio = others().fold(RATIO_SCALE, |acc, j| acc * (self.reserves[j] - c) as u128 / self.reserves[j] as u128);
+            others_ratio * (grown - c as u128) / grown
+        };
+        let mut low = 0u64;
+        let mut high = others().map(|j| self.reserves[j]).min().ok_or(GhostOddsError::InvalidOutcomeIndex)?.saturating_sub(1);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if ratio(mid) >= target { low = mid; } else { high = mid - 1; }
+        }
+
+        for j in others() {
+            self.reserves[j] -= low;
+        }
+        self.reserves[index] = u64::try_from(grown - low as u128).map_err(|_| GhostOddsError::MathOverflow)?;
+        self.supplies[index] = self.supplies[index].checked_sub(amount).ok_or(GhostOddsError::MathOverflow)?;
+        Ok(low)
+    }
+
+    /// Collateral owed to outstanding outcome tokens once the market has settled: one unit per
+    /// winning token, or 1 / `outcome_count` per token of a cancelled market, rounded up.
+    fn owed(&self, status: u8) -> Result<u64> {
+        match status {
+            STATUS_RESOLVED => {
+                let winning_index = self.winning_index.ok_or(GhostOddsError::MarketNotResolved)?;
+                Ok(self.supplies[winning_index as usize])
+            }
+            STATUS_CANCELLED => {
+                let total = self.supplies[..self.outcome_count as usize].iter()
+                    .try_fold(0u64, |total, supply| total.checked_add(*supply))
+                    .ok_or(GhostOddsError::MathOverflow)?;
+                Ok(total.div_ceil(self.outcome_count as u64))
+            }
+            _ => Err(GhostOddsError::MarketNotResolved.into()),
+        }
+    }
+}
+
+/// Limit orders of a binary market (PDA: seeds = [b"order_book", market_id]). Bid collateral is
+/// escrowed in `order_escrow`, ask tokens in `yes_escrow` / `no_escrow`, all owned by the market.
+#[account]
+pub struct OrderBook {
+    pub market_id: u64,
+    pub next_order_id: u64,
+    /// Resting orders, and filled orders that found no room in `claims`.
+    pub orders: Vec<Order>,
+    /// Payouts of fully filled orders, one entry per owner, collected by `claim_order_proceeds`.
+    pub claims: Vec<OrderClaim>,
+    pub bump: u8,
+}
+impl OrderBook {
+    pub const LEN: usize = 8 + 8 + 8 + (4 + MAX_BOOK_ORDERS * Order::LEN) + (4 + MAX_BOOK_ORDERS * OrderClaim::LEN) + 1;
+
+    /// Move the payout of the order at `index` into its owner's claim once it is fully filled,
+    /// freeing its slot. The order stays on the book if the claims list is full.
+    fn retire_if_filled(&mut self, index: usize) -> Result<()> {
+        let order = self.orders[index];
+        if order.remaining > 0 {
+            return Ok(());
+        }
+        let claim_index = match self.claims.iter().position(|claim| claim.owner == order.owner) {
+            Some(claim_index) => claim_index,
+            None if self.claims.len() < MAX_BOOK_ORDERS => {
+                self.claims.push(OrderClaim { owner: order.owner, ..Default::default() });
+                self.claims.len() - 1
+            }
+            None => return Ok(()),
+        };
+        self.claims[claim_index].add(&order)?;
+        self.orders.remove(index);
+        Ok(())
+    }
+
+    /// Index of the best resting order a taker bid (`taker_is_bid`) or ask on YES (`is_yes`) or
+    /// NO can trade with at `limit_price_bps` or better: the cheapest ask or the highest bid,
+    /// oldest first on ties.
+    fn best_match(&self, is_yes: bool, taker_is_bid: bool, limit_price_bps: u16) -> Option<usize> {
+        self.orders.iter().enumerate()
+            .filter(|(_, order)| order.is_yes == is_yes && order.is_bid != taker_is_bid && order.remaining > 0)
+            .filter(|(_, order)| {
+                if taker_is_bid { order.price_bps <= limit_price_bps } else { order.price_bps >= limit_price_bps }
+            })
+            .min_by_key(|(_, order)| {
+                (if taker_is_bid { order.price_bps } else { u16::MAX - order.price_bps }, order.order_id)
+            })
+            .map(|(index, _)| index)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Order {
+    pub order_id: u64,
+    pub owner: Pubkey,
+    pub is_yes: bool,
+    /// Bids buy outcome tokens with collateral; asks sell them for collateral.
+    pub is_bid: bool,
+    /// Collateral per outcome token, in bps.
+    pub price_bps: u16,
+    /// Outcome tokens still to buy or sell.
+    pub remaining: u64,
+    /// Collateral (bid) or outcome tokens (ask) left in escrow.
+    pub escrowed: u64,
+    /// Filled outcome tokens (bid) or collateral (ask) not yet paid out to the owner.
+    pub proceeds: u64,
+    /// Escrowed tokens of an ask that placing it took off the owner's `UserPosition`.
+    pub position_debited: u64,
+}
+impl Order {
+    pub const LEN: usize = 8 + 32 + 1 + 1 + 2 + 8 + 8 + 8 + 8;
+
+    /// (collateral, outcome tokens) owed to the owner: escrow left plus proceeds.
+    fn payout(&self) -> (u64, u64) {
+        if self.is_bid { (self.escrowed, self.proceeds) } else { (self.proceeds, self.escrowed) }
+    }
+}
+
+/// What a maker is owed from fully filled orders.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OrderClaim {
+    pub owner: Pubkey,
+    pub collateral: u64,
+    pub yes_tokens: u64,
+    pub no_tokens: u64,
+}
+impl OrderClaim {
+    pub const LEN: usize = 32 + 8 + 8 + 8;
+
+    fn add(&mut self, order: &Order) -> Result<()> {
+        let (collateral, tokens) = order.payout();
+        self.collateral = self.collateral.checked_add(collateral).ok_or(GhostOddsError::MathOverflow)?;
+        let outcome_tokens = if order.is_yes { &mut self.yes_tokens } else { &mut self.no_tokens };
+        *outcome_tokens = outcome_tokens.checked_add(tokens).ok_or(GhostOddsError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// Which way the AMM price must cross a conditional order's trigger before it can execute.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConditionalKind {
+    /// Buy at or below the trigger, or sell at or above it.
+    Limit,
+    /// Sell at or below the trigger.
+    StopLoss,
+    /// Sell at or above the trigger.
+    TakeProfit,
+}
+
+/// A user's keeper-executed order on a binary market
+/// (PDA: seeds = [b"conditional_order", market_id, owner]). The collateral (buy) or outcome
+/// tokens (sell) are escrowed in `[b"conditional_escrow", market_id, owner]`, owned by the market.
 #[account]
-pub struct Market {
+pub struct ConditionalOrder {
     pub market_id: u64,
-    pub authority: Pubkey,
-    pub question: String,
-    pub description: String,
-    pub category: String,
-    pub collateral_mint: Pubkey,
-    pub yes_mint: Pubkey,
-    pub no_mint: Pubkey,
-    pub vault: Pubkey,
-    pub yes_amount: u64,
-    pub no_amount: u64,
-    pub total_liquidity: u64,
-    pub volume: u64,
-    pub resolution_source: String,
-    pub resolution_value: Option<u64>,
-    pub resolution_operator: u8,
-    pub created_at: i64,
-    pub expires_at: i64,
-    pub lock_time: i64,
-    pub resolved_at: Option<i64>,
-    pub outcome: Option<bool>,
-    pub status: u8,
-    pub fee_bps: u16,
+    pub owner: Pubkey,
+    pub kind: ConditionalKind,
+    pub is_yes: bool,
+    pub is_buy: bool,
+    /// AMM price of the outcome that triggers the order, in bps.
+    pub trigger_price_bps: u16,
+    /// Collateral to spend (buy) or outcome tokens to sell.
+    pub amount: u64,
+    /// Minimum outcome tokens (buy) or collateral (sell) to receive.
+    pub min_out: u64,
+    /// Lamports paid to the keeper that executes the order.
+    pub keeper_tip: u64,
+    pub bump: u8,
+    /// Outcome tokens of a sell taken off the owner's `UserPosition` at placement (at most
+    /// `amount`), and all that cancelling gives back to it.
+    pub position_debited: u64,
+}
+impl ConditionalOrder { pub const LEN: usize = 8 + 8 + 32 + 1 + 1 + 1 + 2 + 8 + 8 + 8 + 1 + 8; }
+
+#[account]
+pub struct UserPosition {
+    pub user: Pubkey,
+    pub market_id: u64,
+    pub yes_tokens: u64,
+    pub no_tokens: u64,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
     pub bump: u8,
 }
-impl Market {
-    pub const LEN: usize = 8 + 8 + 32
-        + (4 + MAX_QUESTION_LEN) + (4 + MAX_DESCRIPTION_LEN) + (4 + MAX_CATEGORY_LEN)
-        + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8
-        + (4 + MAX_RESOLUTION_SOURCE_LEN) + (1 + 8) + 1
-        + 8 + 8 + 8 + (1 + 8) + (1 + 1) + 1 + 2 + 1;
+impl UserPosition { pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 1; }
+
+// ============ Contexts ============
+#[derive(Accounts)]
+pub struct InitializePlatform<'info> {
+    #[account(init, payer = authority, space = Platform::LEN, seeds = [b"platform"], bump)]
+    pub platform: Account<'info, Platform>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: Treasury address
+    pub treasury: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePlatformConfig<'info> {
+    #[account(
+        mut, seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.authority == authority.key() @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(constraint = current_treasury.key() == platform.treasury @ GhostOddsError::Unauthorized)]
+    pub current_treasury: Box<Account<'info, TokenAccount>>,
+    #[account(constraint = treasury.mint == current_treasury.mint @ GhostOddsError::InvalidTreasury)]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut, seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.authority == authority.key() @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(
+        mut, seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.authority == authority.key() @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(holder: Pubkey)]
+pub struct GrantRole<'info> {
+    #[account(
+        seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.authority == authority.key() @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(
+        init_if_needed, payer = authority, space = Role::LEN,
+        seeds = [b"role", holder.as_ref()], bump,
+    )]
+    pub role: Box<Account<'info, Role>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(holder: Pubkey)]
+pub struct RevokeRole<'info> {
+    #[account(
+        seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.authority == authority.key() @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(mut, seeds = [b"role", holder.as_ref()], bump = role.bump)]
+    pub role: Box<Account<'info, Role>>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PausePlatform<'info> {
+    #[account(
+        mut, seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.can_pause(signer.key(), role.as_deref().map(|role| &**role)) @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"role", signer.key().as_ref()], bump = role.bump)]
+    pub role: Option<Box<Account<'info, Role>>>,
+}
+
+#[derive(Accounts)]
+pub struct PauseMarket<'info> {
+    #[account(
+        seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.can_pause(signer.key(), role.as_deref().map(|role| &**role)) @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    pub signer: Signer<'info>,
+    #[account(seeds = [b"role", signer.key().as_ref()], bump = role.bump)]
+    pub role: Option<Box<Account<'info, Role>>>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut, seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.pending_authority == Some(new_authority.key()) @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(question: String, description: String, category: String, resolution_source: String, resolution_value: Option<u64>, resolution_operator: u8, expires_at: i64, initial_liquidity: u64, oracle_feed_id: Option<[u8; 32]>, resolution_range: Option<PriceRange>, twap: Option<TwapConfig>, lmsr_b: Option<u64>)]
+pub struct CreateMarket<'info> {
+    #[account(
+        mut, seeds = [b"platform"], bump = platform.bump,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    /// Role of `authority`, when it lists markets as a delegated creator
+    #[account(seeds = [b"role", authority.key().as_ref()], bump = creator_role.bump)]
+    pub creator_role: Option<Box<Account<'info, Role>>>,
+    #[account(
+        init, payer = authority, space = Market::LEN,
+        seeds = [b"market", platform.market_count.to_le_bytes().as_ref()], bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// CHECK: Creator bond PDA, created via CPI for markets pending review
+    #[account(mut, seeds = [b"creator_bond", platform.market_count.to_le_bytes().as_ref()], bump)]
+    pub creator_bond_vault: Option<UncheckedAccount<'info>>,
+    /// Platform treasury, whose mint markets pending review must use as collateral
+    #[account(constraint = treasury.key() == platform.treasury @ GhostOddsError::Unauthorized)]
+    pub treasury: Option<Box<Account<'info, TokenAccount>>>,
+    /// CHECK: YES mint PDA, created via CPI
+    #[account(mut, seeds = [b"yes_mint", platform.market_count.to_le_bytes().as_ref()], bump)]
+    pub yes_mint: UncheckedAccount<'info>,
+    /// CHECK: NO mint PDA, created via CPI
+    #[account(mut, seeds = [b"no_mint", platform.market_count.to_le_bytes().as_ref()], bump)]
+    pub no_mint: UncheckedAccount<'info>,
+    pub collateral_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Vault PDA, created via CPI
+    #[account(mut, seeds = [b"vault", platform.market_count.to_le_bytes().as_ref()], bump)]
+    pub vault: UncheckedAccount<'info>,
+    /// CHECK: LP share mint PDA, created via CPI
+    #[account(mut, seeds = [b"lp_mint", platform.market_count.to_le_bytes().as_ref()], bump)]
+    pub lp_mint: UncheckedAccount<'info>,
+    /// CHECK: Creator's LP share account PDA, created via CPI
+    #[account(mut, seeds = [b"lp_tokens", platform.market_count.to_le_bytes().as_ref(), authority.key().as_ref()], bump)]
+    pub authority_lp_tokens: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = authority_collateral.mint == collateral_mint.key() @ GhostOddsError::Unauthorized,
+        constraint = authority_collateral.owner == authority.key() @ GhostOddsError::Unauthorized,
+    )]
+    pub authority_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: Legacy Pyth price account or Switchboard feed an oracle market is bound to,
+    /// validated in instruction logic
+    pub oracle_account: Option<UncheckedAccount<'info>>,
+    /// Registered symbol of the feed an oracle market is bound to
+    pub oracle_feed: Option<Box<Account<'info, OracleFeed>>>,
+}
+
+#[derive(Accounts)]
+#[instruction(feed_id: [u8; 32])]
+pub struct RegisterOracleFeed<'info> {
+    #[account(
+        seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.authority == authority.key() @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(
+        init_if_needed, payer = authority, space = OracleFeed::LEN,
+        seeds = [b"oracle_feed", feed_id.as_ref()], bump,
+    )]
+    pub oracle_feed: Box<Account<'info, OracleFeed>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReviewMarket<'info> {
+    #[account(
+        seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.authority == curator.key()
+            || curator_role.as_ref().is_some_and(|role| role.has(Role::CURATOR)) @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(seeds = [b"role", curator.key().as_ref()], bump = curator_role.bump)]
+    pub curator_role: Option<Box<Account<'info, Role>>>,
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Creator bond PDA; only initialized when `market.creator_bond` is nonzero
+    #[account(mut, seeds = [b"creator_bond", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub creator_bond_vault: UncheckedAccount<'info>,
+    #[account(mut,
+        constraint = creator_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = creator_collateral.owner == market.creator @ GhostOddsError::Unauthorized)]
+    pub creator_collateral: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Market creator, receives the rent of the closed bond account
+    #[account(mut, constraint = creator.key() == market.creator @ GhostOddsError::Unauthorized)]
+    pub creator: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = treasury.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = treasury.key() == platform.treasury @ GhostOddsError::Unauthorized,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+    pub curator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawUnreviewedMarket<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Creator bond PDA; only initialized when `market.creator_bond` is nonzero
+    #[account(mut, seeds = [b"creator_bond", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub creator_bond_vault: UncheckedAccount<'info>,
+    #[account(mut,
+        constraint = creator_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = creator_collateral.owner == market.creator @ GhostOddsError::Unauthorized)]
+    pub creator_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = creator.key() == market.creator @ GhostOddsError::Unauthorized)]
+    pub creator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BuyOutcome<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(
+        mut, seeds = [b"platform"], bump = platform.bump,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(mut, constraint = yes_mint.key() == market.yes_mint @ GhostOddsError::Unauthorized)]
+    pub yes_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = no_mint.key() == market.no_mint @ GhostOddsError::Unauthorized)]
+    pub no_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = treasury.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = treasury.key() == platform.treasury @ GhostOddsError::Unauthorized,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_yes_tokens.mint == market.yes_mint @ GhostOddsError::Unauthorized,
+        constraint = user_yes_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_yes_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_no_tokens.mint == market.no_mint @ GhostOddsError::Unauthorized,
+        constraint = user_no_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_no_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed, payer = user, space = UserPosition::LEN,
+        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()], bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    /// Order book whose resting asks `buy_outcome` fills before the AMM, with its escrows.
+    #[account(mut, seeds = [b"order_book", market.market_id.to_le_bytes().as_ref()], bump = order_book.bump)]
+    pub order_book: Option<Box<Account<'info, OrderBook>>>,
+    #[account(mut, seeds = [b"order_escrow", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub order_escrow: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut, seeds = [b"yes_escrow", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub yes_escrow: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut, seeds = [b"no_escrow", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub no_escrow: Option<Box<Account<'info, TokenAccount>>>,
+    /// Market creator's collateral account, required when the market charges a creator fee
+    #[account(mut,
+        constraint = creator_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = creator_collateral.owner == market.creator @ GhostOddsError::Unauthorized)]
+    pub creator_collateral: Option<Box<Account<'info, TokenAccount>>>,
+}
+
+#[derive(Accounts)]
+pub struct SellOutcome<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(
+        mut, seeds = [b"platform"], bump = platform.bump,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(mut, constraint = yes_mint.key() == market.yes_mint @ GhostOddsError::Unauthorized)]
+    pub yes_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = no_mint.key() == market.no_mint @ GhostOddsError::Unauthorized)]
+    pub no_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = treasury.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = treasury.key() == platform.treasury @ GhostOddsError::Unauthorized,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_yes_tokens.mint == market.yes_mint @ GhostOddsError::Unauthorized,
+        constraint = user_yes_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_yes_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_no_tokens.mint == market.no_mint @ GhostOddsError::Unauthorized,
+        constraint = user_no_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_no_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump = user_position.bump)]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    /// Market creator's collateral account, required when the market charges a creator fee
+    #[account(mut,
+        constraint = creator_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = creator_collateral.owner == market.creator @ GhostOddsError::Unauthorized)]
+    pub creator_collateral: Option<Box<Account<'info, TokenAccount>>>,
+}
+
+#[derive(Accounts)]
+pub struct MintCompleteSet<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(mut, constraint = yes_mint.key() == market.yes_mint @ GhostOddsError::Unauthorized)]
+    pub yes_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = no_mint.key() == market.no_mint @ GhostOddsError::Unauthorized)]
+    pub no_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_yes_tokens.mint == market.yes_mint @ GhostOddsError::Unauthorized,
+        constraint = user_yes_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_yes_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_no_tokens.mint == market.no_mint @ GhostOddsError::Unauthorized,
+        constraint = user_no_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_no_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed, payer = user, space = UserPosition::LEN,
+        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()], bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
-#[account]
-pub struct UserPosition {
-    pub user: Pubkey,
-    pub market_id: u64,
-    pub yes_tokens: u64,
-    pub no_tokens: u64,
-    pub total_deposited: u64,
-    pub total_withdrawn: u64,
-    pub bump: u8,
+#[derive(Accounts)]
+pub struct MergeCompleteSet<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(mut, constraint = yes_mint.key() == market.yes_mint @ GhostOddsError::Unauthorized)]
+    pub yes_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = no_mint.key() == market.no_mint @ GhostOddsError::Unauthorized)]
+    pub no_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_yes_tokens.mint == market.yes_mint @ GhostOddsError::Unauthorized,
+        constraint = user_yes_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_yes_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_no_tokens.mint == market.no_mint @ GhostOddsError::Unauthorized,
+        constraint = user_no_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_no_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump = user_position.bump)]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
-impl UserPosition { pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 1; }
 
-// ============ Contexts ============
 #[derive(Accounts)]
-pub struct InitializePlatform<'info> {
-    #[account(init, payer = authority, space = Platform::LEN, seeds = [b"platform"], bump)]
-    pub platform: Account<'info, Platform>,
+pub struct AddLiquidity<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(mut, constraint = yes_mint.key() == market.yes_mint @ GhostOddsError::Unauthorized)]
+    pub yes_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = no_mint.key() == market.no_mint @ GhostOddsError::Unauthorized)]
+    pub no_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = lp_mint.key() == market.lp_mint @ GhostOddsError::Unauthorized)]
+    pub lp_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_yes_tokens.mint == market.yes_mint @ GhostOddsError::Unauthorized,
+        constraint = user_yes_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_yes_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_no_tokens.mint == market.no_mint @ GhostOddsError::Unauthorized,
+        constraint = user_no_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_no_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_lp_tokens.mint == market.lp_mint @ GhostOddsError::Unauthorized,
+        constraint = user_lp_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_lp_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed, payer = user, space = UserPosition::LEN,
+        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()], bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
     #[account(mut)]
-    pub authority: Signer<'info>,
-    /// CHECK: Treasury address
-    pub treasury: UncheckedAccount<'info>,
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(question: String, description: String, category: String, resolution_source: String, resolution_value: Option<u64>, resolution_operator: u8, expires_at: i64, initial_liquidity: u64)]
-pub struct CreateMarket<'info> {
+pub struct RemoveLiquidity<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(mut, constraint = yes_mint.key() == market.yes_mint @ GhostOddsError::Unauthorized)]
+    pub yes_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = no_mint.key() == market.no_mint @ GhostOddsError::Unauthorized)]
+    pub no_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = lp_mint.key() == market.lp_mint @ GhostOddsError::Unauthorized)]
+    pub lp_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_yes_tokens.mint == market.yes_mint @ GhostOddsError::Unauthorized,
+        constraint = user_yes_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_yes_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_no_tokens.mint == market.no_mint @ GhostOddsError::Unauthorized,
+        constraint = user_no_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_no_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_lp_tokens.mint == market.lp_mint @ GhostOddsError::Unauthorized,
+        constraint = user_lp_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_lp_tokens: Box<Account<'info, TokenAccount>>,
     #[account(
-        mut, seeds = [b"platform"], bump = platform.bump,
-        constraint = platform.authority == authority.key() @ GhostOddsError::Unauthorized,
+        init_if_needed, payer = user, space = UserPosition::LEN,
+        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()], bump,
     )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateOrderBook<'info> {
+    #[account(seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(
+        init, payer = payer, space = OrderBook::LEN,
+        seeds = [b"order_book", market.market_id.to_le_bytes().as_ref()], bump,
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+    #[account(
+        init, payer = payer,
+        seeds = [b"order_escrow", market.market_id.to_le_bytes().as_ref()], bump,
+        token::mint = collateral_mint, token::authority = market,
+    )]
+    pub order_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init, payer = payer,
+        seeds = [b"yes_escrow", market.market_id.to_le_bytes().as_ref()], bump,
+        token::mint = yes_mint, token::authority = market,
+    )]
+    pub yes_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init, payer = payer,
+        seeds = [b"no_escrow", market.market_id.to_le_bytes().as_ref()], bump,
+        token::mint = no_mint, token::authority = market,
+    )]
+    pub no_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(constraint = collateral_mint.key() == market.collateral_mint @ GhostOddsError::Unauthorized)]
+    pub collateral_mint: Box<Account<'info, Mint>>,
+    #[account(constraint = yes_mint.key() == market.yes_mint @ GhostOddsError::Unauthorized)]
+    pub yes_mint: Box<Account<'info, Mint>>,
+    #[account(constraint = no_mint.key() == market.no_mint @ GhostOddsError::Unauthorized)]
+    pub no_mint: Box<Account<'info, Mint>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceOrder<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
     pub platform: Box<Account<'info, Platform>>,
+    #[account(mut, seeds = [b"order_book", market.market_id.to_le_bytes().as_ref()], bump = order_book.bump)]
+    pub order_book: Box<Account<'info, OrderBook>>,
+    #[account(mut, seeds = [b"order_escrow", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub order_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"yes_escrow", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub yes_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"no_escrow", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub no_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
     #[account(
-        init, payer = authority, space = Market::LEN,
-        seeds = [b"market", platform.market_count.to_le_bytes().as_ref()], bump,
+        mut,
+        constraint = treasury.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = treasury.key() == platform.treasury @ GhostOddsError::Unauthorized,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_yes_tokens.mint == market.yes_mint @ GhostOddsError::Unauthorized,
+        constraint = user_yes_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_yes_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_no_tokens.mint == market.no_mint @ GhostOddsError::Unauthorized,
+        constraint = user_no_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_no_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed, payer = user, space = UserPosition::LEN,
+        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()], bump,
     )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    /// Market creator's collateral account, required when the market charges a creator fee
+    #[account(mut,
+        constraint = creator_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = creator_collateral.owner == market.creator @ GhostOddsError::Unauthorized)]
+    pub creator_collateral: Option<Box<Account<'info, TokenAccount>>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    #[account(seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
     pub market: Box<Account<'info, Market>>,
-    /// CHECK: YES mint PDA, created via CPI
-    #[account(mut, seeds = [b"yes_mint", platform.market_count.to_le_bytes().as_ref()], bump)]
-    pub yes_mint: UncheckedAccount<'info>,
-    /// CHECK: NO mint PDA, created via CPI
-    #[account(mut, seeds = [b"no_mint", platform.market_count.to_le_bytes().as_ref()], bump)]
-    pub no_mint: UncheckedAccount<'info>,
-    pub collateral_mint: Box<Account<'info, Mint>>,
-    /// CHECK: Vault PDA, created via CPI
-    #[account(mut, seeds = [b"vault", platform.market_count.to_le_bytes().as_ref()], bump)]
-    pub vault: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"order_book", market.market_id.to_le_bytes().as_ref()], bump = order_book.bump)]
+    pub order_book: Box<Account<'info, OrderBook>>,
+    #[account(mut, seeds = [b"order_escrow", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub order_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"yes_escrow", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub yes_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"no_escrow", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub no_escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_yes_tokens.mint == market.yes_mint @ GhostOddsError::Unauthorized,
+        constraint = user_yes_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_yes_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_no_tokens.mint == market.no_mint @ GhostOddsError::Unauthorized,
+        constraint = user_no_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_no_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump = user_position.bump)]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: ConditionalKind, is_yes: bool, is_buy: bool)]
+pub struct PlaceConditionalOrder<'info> {
+    #[account(seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
     #[account(
-        mut,
-        constraint = authority_collateral.mint == collateral_mint.key() @ GhostOddsError::Unauthorized,
-        constraint = authority_collateral.owner == authority.key() @ GhostOddsError::Unauthorized,
+        init, payer = user, space = ConditionalOrder::LEN,
+        seeds = [b"conditional_order", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()], bump,
     )]
-    pub authority_collateral: Box<Account<'info, TokenAccount>>,
+    pub conditional_order: Box<Account<'info, ConditionalOrder>>,
+    #[account(
+        init, payer = user,
+        seeds = [b"conditional_escrow", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()], bump,
+        token::mint = escrow_mint, token::authority = market,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+    /// Collateral mint for buys, the outcome's mint for sells
+    #[account(constraint = escrow_mint.key() == if is_buy {
+        market.collateral_mint
+    } else if is_yes {
+        market.yes_mint
+    } else {
+        market.no_mint
+    } @ GhostOddsError::Unauthorized)]
+    pub escrow_mint: Box<Account<'info, Mint>>,
+    #[account(mut,
+        constraint = user_source.mint == escrow_mint.key() @ GhostOddsError::Unauthorized,
+        constraint = user_source.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_source: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed, payer = user, space = UserPosition::LEN,
+        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()], bump,
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub user: Signer<'info>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct BuyOutcome<'info> {
-    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+pub struct CancelConditionalOrder<'info> {
+    #[account(seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
     pub market: Box<Account<'info, Market>>,
     #[account(
-        mut, seeds = [b"platform"], bump = platform.bump,
+        mut, close = user,
+        seeds = [b"conditional_order", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump = conditional_order.bump,
     )]
+    pub conditional_order: Box<Account<'info, ConditionalOrder>>,
+    #[account(mut, seeds = [b"conditional_escrow", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()], bump)]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_destination.mint == escrow.mint @ GhostOddsError::Unauthorized,
+        constraint = user_destination.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_destination: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()],
+        bump = user_position.bump)]
+    pub user_position: Box<Account<'info, UserPosition>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteConditionalOrder<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(mut, seeds = [b"platform"], bump = platform.bump)]
     pub platform: Box<Account<'info, Platform>>,
+    #[account(
+        mut, close = owner,
+        seeds = [b"conditional_order", market.market_id.to_le_bytes().as_ref(), conditional_order.owner.as_ref()],
+        bump = conditional_order.bump,
+    )]
+    pub conditional_order: Box<Account<'info, ConditionalOrder>>,
+    #[account(mut,
+        seeds = [b"conditional_escrow", market.market_id.to_le_bytes().as_ref(), conditional_order.owner.as_ref()],
+        bump)]
+    pub escrow: Box<Account<'info, TokenAccount>>,
     #[account(mut, constraint = yes_mint.key() == market.yes_mint @ GhostOddsError::Unauthorized)]
     pub yes_mint: Box<Account<'info, Mint>>,
     #[account(mut, constraint = no_mint.key() == market.no_mint @ GhostOddsError::Unauthorized)]
@@ -660,48 +4104,184 @@ pub struct BuyOutcome<'info> {
     )]
     pub treasury: Box<Account<'info, TokenAccount>>,
     #[account(mut,
-        constraint = user_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
-        constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
-    pub user_collateral: Box<Account<'info, TokenAccount>>,
+        constraint = owner_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = owner_collateral.owner == owner.key() @ GhostOddsError::Unauthorized)]
+    pub owner_collateral: Box<Account<'info, TokenAccount>>,
     #[account(mut,
-        constraint = user_yes_tokens.mint == market.yes_mint @ GhostOddsError::Unauthorized,
-        constraint = user_yes_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
-    pub user_yes_tokens: Box<Account<'info, TokenAccount>>,
+        constraint = owner_outcome_tokens.mint == if conditional_order.is_yes {
+            market.yes_mint
+        } else {
+            market.no_mint
+        } @ GhostOddsError::Unauthorized,
+        constraint = owner_outcome_tokens.owner == owner.key() @ GhostOddsError::Unauthorized)]
+    pub owner_outcome_tokens: Box<Account<'info, TokenAccount>>,
     #[account(mut,
-        constraint = user_no_tokens.mint == market.no_mint @ GhostOddsError::Unauthorized,
-        constraint = user_no_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
-    pub user_no_tokens: Box<Account<'info, TokenAccount>>,
+        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), owner.key().as_ref()],
+        bump = owner_position.bump)]
+    pub owner_position: Box<Account<'info, UserPosition>>,
+    /// CHECK: Order owner, receives the rent of the closed order and escrow
+    #[account(mut, constraint = owner.key() == conditional_order.owner @ GhostOddsError::Unauthorized)]
+    pub owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    /// Market creator's collateral account, required when the market charges a creator fee
+    #[account(mut,
+        constraint = creator_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = creator_collateral.owner == market.creator @ GhostOddsError::Unauthorized)]
+    pub creator_collateral: Option<Box<Account<'info, TokenAccount>>>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveMarket<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
+    pub resolver: Signer<'info>,
+    #[account(seeds = [b"role", resolver.key().as_ref()], bump = resolver_role.bump)]
+    pub resolver_role: Option<Box<Account<'info, Role>>>,
+    /// CHECK: Optional oracle account (the feed bound at creation) or PriceUpdateV2,
+    /// validated in instruction logic
+    #[account(constraint = market.oracle_feed.is_none_or(|feed| feed == oracle_account.key()) @ GhostOddsError::OracleFeedMismatch)]
+    pub oracle_account: Option<UncheckedAccount<'info>>,
+    #[account(seeds = [b"price_samples", market.market_id.to_le_bytes().as_ref()], bump = price_samples.bump)]
+    pub price_samples: Option<Box<Account<'info, PriceSamples>>>,
+}
+
+#[derive(Accounts)]
+pub struct RecordPriceSample<'info> {
+    #[account(seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
     #[account(
-        init_if_needed, payer = user, space = UserPosition::LEN,
-        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()], bump,
+        init_if_needed, payer = recorder, space = PriceSamples::LEN,
+        seeds = [b"price_samples", market.market_id.to_le_bytes().as_ref()], bump,
     )]
-    pub user_position: Box<Account<'info, UserPosition>>,
+    pub price_samples: Box<Account<'info, PriceSamples>>,
+    /// CHECK: Oracle account (the feed bound at creation) or PriceUpdateV2,
+    /// validated in instruction logic
+    #[account(constraint = market.oracle_feed.is_none_or(|feed| feed == oracle_account.key()) @ GhostOddsError::OracleFeedMismatch)]
+    pub oracle_account: UncheckedAccount<'info>,
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub recorder: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeOutcome<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(
+        init, payer = proposer, space = Proposal::LEN,
+        seeds = [b"proposal", market.market_id.to_le_bytes().as_ref()], bump,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+    #[account(
+        init, payer = proposer,
+        seeds = [b"bond_vault", market.market_id.to_le_bytes().as_ref()], bump,
+        token::mint = collateral_mint, token::authority = market,
+    )]
+    pub bond_vault: Box<Account<'info, TokenAccount>>,
+    #[account(constraint = collateral_mint.key() == market.collateral_mint @ GhostOddsError::Unauthorized)]
+    pub collateral_mint: Box<Account<'info, Mint>>,
+    #[account(mut,
+        constraint = proposer_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = proposer_collateral.owner == proposer.key() @ GhostOddsError::Unauthorized)]
+    pub proposer_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct SellOutcome<'info> {
+pub struct DisputeOutcome<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(mut, seeds = [b"proposal", market.market_id.to_le_bytes().as_ref()], bump = proposal.bump)]
+    pub proposal: Box<Account<'info, Proposal>>,
+    #[account(mut, seeds = [b"bond_vault", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub bond_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = disputer_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = disputer_collateral.owner == disputer.key() @ GhostOddsError::Unauthorized)]
+    pub disputer_collateral: Box<Account<'info, TokenAccount>>,
+    pub disputer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeOutcome<'info> {
     #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
     pub market: Box<Account<'info, Market>>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
     #[account(
-        mut, seeds = [b"platform"], bump = platform.bump,
+        mut, close = proposer,
+        seeds = [b"proposal", market.market_id.to_le_bytes().as_ref()], bump = proposal.bump,
     )]
+    pub proposal: Box<Account<'info, Proposal>>,
+    #[account(mut, seeds = [b"bond_vault", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub bond_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = proposer_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = proposer_collateral.owner == proposal.proposer @ GhostOddsError::Unauthorized)]
+    pub proposer_collateral: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Proposer, receives the rent of the closed proposal and bond vault
+    #[account(mut, constraint = proposer.key() == proposal.proposer @ GhostOddsError::Unauthorized)]
+    pub proposer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SettleDispute<'info> {
+    #[account(seeds = [b"platform"], bump = platform.bump)]
     pub platform: Box<Account<'info, Platform>>,
-    #[account(mut, constraint = yes_mint.key() == market.yes_mint @ GhostOddsError::Unauthorized)]
-    pub yes_mint: Box<Account<'info, Mint>>,
-    #[account(mut, constraint = no_mint.key() == market.no_mint @ GhostOddsError::Unauthorized)]
-    pub no_mint: Box<Account<'info, Mint>>,
-    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
-    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(
+        mut, close = proposer,
+        seeds = [b"proposal", market.market_id.to_le_bytes().as_ref()], bump = proposal.bump,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+    #[account(mut, seeds = [b"bond_vault", market.market_id.to_le_bytes().as_ref()], bump)]
+    pub bond_vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = proposer_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = proposer_collateral.owner == proposal.proposer @ GhostOddsError::Unauthorized)]
+    pub proposer_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = disputer_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = Some(disputer_collateral.owner) == proposal.disputer @ GhostOddsError::Unauthorized)]
+    pub disputer_collateral: Box<Account<'info, TokenAccount>>,
     #[account(
         mut,
         constraint = treasury.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
         constraint = treasury.key() == platform.treasury @ GhostOddsError::Unauthorized,
     )]
     pub treasury: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Proposer, receives the rent of the closed proposal and bond vault
+    #[account(mut, constraint = proposer.key() == proposal.proposer @ GhostOddsError::Unauthorized)]
+    pub proposer: UncheckedAccount<'info>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [b"role", authority.key().as_ref()], bump = resolver_role.bump)]
+    pub resolver_role: Option<Box<Account<'info, Role>>>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemWinnings<'info> {
+    #[account(seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(mut, constraint = yes_mint.key() == market.yes_mint @ GhostOddsError::Unauthorized)]
+    pub yes_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = no_mint.key() == market.no_mint @ GhostOddsError::Unauthorized)]
+    pub no_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
     #[account(mut,
         constraint = user_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
         constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
@@ -724,16 +4304,56 @@ pub struct SellOutcome<'info> {
 }
 
 #[derive(Accounts)]
-pub struct ResolveMarket<'info> {
+pub struct ResolveInvalid<'info> {
     #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
     pub market: Box<Account<'info, Market>>,
-    pub resolver: Signer<'info>,
-    /// CHECK: Optional Pyth price feed account, validated in instruction logic
-    pub pyth_price_account: Option<UncheckedAccount<'info>>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [b"role", authority.key().as_ref()], bump = resolver_role.bump)]
+    pub resolver_role: Option<Box<Account<'info, Role>>>,
 }
 
 #[derive(Accounts)]
-pub struct RedeemWinnings<'info> {
+pub struct CancelMarket<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
+    pub authority: Signer<'info>,
+    #[account(seeds = [b"role", authority.key().as_ref()], bump = role.bump)]
+    pub role: Option<Box<Account<'info, Role>>>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeSchedule<'info> {
+    #[account(
+        seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.authority == authority.key() @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarket<'info> {
+    #[account(
+        seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.authority == authority.key()
+            || curator_role.as_ref().is_some_and(|role| role.has(Role::CURATOR)) @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(seeds = [b"role", authority.key().as_ref()], bump = curator_role.bump)]
+    pub curator_role: Option<Box<Account<'info, Role>>>,
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemCancelled<'info> {
     #[account(seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
     pub market: Box<Account<'info, Market>>,
     #[account(mut, constraint = yes_mint.key() == market.yes_mint @ GhostOddsError::Unauthorized)]
@@ -754,30 +4374,135 @@ pub struct RedeemWinnings<'info> {
         constraint = user_no_tokens.mint == market.no_mint @ GhostOddsError::Unauthorized,
         constraint = user_no_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
     pub user_no_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCategoricalMarket<'info> {
+    #[account(
+        mut, seeds = [b"platform"], bump = platform.bump,
+        constraint = platform.authority == authority.key()
+            || creator_role.as_ref().is_some_and(|role| role.has(Role::CREATOR)) @ GhostOddsError::Unauthorized,
+    )]
+    pub platform: Box<Account<'info, Platform>>,
+    /// Role of `authority`, when it lists markets as a delegated creator
+    #[account(seeds = [b"role", authority.key().as_ref()], bump = creator_role.bump)]
+    pub creator_role: Option<Box<Account<'info, Role>>>,
+    #[account(
+        init, payer = authority, space = Market::LEN,
+        seeds = [b"market", platform.market_count.to_le_bytes().as_ref()], bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    #[account(
+        init, payer = authority, space = OutcomePool::LEN,
+        seeds = [b"outcome_pool", platform.market_count.to_le_bytes().as_ref()], bump,
+    )]
+    pub outcome_pool: Box<Account<'info, OutcomePool>>,
+    pub collateral_mint: Box<Account<'info, Mint>>,
+    /// CHECK: Vault PDA, created via CPI
+    #[account(mut, seeds = [b"vault", platform.market_count.to_le_bytes().as_ref()], bump)]
+    pub vault: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = authority_collateral.mint == collateral_mint.key() @ GhostOddsError::Unauthorized,
+        constraint = authority_collateral.owner == authority.key() @ GhostOddsError::Unauthorized,
+    )]
+    pub authority_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(outcome_index: u8)]
+pub struct BuyCategorical<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(mut, seeds = [b"outcome_pool", market.market_id.to_le_bytes().as_ref()], bump = outcome_pool.bump)]
+    pub outcome_pool: Box<Account<'info, OutcomePool>>,
+    #[account(mut, seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(mut, seeds = [b"outcome_mint", market.market_id.to_le_bytes().as_ref(), &[outcome_index]], bump)]
+    pub outcome_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = treasury.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = treasury.key() == platform.treasury @ GhostOddsError::Unauthorized,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
     #[account(mut,
-        seeds = [b"position", market.market_id.to_le_bytes().as_ref(), user.key().as_ref()],
-        bump = user_position.bump)]
-    pub user_position: Box<Account<'info, UserPosition>>,
+        constraint = user_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_outcome_tokens.mint == outcome_mint.key() @ GhostOddsError::Unauthorized,
+        constraint = user_outcome_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_outcome_tokens: Box<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct CancelMarket<'info> {
+#[instruction(outcome_index: u8)]
+pub struct SellCategorical<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(mut, seeds = [b"outcome_pool", market.market_id.to_le_bytes().as_ref()], bump = outcome_pool.bump)]
+    pub outcome_pool: Box<Account<'info, OutcomePool>>,
+    #[account(mut, seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
+    #[account(mut, seeds = [b"outcome_mint", market.market_id.to_le_bytes().as_ref(), &[outcome_index]], bump)]
+    pub outcome_mint: Box<Account<'info, Mint>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = treasury.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = treasury.key() == platform.treasury @ GhostOddsError::Unauthorized,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = user_outcome_tokens.mint == outcome_mint.key() @ GhostOddsError::Unauthorized,
+        constraint = user_outcome_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_outcome_tokens: Box<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveCategorical<'info> {
     #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
     pub market: Box<Account<'info, Market>>,
+    #[account(mut, seeds = [b"outcome_pool", market.market_id.to_le_bytes().as_ref()], bump = outcome_pool.bump)]
+    pub outcome_pool: Box<Account<'info, OutcomePool>>,
+    #[account(seeds = [b"platform"], bump = platform.bump)]
+    pub platform: Box<Account<'info, Platform>>,
     pub authority: Signer<'info>,
+    #[account(seeds = [b"role", authority.key().as_ref()], bump = resolver_role.bump)]
+    pub resolver_role: Option<Box<Account<'info, Role>>>,
 }
 
 #[derive(Accounts)]
-pub struct RedeemCancelled<'info> {
+#[instruction(outcome_index: u8)]
+pub struct RedeemCategorical<'info> {
     #[account(seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
     pub market: Box<Account<'info, Market>>,
-    #[account(mut, constraint = yes_mint.key() == market.yes_mint @ GhostOddsError::Unauthorized)]
-    pub yes_mint: Box<Account<'info, Mint>>,
-    #[account(mut, constraint = no_mint.key() == market.no_mint @ GhostOddsError::Unauthorized)]
-    pub no_mint: Box<Account<'info, Mint>>,
+    #[account(mut, seeds = [b"outcome_pool", market.market_id.to_le_bytes().as_ref()], bump = outcome_pool.bump)]
+    pub outcome_pool: Box<Account<'info, OutcomePool>>,
+    #[account(mut, seeds = [b"outcome_mint", market.market_id.to_le_bytes().as_ref(), &[outcome_index]], bump)]
+    pub outcome_mint: Box<Account<'info, Mint>>,
     #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
     pub vault: Box<Account<'info, TokenAccount>>,
     #[account(mut,
@@ -785,24 +4510,82 @@ pub struct RedeemCancelled<'info> {
         constraint = user_collateral.owner == user.key() @ GhostOddsError::Unauthorized)]
     pub user_collateral: Box<Account<'info, TokenAccount>>,
     #[account(mut,
-        constraint = user_yes_tokens.mint == market.yes_mint @ GhostOddsError::Unauthorized,
-        constraint = user_yes_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
-    pub user_yes_tokens: Box<Account<'info, TokenAccount>>,
-    #[account(mut,
-        constraint = user_no_tokens.mint == market.no_mint @ GhostOddsError::Unauthorized,
-        constraint = user_no_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
-    pub user_no_tokens: Box<Account<'info, TokenAccount>>,
+        constraint = user_outcome_tokens.mint == outcome_mint.key() @ GhostOddsError::Unauthorized,
+        constraint = user_outcome_tokens.owner == user.key() @ GhostOddsError::Unauthorized)]
+    pub user_outcome_tokens: Box<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct WithdrawCategoricalSurplus<'info> {
+    #[account(mut, seeds = [b"market", market.market_id.to_le_bytes().as_ref()], bump = market.bump)]
+    pub market: Box<Account<'info, Market>>,
+    #[account(seeds = [b"outcome_pool", market.market_id.to_le_bytes().as_ref()], bump = outcome_pool.bump)]
+    pub outcome_pool: Box<Account<'info, OutcomePool>>,
+    #[account(mut, constraint = vault.key() == market.vault @ GhostOddsError::Unauthorized)]
+    pub vault: Box<Account<'info, TokenAccount>>,
+    #[account(mut,
+        constraint = authority_collateral.mint == market.collateral_mint @ GhostOddsError::Unauthorized,
+        constraint = authority_collateral.owner == authority.key() @ GhostOddsError::Unauthorized)]
+    pub authority_collateral: Box<Account<'info, TokenAccount>>,
+    #[account(constraint = authority.key() == market.creator @ GhostOddsError::Unauthorized)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
 // ============ Events ============
-#[event] pub struct PlatformInitialized { pub authority: Pubkey, pub fee_bps: u16, pub treasury: Pubkey }
+#[event] pub struct PlatformInitialized { pub authority: Pubkey, pub fee_bps: u16, pub lp_fee_bps: u16, pub treasury: Pubkey }
+#[event] pub struct PlatformConfigUpdated { pub authority: Pubkey, pub old_fee_bps: u16, pub old_lp_fee_bps: u16, pub old_treasury: Pubkey, pub fee_bps: u16, pub lp_fee_bps: u16, pub treasury: Pubkey, pub creator_bond: u64, pub creator_fee_bps: u16 }
+#[event] pub struct AuthorityProposed { pub authority: Pubkey, pub pending_authority: Option<Pubkey> }
+#[event] pub struct GuardianUpdated { pub authority: Pubkey, pub guardian: Option<Pubkey> }
+#[event] pub struct OracleFeedRegistered { pub feed_id: [u8; 32], pub symbol: String }
+#[event] pub struct RoleGranted { pub holder: Pubkey, pub flags: u8, pub role_flags: u8 }
+#[event] pub struct RoleRevoked { pub holder: Pubkey, pub flags: u8, pub role_flags: u8 }
+#[event] pub struct PlatformPaused { pub by: Pubkey }
+#[event] pub struct PlatformUnpaused { pub by: Pubkey }
+#[event] pub struct MarketPaused { pub market_id: u64, pub by: Pubkey }
+#[event] pub struct MarketUnpaused { pub market_id: u64, pub by: Pubkey }
+#[event] pub struct AuthorityTransferred { pub previous_authority: Pubkey, pub new_authority: Pubkey }
 #[event] pub struct MarketCreated { pub market_id: u64, pub question: String, pub expires_at: i64, pub initial_liquidity: u64 }
-#[event] pub struct OutcomePurchased { pub market_id: u64, pub user: Pubkey, pub is_yes: bool, pub amount_in: u64, pub tokens_out: u64, pub fee: u64 }
-#[event] pub struct OutcomeSold { pub market_id: u64, pub user: Pubkey, pub is_yes: bool, pub tokens_in: u64, pub collateral_out: u64, pub fee: u64 }
-#[event] pub struct MarketResolved { pub market_id: u64, pub outcome: bool, pub resolved_at: i64 }
+#[event] pub struct OutcomePurchased { pub market_id: u64, pub user: Pubkey, pub is_yes: bool, pub amount_in: u64, pub tokens_out: u64, pub fee_bps: u16, pub fee: u64, pub lp_fee: u64, pub creator_fee: u64, pub protocol_fee: u64 }
+#[event] pub struct OutcomeSold { pub market_id: u64, pub user: Pubkey, pub is_yes: bool, pub tokens_in: u64, pub collateral_out: u64, pub fee_bps: u16, pub fee: u64, pub lp_fee: u64, pub creator_fee: u64, pub protocol_fee: u64 }
+#[event] pub struct CompleteSetMinted { pub market_id: u64, pub user: Pubkey, pub amount: u64 }
+#[event] pub struct CompleteSetMerged { pub market_id: u64, pub user: Pubkey, pub amount: u64 }
+#[event] pub struct LiquidityAdded { pub market_id: u64, pub provider: Pubkey, pub amount: u64, pub shares: u64, pub yes_returned: u64, pub no_returned: u64 }
+#[event] pub struct OrderBookCreated { pub market_id: u64 }
+#[event] pub struct OrderPlaced { pub market_id: u64, pub order_id: Option<u64>, pub owner: Pubkey, pub is_yes: bool, pub is_bid: bool, pub price_bps: u16, pub quantity: u64, pub filled: u64 }
+#[event] pub struct OrderFilled { pub market_id: u64, pub order_id: u64, pub maker: Pubkey, pub taker: Pubkey, pub is_yes: bool, pub maker_is_bid: bool, pub price_bps: u16, pub quantity: u64 }
+#[event] pub struct OrderProceedsClaimed { pub market_id: u64, pub owner: Pubkey, pub collateral_out: u64, pub yes_tokens_out: u64, pub no_tokens_out: u64 }
+#[event] pub struct OrderCancelled { pub market_id: u64, pub order_id: u64, pub owner: Pubkey, pub unfilled: u64, pub collateral_out: u64, pub tokens_out: u64 }
+#[event] pub struct ConditionalOrderPlaced { pub market_id: u64, pub owner: Pubkey, pub kind: ConditionalKind, pub is_yes: bool, pub is_buy: bool, pub trigger_price_bps: u16, pub amount: u64, pub min_out: u64, pub keeper_tip: u64 }
+#[event] pub struct ConditionalOrderCancelled { pub market_id: u64, pub owner: Pubkey }
+#[event] pub struct ConditionalOrderExecuted { pub market_id: u64, pub owner: Pubkey, pub keeper: Pubkey, pub price_bps: u16, pub amount_in: u64, pub amount_out: u64, pub keeper_tip: u64 }
+#[event] pub struct FeeScheduleUpdated { pub market_id: u64, pub fee_schedule: Option<FeeSchedule> }
+#[event] pub struct MarketSubmitted { pub market_id: u64, pub creator: Pubkey, pub creator_bond: u64 }
+#[event] pub struct MarketApproved { pub market_id: u64, pub curator: Pubkey, pub bond_returned: u64 }
+#[event] pub struct MarketRejected { pub market_id: u64, pub curator: Pubkey, pub liquidity_refunded: u64, pub bond_slashed: u64 }
+#[event] pub struct UnreviewedMarketWithdrawn { pub market_id: u64, pub creator: Pubkey, pub liquidity_refunded: u64, pub bond_returned: u64 }
+#[event] pub struct MarketMetadataUpdated { pub market_id: u64, pub question: String, pub description: String, pub category: String }
+#[event] pub struct MarketExpiryExtended { pub market_id: u64, pub expires_at: i64, pub lock_time: i64 }
+#[event] pub struct CategoricalSurplusWithdrawn { pub market_id: u64, pub authority: Pubkey, pub amount: u64 }
+#[event] pub struct LiquidityRemoved { pub market_id: u64, pub provider: Pubkey, pub shares: u64, pub collateral_out: u64, pub yes_returned: u64, pub no_returned: u64 }
+#[event] pub struct MarketResolved {
+    pub market_id: u64, pub outcome: bool, pub resolved_at: i64,
+    pub settlement_price: Option<u64>, pub settlement_conf: Option<u64>, pub settlement_publish_time: Option<i64>,
+}
+#[event] pub struct PriceSampleRecorded { pub market_id: u64, pub price: u64, pub conf: u64, pub publish_time: i64, pub sample_count: u16 }
+#[event] pub struct OutcomeProposed { pub market_id: u64, pub proposer: Pubkey, pub outcome: bool, pub bond: u64, pub dispute_deadline: i64 }
+#[event] pub struct OutcomeDisputed { pub market_id: u64, pub disputer: Pubkey, pub bond: u64 }
+#[event] pub struct DisputeSettled { pub market_id: u64, pub outcome: bool, pub winner: Pubkey, pub slashed: u64 }
+#[event] pub struct MarketInvalidated { pub market_id: u64, pub yes_price_bps: u16, pub resolved_at: i64 }
+#[event] pub struct InvalidRedeemed { pub market_id: u64, pub user: Pubkey, pub yes_tokens: u64, pub no_tokens: u64, pub payout: u64 }
+#[event] pub struct ScalarRedeemed { pub market_id: u64, pub user: Pubkey, pub long_tokens: u64, pub short_tokens: u64, pub payout: u64 }
+#[event] pub struct CategoricalMarketCreated { pub market_id: u64, pub question: String, pub outcome_count: u8, pub expires_at: i64, pub initial_liquidity: u64 }
+#[event] pub struct CategoricalPurchased { pub market_id: u64, pub user: Pubkey, pub outcome_index: u8, pub amount_in: u64, pub tokens_out: u64, pub fee: u64 }
+#[event] pub struct CategoricalSold { pub market_id: u64, pub user: Pubkey, pub outcome_index: u8, pub tokens_in: u64, pub collateral_out: u64, pub fee: u64 }
+#[event] pub struct CategoricalResolved { pub market_id: u64, pub winning_index: u8, pub resolved_at: i64 }
 #[event] pub struct WinningsRedeemed { pub market_id: u64, pub user: Pubkey, pub payout: u64 }
 #[event] pub struct MarketCancelled { pub market_id: u64 }
 #[event] pub struct CancelledRedeemed { pub market_id: u64, pub user: Pubkey, pub refund: u64 }
@@ -811,6 +4594,7 @@ pub struct RedeemCancelled<'info> {
 #[error_code]
 pub enum GhostOddsError {
     #[msg("Fee exceeds maximum allowed")] FeeTooHigh,
+    #[msg("LP and creator fees cannot exceed the total fee")] InvalidFeeSplit,
     #[msg("Question exceeds maximum length")] QuestionTooLong,
     #[msg("Description exceeds maximum length")] DescriptionTooLong,
     #[msg("Category exceeds maximum length")] CategoryTooLong,
@@ -830,6 +4614,59 @@ pub enum GhostOddsError {
     #[msg("Market is not cancelled")] MarketNotCancelled,
     #[msg("Invalid oracle account")] InvalidOracle,
     #[msg("Price data is stale")] StalePriceData,
+    #[msg("Settlement price was not published within the window around expiry")] SettlementPriceOutOfWindow,
     #[msg("Price confidence interval too wide")] PriceConfidenceTooWide,
     #[msg("Oracle price account required for oracle-resolved markets")] OracleRequired,
+    #[msg("Oracle price account does not match the feed bound to this market")] OracleFeedMismatch,
+    #[msg("Resolution source does not match the market's oracle binding")] ResolutionSourceMismatch,
+    #[msg("Price update is not fully verified")] InsufficientVerification,
+    #[msg("Invalid price range for a between market")] InvalidRange,
+    #[msg("Invalid TWAP window or minimum sample count")] InvalidTwapConfig,
+    #[msg("Market does not settle on a TWAP")] TwapNotEnabled,
+    #[msg("Price was not published within the TWAP window")] OutsideTwapWindow,
+    #[msg("Price sample is not newer than the last recorded sample")] DuplicatePriceSample,
+    #[msg("Price samples account required for TWAP markets")] PriceSamplesRequired,
+    #[msg("Not enough price samples recorded to compute the TWAP")] InsufficientPriceSamples,
+    #[msg("Only manual markets can be resolved optimistically")] NotManualMarket,
+    #[msg("Authority resolution grace period has not passed")] GracePeriodActive,
+    #[msg("Market has no pending proposal")] NoPendingProposal,
+    #[msg("Dispute window has closed")] DisputeWindowClosed,
+    #[msg("Dispute window is still open")] DisputeWindowOpen,
+    #[msg("Market proposal is not disputed")] NotDisputed,
+    #[msg("Invalid settlement price must be at most 10000 bps")] InvalidSettlementPrice,
+    #[msg("Market was not resolved as invalid")] MarketNotInvalid,
+    #[msg("Instruction only supports binary markets")] NotBinaryMarket,
+    #[msg("Categorical markets need 3 to 8 outcomes, one mint each")] InvalidOutcomeCount,
+    #[msg("Outcome index out of range")] InvalidOutcomeIndex,
+    #[msg("Outcome mint does not match its PDA")] InvalidOutcomeMint,
+    #[msg("Outcome did not win")] NotWinningOutcome,
+    #[msg("Initial liquidity does not cover the LMSR maximum loss")] LmsrUnderfunded,
+    #[msg("Instruction does not support the market's pricing curve")] UnsupportedCurve,
+    #[msg("Trade exceeds the pool's liquidity")] InsufficientLiquidity,
+    #[msg("Transaction deadline has passed")] DeadlineExceeded,
+    #[msg("Price impact exceeds the allowed maximum")] PriceImpactTooHigh,
+    #[msg("Order price must be between 1 and 9999 bps")] InvalidOrderPrice,
+    #[msg("Order book is full")] OrderBookFull,
+    #[msg("Order not found")] OrderNotFound,
+    #[msg("Order book escrow accounts required")] OrderBookAccountsRequired,
+    #[msg("Stop-loss and take-profit orders must be sells")] InvalidConditionalOrder,
+    #[msg("Trigger price not reached")] TriggerNotReached,
+    #[msg("Fee schedule maximum must be between the market fee and the platform maximum")] InvalidFeeSchedule,
+    #[msg("Treasury must hold the same mint as the current treasury")] InvalidTreasury,
+    #[msg("Platform is paused")] PlatformPaused,
+    #[msg("Market is not paused")] MarketNotPaused,
+    #[msg("Unknown or empty role flags")] InvalidRoleFlags,
+    #[msg("Treasury and creator bond accounts required")] CreatorBondAccountsRequired,
+    #[msg("Market is not pending review")] MarketNotPendingReview,
+    #[msg("Creator collateral account required")] CreatorFeeAccountRequired,
+    #[msg("New expiry must be later than the current one")] ExpiryNotExtended,
+    #[msg("Market is still within its review period")] ReviewPeriodActive,
+    #[msg("Expiry can be extended by at most 30 days at a time")] ExpiryExtensionTooLong,
+    #[msg("Fee schedule raises fees too far at once")] FeeIncreaseTooLarge,
+    #[msg("Price sample recorded too soon after the previous one")] PriceSampleTooSoon,
+    #[msg("Price samples do not cover enough of the TWAP window")] InsufficientTwapCoverage,
+    #[msg("Order quantity is below the minimum")] OrderTooSmall,
+    #[msg("No filled orders to claim")] NothingToClaim,
+    #[msg("Feed symbol must be 1 to 16 bytes")] InvalidFeedSymbol,
+    #[msg("Oracle feed has no registered symbol")] OracleFeedNotRegistered,
 }
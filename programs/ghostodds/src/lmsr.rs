@@ -0,0 +1,235 @@
+use anchor_lang::prelude::*;
+
+use crate::GhostOddsError;
+
+/// Fixed-point scale (18 decimals) of the intermediate values below and of `price`.
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+/// ln(2) scaled by `WAD`.
+const LN2: u128 = 693_147_180_559_945_309;
+/// Largest exponent `exp` accepts (scaled by `WAD`), so that e^x * `WAD` fits a u128.
+const MAX_EXP_INPUT: u128 = 40 * WAD;
+
+/// e^x for `x` scaled by `WAD`, as a `WAD`-scaled value.
+fn exp(x: u128) -> Result<u128> {
+    require!(x <= MAX_EXP_INPUT, GhostOddsError::MathOverflow);
+    // e^x = 2^k * e^r with r in [0, ln 2), and e^r from its Taylor series
+    let k = x / LN2;
+    let r = x - k * LN2;
+    let mut sum = WAD;
+    let mut term = WAD;
+    let mut i = 1;
+    while term > 0 {
+        term = term * r / (i * WAD);
+        sum += term;
+        i += 1;
+    }
+    Ok(sum << k)
+}
+
+/// ln(y) for `y >= WAD` (both scaled by `WAD`).
+fn ln_at_least_one(y: u128) -> u128 {
+    // ln(y) = k * ln 2 + ln(m) with m = y / 2^k in [1, 2), and ln(m) = 2 * atanh((m - 1) / (m + 1))
+    let k = (y / WAD).ilog2();
+    let m = y >> k;
+    let z = (m - WAD) * WAD / (m + WAD);
+    let z_squared = z * z / WAD;
+    let mut sum = 0;
+    let mut term = z;
+    let mut i = 0;
+    while term > 0 {
+        sum += term / (2 * i + 1);
+        term = term * z_squared / WAD;
+        i += 1;
+    }
+    k as u128 * LN2 + 2 * sum
+}
+
+/// ln(y) for any positive `y` (both scaled by `WAD`).
+fn ln(y: u128) -> Result<i128> {
+    require!(y > 0, GhostOddsError::MathOverflow);
+    if y >= WAD {
+        Ok(ln_at_least_one(y) as i128)
+    } else {
+        Ok(-(ln_at_least_one(WAD * WAD / y) as i128))
+    }
+}
+
+/// `value / b` as a `WAD`-scaled ratio.
+fn ratio(value: u64, b: u64) -> Result<u128> {
+    (value as u128)
+        .checked_mul(WAD)
+        .ok_or(GhostOddsError::MathOverflow)?
+        .checked_div(b as u128)
+        .ok_or(GhostOddsError::MathOverflow.into())
+}
+
+/// Instantaneous price (scaled by `WAD`) of the outcome with outstanding quantity `q_self`:
+/// e^(q_self/b) / (e^(q_self/b) + e^(q_other/b)) = 1 / (1 + e^((q_other - q_self)/b)).
+pub fn price(b: u64, q_self: u64, q_other: u64) -> Result<u128> {
+    if q_other >= q_self {
+        let e = exp(ratio(q_other - q_self, b)?)?;
+        Ok(WAD * WAD / (WAD + e))
+    } else {
+        let e = exp(ratio(q_self - q_other, b)?)?;
+        Ok(WAD - WAD * WAD / (e + WAD))
+    }
+}
+
+/// Tokens of an outcome bought for `amount` collateral: the `d` for which the cost function
+/// C(q) = b * ln(e^(q_yes/b) + e^(q_no/b)) rises by exactly `amount`. With p the outcome's price,
+/// d / b = ln(e^(amount/b) - 1 + p) - ln(p). Rounded down.
+pub fn buy(b: u64, q_self: u64, q_other: u64, amount: u64) -> Result<u64> {
+    let p = price(b, q_self, q_other)?;
+    require!(p > 0, GhostOddsError::MathOverflow);
+    let grown = exp(ratio(amount, b)?)?;
+    let tokens = ln(grown - WAD + p)? - ln(p)?;
+    to_amount(tokens as u128, b)
+}
+
+/// Collateral needed to buy exactly `tokens` of an outcome, the inverse of `buy`:
+/// amount / b = ln(1 - p + p * e^(tokens/b)). Rounded up.
+pub fn buy_cost(b: u64, q_self: u64, q_other: u64, tokens: u64) -> Result<u64> {
+    let p = price(b, q_self, q_other)?;
+    let grown = exp(ratio(tokens, b)?)?;
+    // p * grown / WAD without overflowing, as grown can exceed WAD by 17 orders of magnitude
+    let scaled = (grown / WAD) * p + (grown % WAD) * p / WAD;
+    to_amount_ceil(ln_at_least_one(WAD - p + scaled), b)
+}
+
+/// Collateral released by selling `tokens` of an outcome: the drop of the cost function,
+/// amount / b = -ln(1 - p * (1 - e^(-tokens/b))). Rounded down.
+pub fn sell(b: u64, q_self: u64, q_other: u64, tokens: u64) -> Result<u64> {
+    let p = price(b, q_self, q_other)?;
+    let z = ratio(tokens, b)?;
+    let decay = if z > MAX_EXP_INPUT { 0 } else { WAD * WAD / exp(z)? };
+    let remaining = WAD - p * (WAD - decay) / WAD;
+    require!(remaining > 0, GhostOddsError::MathOverflow);
+    to_amount(ln_at_least_one(WAD * WAD / remaining), b)
+}
+
+/// Tokens of an outcome to sell for exactly `amount` collateral, the inverse of `sell`:
+/// tokens / b = -ln(1 - (1 - e^(-amount/b)) / p). Rounded up.
+pub fn sell_tokens_for(b: u64, q_self: u64, q_other: u64, amount: u64) -> Result<u64> {
+    let p = price(b, q_self, q_other)?;
+    let decay = WAD * WAD / exp(ratio(amount, b)?)?;
+    // However many tokens are sold, they release less than b * ln(1 / (1 - p))
+    require!(WAD - decay < p, GhostOddsError::InsufficientLiquidity);
+    let remaining = WAD - (WAD - decay) * WAD / p;
+    to_amount_ceil(ln_at_least_one(WAD * WAD / remaining), b)
+}
+
+/// Worst-case loss of an LMSR market maker with liquidity `b` on a binary market: b * ln(2),
+/// rounded up.
+pub fn max_loss(b: u64) -> Result<u64> {
+    let loss = (b as u128)
+        .checked_mul(LN2)
+        .ok_or(GhostOddsError::MathOverflow)?
+        .div_ceil(WAD);
+    Ok(u64::try_from(loss).map_err(|_| GhostOddsError::MathOverflow)?)
+}
+
+/// `b * value / WAD`, rounded down.
+fn to_amount(value: u128, b: u64) -> Result<u64> {
+    let amount = value
+        .checked_mul(b as u128)
+        .ok_or(GhostOddsError::MathOverflow)?
+        / WAD;
+    Ok(u64::try_from(amount).map_err(|_| GhostOddsError::MathOverflow)?)
+}
+
+/// `b * value / WAD`, rounded up.
+fn to_amount_ceil(value: u128, b: u64) -> Result<u64> {
+    let amount = value
+        .checked_mul(b as u128)
+        .ok_or(GhostOddsError::MathOverflow)?
+        .div_ceil(WAD);
+    Ok(u64::try_from(amount).map_err(|_| GhostOddsError::MathOverflow)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const B: u64 = 1_000_000_000; // 1,000 USDC
+
+    #[test]
+    fn exp_and_ln_are_accurate() {
+        // e ≈ 2.718281828459045235
+        assert!(exp(WAD).unwrap().abs_diff(2_718_281_828_459_045_235) < 1_000);
+        assert!(ln(2_718_281_828_459_045_235).unwrap().abs_diff(WAD as i128) < 1_000);
+        assert!(ln(WAD / 2).unwrap().abs_diff(-(LN2 as i128)) < 1_000);
+        assert_eq!(ln(WAD).unwrap(), 0);
+        assert!(exp(MAX_EXP_INPUT + 1).is_err());
+    }
+
+    #[test]
+    fn prices_sum_to_one() {
+        for (q_yes, q_no) in [(0, 0), (500_000_000, 0), (0, 3_000_000_000), (7_000_000, 2_000_000_000)] {
+            let sum = price(B, q_yes, q_no).unwrap() + price(B, q_no, q_yes).unwrap();
+            assert!(sum.abs_diff(WAD) <= 2);
+        }
+        assert_eq!(price(B, 0, 0).unwrap(), WAD / 2);
+    }
+
+    #[test]
+    fn buy_matches_the_cost_function() {
+        // From 50/50, spending x buys d with e^(d/b) = 2e^(x/b) - 1
+        let tokens = buy(B, 0, 0, 100_000_000).unwrap();
+        let expected = (2.0 * (0.1f64).exp() - 1.0).ln() * B as f64;
+        assert!((tokens as f64 - expected).abs() <= 1.0);
+    }
+
+    #[test]
+    fn selling_what_was_bought_never_returns_more() {
+        for amount in [1, 1_000, 100_000_000, 5_000_000_000] {
+            let tokens = buy(B, 250_000_000, 0, amount).unwrap();
+            let back = sell(B, 250_000_000 + tokens, 0, tokens).unwrap();
+            assert!(back <= amount);
+            assert!(amount - back <= 1 + amount / 1_000_000_000);
+        }
+    }
+
+    #[test]
+    fn exact_quotes_invert_buy_and_sell() {
+        for amount in [1_000, 100_000_000, 5_000_000_000] {
+            let tokens = buy(B, 250_000_000, 0, amount).unwrap();
+            let cost = buy_cost(B, 250_000_000, 0, tokens).unwrap();
+            assert!(cost <= amount && amount - cost <= 1 + amount / 1_000_000_000);
+
+            let sold = sell_tokens_for(B, 250_000_000 + tokens, 0, amount / 2).unwrap();
+            assert!(sell(B, 250_000_000 + tokens, 0, sold).unwrap() >= amount / 2);
+        }
+        assert!(sell_tokens_for(B, 100, 0, B).is_err());
+    }
+
+    #[test]
+    fn max_loss_is_b_ln_2() {
+        assert_eq!(max_loss(B).unwrap(), 693_147_181);
+    }
+
+    proptest! {
+        #[test]
+        fn payouts_never_exceed_collateral_in_plus_max_loss(
+            trades in proptest::collection::vec((any::<bool>(), any::<bool>(), 1u64..5_000_000_000), 1..20),
+        ) {
+            // Whichever outcome wins, its holders are paid 1 per token out of what traders paid
+            // in and the subsidy
+            let (mut q_yes, mut q_no, mut collateral_in) = (0u64, 0u64, 0i128);
+            for (is_yes, is_buy, amount) in trades {
+                let (q_self, q_other) = if is_yes { (q_yes, q_no) } else { (q_no, q_yes) };
+                let q_self = if is_buy {
+                    let Ok(tokens) = buy(B, q_self, q_other, amount) else { continue };
+                    collateral_in += amount as i128;
+                    q_self + tokens
+                } else {
+                    let tokens = amount.min(q_self);
+                    collateral_in -= sell(B, q_self, q_other, tokens).unwrap() as i128;
+                    q_self - tokens
+                };
+                if is_yes { q_yes = q_self } else { q_no = q_self }
+            }
+            prop_assert!(q_yes.max(q_no) as i128 <= collateral_in + max_loss(B).unwrap() as i128);
+        }
+    }
+}
@@ -0,0 +1,549 @@
+//! Swap and fee math of binary markets, free of accounts so it can be tested on the host and
+//! reused off-chain to quote trades.
+
+use anchor_lang::prelude::*;
+
+use crate::{lmsr, Curve, FeeSchedule, GhostOddsError, MAX_FEE_BPS};
+
+/// Fixed-point scale of `price_yes` (18 decimals).
+pub const PRICE_SCALE: u128 = lmsr::WAD;
+
+const BPS: u128 = 10000;
+
+/// Pricing state of a binary market (see `Market::pool`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pool {
+    pub curve: Curve,
+    pub lmsr_b: u64,
+    pub yes_amount: u64,
+    pub no_amount: u64,
+    pub fee_bps: u16,
+    pub lp_fee_bps: u16,
+    /// Part of `fee_bps` paid to the market creator, out of the protocol's share.
+    pub creator_fee_bps: u16,
+}
+
+impl Pool {
+    /// (traded side, other side) of the pool's amounts.
+    fn sides(&self, is_yes: bool) -> (u64, u64) {
+        if is_yes { (self.yes_amount, self.no_amount) } else { (self.no_amount, self.yes_amount) }
+    }
+
+    /// (yes_amount, no_amount) after a trade that left the pool at (`side`, `other`), with the LP
+    /// fee minted as complete sets into both constant-product reserves.
+    pub fn amounts_after(&self, is_yes: bool, side: u64, other: u64, lp_fee: u64) -> Result<(u64, u64)> {
+        let (side, other) = match self.curve {
+            Curve::ConstantProduct => (
+                side.checked_add(lp_fee).ok_or(GhostOddsError::MathOverflow)?,
+                other.checked_add(lp_fee).ok_or(GhostOddsError::MathOverflow)?,
+            ),
+            Curve::Lmsr => (side, other),
+        };
+        Ok(if is_yes { (side, other) } else { (other, side) })
+    }
+
+    /// Split a fee charged by this pool into its LP, creator and protocol parts.
+    pub fn split_fee(&self, fee: u64) -> Result<(u64, u64, u64)> {
+        let (lp_fee, rest) = split_fee(fee, self.fee_bps, self.lp_fee_bps)?;
+        let (creator_fee, _) = split_fee(fee, self.fee_bps, self.creator_fee_bps)?;
+        let creator_fee = creator_fee.min(rest);
+        Ok((lp_fee, creator_fee, rest - creator_fee))
+    }
+}
+
+/// Collateral paid for a buy and the outcome tokens it mints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BuyQuote {
+    /// Collateral taken from the buyer, fee included.
+    pub amount_in: u64,
+    /// Part of `amount_in` that buys tokens.
+    pub input_after_fee: u64,
+    /// Fee rate charged (`Pool::fee_bps`).
+    pub fee_bps: u16,
+    pub fee: u64,
+    pub lp_fee: u64,
+    pub creator_fee: u64,
+    pub protocol_fee: u64,
+    pub tokens_out: u64,
+    /// Pool amounts after the trade.
+    pub yes_amount: u64,
+    pub no_amount: u64,
+}
+
+/// Outcome tokens burned by a sell and the collateral it releases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SellQuote {
+    pub tokens_in: u64,
+    /// Collateral released by the curve, fee included.
+    pub collateral_before_fee: u64,
+    /// Fee rate charged (`Pool::fee_bps`).
+    pub fee_bps: u16,
+    pub fee: u64,
+    pub lp_fee: u64,
+    pub creator_fee: u64,
+    pub protocol_fee: u64,
+    /// Collateral paid to the seller.
+    pub collateral_out: u64,
+    /// Pool amounts after the trade.
+    pub yes_amount: u64,
+    pub no_amount: u64,
+}
+
+/// Trading fee on `amount` at `fee_bps`, rounded up.
+pub fn fee_for(amount: u64, fee_bps: u16) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee_bps as u128)
+        .ok_or(GhostOddsError::MathOverflow)?
+        .div_ceil(BPS);
+    Ok(u64::try_from(fee).map_err(|_| GhostOddsError::MathOverflow)?)
+}
+
+/// Split a trading fee charged at `fee_bps` into its LP portion (`lp_fee_bps` of the trade)
+/// and the protocol remainder.
+pub fn split_fee(fee: u64, fee_bps: u16, lp_fee_bps: u16) -> Result<(u64, u64)> {
+    if fee_bps == 0 {
+        return Ok((0, fee));
+    }
+    let lp_fee = ((fee as u128).checked_mul(lp_fee_bps as u128).ok_or(GhostOddsError::MathOverflow)?
+        .checked_div(fee_bps as u128).ok_or(GhostOddsError::MathOverflow)?) as u64;
+    Ok((lp_fee, fee.checked_sub(lp_fee).ok_or(GhostOddsError::MathOverflow)?))
+}
+
+/// Collateral for `tokens` outcome tokens at `price_bps` per token.
+pub fn collateral_at_price(tokens: u64, price_bps: u16, round_up: bool) -> Result<u64> {
+    let value = tokens as u128 * price_bps as u128;
+    let collateral = if round_up { value.div_ceil(BPS) } else { value / BPS };
+    Ok(u64::try_from(collateral).map_err(|_| GhostOddsError::MathOverflow)?)
+}
+
+/// Fee rate of a market with a `FeeSchedule` at `now`: `base_fee_bps` at `created_at`, rising
+/// linearly to the schedule's maximum at `lock_time`, plus `volatility_factor_bps` of the
+/// recent price move, capped at `MAX_FEE_BPS`.
+pub fn dynamic_fee_bps(
+    base_fee_bps: u16,
+    schedule: &FeeSchedule,
+    created_at: i64,
+    lock_time: i64,
+    now: i64,
+    price_move_bps: u16,
+) -> u16 {
+    let duration = (lock_time - created_at).max(1) as u128;
+    let elapsed = (now - created_at).clamp(0, duration as i64) as u128;
+    let ramp = schedule.max_fee_bps.saturating_sub(base_fee_bps) as u128 * elapsed / duration;
+    let volatility = price_move_bps as u128 * schedule.volatility_factor_bps as u128 / BPS;
+    (base_fee_bps as u128 + ramp + volatility).min(MAX_FEE_BPS as u128) as u16
+}
+
+/// Smallest amount that leaves at least `net` once `fee_for` is taken out of it.
+fn gross_for_net(net: u64, fee_bps: u16) -> Result<u64> {
+    require!((fee_bps as u128) < BPS, GhostOddsError::FeeTooHigh);
+    let gross = (net as u128)
+        .checked_mul(BPS)
+        .ok_or(GhostOddsError::MathOverflow)?
+        .div_ceil(BPS - fee_bps as u128);
+    Ok(u64::try_from(gross).map_err(|_| GhostOddsError::MathOverflow)?)
+}
+
+/// Constant-product reserve matching `reserve` on the other side: k / `reserve`, rounded up so
+/// that k never decreases.
+fn matching_reserve(k: u128, reserve: u64) -> Result<u64> {
+    require!(reserve > 0, GhostOddsError::InsufficientLiquidity);
+    u64::try_from(k.div_ceil(reserve as u128)).map_err(|_| GhostOddsError::MathOverflow.into())
+}
+
+fn product(a: u64, b: u64) -> u128 {
+    a as u128 * b as u128
+}
+
+/// Smallest collateral `x` that, minted as `x` complete sets into the pool, lets `tokens_out` of
+/// `side` be taken out while keeping k: (`side` + x - `tokens_out`) * (`other` + x) >= k.
+fn input_for_tokens(side: u64, other: u64, tokens_out: u64) -> Result<u64> {
+    let k = product(side, other);
+    let keeps_k = |x: u64| {
+        let new_side = (side as u128 + x as u128).saturating_sub(tokens_out as u128);
+        new_side * (other as u128 + x as u128) >= k
+    };
+    // Minting `tokens_out` sets and taking the bought side's share leaves `side` * (`other` + x)
+    let (mut low, mut high) = (1u64, tokens_out);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if keeps_k(mid) { high = mid; } else { low = mid + 1; }
+    }
+    Ok(low)
+}
+
+/// Largest number of complete sets that can be burned out of a pool holding (`side`, `other`)
+/// while keeping the product at least `k`: (`side` - c) * (`other` - c) >= k.
+fn sets_burnable(k: u128, side: u64, other: u64) -> u64 {
+    let keeps_k = |c: u64| product(side - c, other - c) >= k;
+    let (mut low, mut high) = (0u64, side.min(other));
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if keeps_k(mid) { low = mid; } else { high = mid - 1; }
+    }
+    low
+}
+
+/// Price of YES (scaled by `PRICE_SCALE`), i.e. the collateral a YES token pays out per unit
+/// as implied by the pool. The NO price is the complement.
+pub fn price_yes(pool: &Pool) -> Result<u128> {
+    match pool.curve {
+        Curve::ConstantProduct => {
+            let total = (pool.yes_amount as u128).checked_add(pool.no_amount as u128).ok_or(GhostOddsError::MathOverflow)?;
+            require!(total > 0, GhostOddsError::InsufficientLiquidity);
+            Ok(pool.no_amount as u128 * PRICE_SCALE / total)
+        }
+        Curve::Lmsr => lmsr::price(pool.lmsr_b, pool.yes_amount, pool.no_amount),
+    }
+}
+
+/// Relative move, in bps, of the YES (`is_yes`) or NO price from `before` to `after`.
+pub fn price_impact_bps(before: &Pool, after: &Pool, is_yes: bool) -> Result<u64> {
+    let outcome_price = |pool: &Pool| -> Result<u128> {
+        let price = price_yes(pool)?;
+        Ok(if is_yes { price } else { PRICE_SCALE - price })
+    };
+    let (from, to) = (outcome_price(before)?, outcome_price(after)?);
+    let impact = from.abs_diff(to)
+        .checked_mul(BPS)
+        .ok_or(GhostOddsError::MathOverflow)?
+        .checked_div(from)
+        .ok_or(GhostOddsError::MathOverflow)?;
+    Ok(u64::try_from(impact).unwrap_or(u64::MAX))
+}
+
+/// Spend `amount_in` collateral, fee included, on YES (`is_yes`) or NO.
+pub fn quote_buy(pool: &Pool, is_yes: bool, amount_in: u64) -> Result<BuyQuote> {
+    require!(amount_in > 0, GhostOddsError::ZeroAmount);
+    let fee = fee_for(amount_in, pool.fee_bps)?;
+    let input_after_fee = amount_in.checked_sub(fee).ok_or(GhostOddsError::MathOverflow)?;
+    require!(input_after_fee > 0, GhostOddsError::ZeroAmount);
+    let (lp_fee, creator_fee, protocol_fee) = pool.split_fee(fee)?;
+
+    let (side, other) = pool.sides(is_yes);
+    let (tokens_out, new_side, new_other) = match pool.curve {
+        // The input mints complete sets into both reserves, then the bought side is drawn back
+        // down to k: a buy sells the buyer one token of their side per unit plus the other
+        // side's tokens swapped for it
+        Curve::ConstantProduct => {
+            let grown_side = side.checked_add(input_after_fee).ok_or(GhostOddsError::MathOverflow)?;
+            let new_other = other.checked_add(input_after_fee).ok_or(GhostOddsError::MathOverflow)?;
+            let new_side = matching_reserve(product(side, other), new_other)?;
+            (grown_side.saturating_sub(new_side), new_side, new_other)
+        }
+        // Outstanding quantities: the bought side grows by the tokens sold to the buyer
+        Curve::Lmsr => {
+            let tokens_out = lmsr::buy(pool.lmsr_b, side, other, input_after_fee)?;
+            (tokens_out, side.checked_add(tokens_out).ok_or(GhostOddsError::MathOverflow)?, other)
+        }
+    };
+    require!(tokens_out > 0, GhostOddsError::ZeroAmount);
+
+    let (yes_amount, no_amount) = pool.amounts_after(is_yes, new_side, new_other, lp_fee)?;
+    Ok(BuyQuote { amount_in, input_after_fee, fee_bps: pool.fee_bps, fee, lp_fee, creator_fee, protocol_fee, tokens_out, yes_amount, no_amount })
+}
+
+/// Buy exactly `tokens_out` YES (`is_yes`) or NO for the smallest collateral amount.
+pub fn quote_buy_exact_out(pool: &Pool, is_yes: bool, tokens_out: u64) -> Result<BuyQuote> {
+    require!(tokens_out > 0, GhostOddsError::ZeroAmount);
+    let (side, other) = pool.sides(is_yes);
+    let required = match pool.curve {
+        Curve::ConstantProduct => input_for_tokens(side, other, tokens_out)?,
+        Curve::Lmsr => lmsr::buy_cost(pool.lmsr_b, side, other, tokens_out)?,
+    };
+    let amount_in = gross_for_net(required.max(1), pool.fee_bps)?;
+    let fee = fee_for(amount_in, pool.fee_bps)?;
+    let input_after_fee = amount_in.checked_sub(fee).ok_or(GhostOddsError::MathOverflow)?;
+    let (lp_fee, creator_fee, protocol_fee) = pool.split_fee(fee)?;
+
+    // Rounding can leave `input_after_fee` above `required`; the surplus stays in the pool
+    let (new_side, new_other) = match pool.curve {
+        Curve::ConstantProduct => (
+            side.checked_add(input_after_fee).ok_or(GhostOddsError::MathOverflow)?
+                .checked_sub(tokens_out).ok_or(GhostOddsError::MathOverflow)?,
+            other.checked_add(input_after_fee).ok_or(GhostOddsError::MathOverflow)?,
+        ),
+        Curve::Lmsr => (side.checked_add(tokens_out).ok_or(GhostOddsError::MathOverflow)?, other),
+    };
+    let (yes_amount, no_amount) = pool.amounts_after(is_yes, new_side, new_other, lp_fee)?;
+    Ok(BuyQuote { amount_in, input_after_fee, fee_bps: pool.fee_bps, fee, lp_fee, creator_fee, protocol_fee, tokens_out, yes_amount, no_amount })
+}
+
+/// Sell `tokens_in` YES (`is_yes`) or NO back to the pool.
+pub fn quote_sell(pool: &Pool, is_yes: bool, tokens_in: u64) -> Result<SellQuote> {
+    require!(tokens_in > 0, GhostOddsError::ZeroAmount);
+    let (side, other) = pool.sides(is_yes);
+    let (collateral_before_fee, new_side, new_other) = match pool.curve {
+        // The sold tokens join their reserve, then complete sets are burned out of both reserves
+        // for collateral as far as k allows
+        Curve::ConstantProduct => {
+            let grown_side = side.checked_add(tokens_in).ok_or(GhostOddsError::MathOverflow)?;
+            let burned = sets_burnable(product(side, other), grown_side, other);
+            (burned, grown_side - burned, other - burned)
+        }
+        // The market maker can only buy back tokens it sold
+        Curve::Lmsr => {
+            let new_side = side.checked_sub(tokens_in).ok_or(GhostOddsError::InsufficientLiquidity)?;
+            (lmsr::sell(pool.lmsr_b, side, other, tokens_in)?, new_side, other)
+        }
+    };
+
+    let fee = fee_for(collateral_before_fee, pool.fee_bps)?;
+    let collateral_out = collateral_before_fee.checked_sub(fee).ok_or(GhostOddsError::MathOverflow)?;
+    require!(collateral_out > 0, GhostOddsError::ZeroAmount);
+    let (lp_fee, creator_fee, protocol_fee) = pool.split_fee(fee)?;
+
+    let (yes_amount, no_amount) = pool.amounts_after(is_yes, new_side, new_other, lp_fee)?;
+    Ok(SellQuote { tokens_in, collateral_before_fee, fee_bps: pool.fee_bps, fee, lp_fee, creator_fee, protocol_fee, collateral_out, yes_amount, no_amount })
+}
+
+/// Sell the fewest YES (`is_yes`) or NO tokens that pay out exactly `collateral_out` after fees.
+pub fn quote_sell_exact_out(pool: &Pool, is_yes: bool, collateral_out: u64) -> Result<SellQuote> {
+    require!(collateral_out > 0, GhostOddsError::ZeroAmount);
+    let collateral_before_fee = gross_for_net(collateral_out, pool.fee_bps)?;
+    let fee = fee_for(collateral_before_fee, pool.fee_bps)?;
+    let (lp_fee, creator_fee, protocol_fee) = pool.split_fee(fee)?;
+
+    let (side, other) = pool.sides(is_yes);
+    let (tokens_in, new_side, new_other) = match pool.curve {
+        // Burning `collateral_before_fee` sets leaves `other` short by as much; the side reserve
+        // must then hold k / new_other after its own burn
+        Curve::ConstantProduct => {
+            require!(collateral_before_fee < other, GhostOddsError::InsufficientLiquidity);
+            let new_other = other - collateral_before_fee;
+            let new_side = matching_reserve(product(side, other), new_other)?;
+            let tokens_in = new_side.checked_add(collateral_before_fee).ok_or(GhostOddsError::MathOverflow)?
+                .checked_sub(side).ok_or(GhostOddsError::MathOverflow)?;
+            (tokens_in, new_side, new_other)
+        }
+        Curve::Lmsr => {
+            let tokens_in = lmsr::sell_tokens_for(pool.lmsr_b, side, other, collateral_before_fee)?;
+            (tokens_in, side.checked_sub(tokens_in).ok_or(GhostOddsError::InsufficientLiquidity)?, other)
+        }
+    };
+    require!(tokens_in > 0, GhostOddsError::ZeroAmount);
+
+    let (yes_amount, no_amount) = pool.amounts_after(is_yes, new_side, new_other, lp_fee)?;
+    Ok(SellQuote {
+        tokens_in,
+        collateral_before_fee,
+        fee_bps: pool.fee_bps,
+        fee,
+        lp_fee,
+        creator_fee,
+        protocol_fee,
+        collateral_out: collateral_before_fee - fee,
+        yes_amount,
+        no_amount,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn cp_pool(yes_amount: u64, no_amount: u64, fee_bps: u16) -> Pool {
+        Pool { curve: Curve::ConstantProduct, lmsr_b: 0, yes_amount, no_amount, fee_bps, lp_fee_bps: fee_bps / 4, creator_fee_bps: 0 }
+    }
+
+    fn lmsr_pool(yes_amount: u64, no_amount: u64) -> Pool {
+        Pool { curve: Curve::Lmsr, lmsr_b: 1_000_000_000, yes_amount, no_amount, fee_bps: 200, lp_fee_bps: 0, creator_fee_bps: 0 }
+    }
+
+    #[test]
+    fn buy_from_a_balanced_pool() {
+        let quote = quote_buy(&cp_pool(500_000, 500_000, 200), true, 100_000).unwrap();
+        assert_eq!(quote.fee, 2_000);
+        assert_eq!((quote.lp_fee, quote.protocol_fee), (500, 1_500));
+        assert_eq!(quote.input_after_fee, 98_000);
+        // 98,000 minted YES plus 500,000 - ceil(500,000^2 / 598,000) = 81,939 swapped from NO
+        assert_eq!(quote.tokens_out, 179_939);
+        assert_eq!((quote.yes_amount, quote.no_amount), (418_061 + 500, 598_000 + 500));
+    }
+
+    #[test]
+    fn execution_prices_of_both_sides_sum_to_one() {
+        // Selling both halves of complete sets releases the collateral they were minted for, less
+        // the rounding the pool keeps
+        let pool = cp_pool(500_000, 500_000, 0);
+        let yes = quote_sell(&pool, true, 100_000).unwrap();
+        let after = Pool { yes_amount: yes.yes_amount, no_amount: yes.no_amount, ..pool };
+        let no = quote_sell(&after, false, 100_000).unwrap();
+        assert_eq!(yes.collateral_out, 47_506);
+        assert_eq!(yes.collateral_out + no.collateral_out, 99_999);
+    }
+
+    #[test]
+    fn price_follows_the_reserves() {
+        assert_eq!(price_yes(&cp_pool(500_000, 500_000, 0)).unwrap(), PRICE_SCALE / 2);
+        assert_eq!(price_yes(&cp_pool(250_000, 750_000, 0)).unwrap(), PRICE_SCALE * 3 / 4);
+        assert_eq!(price_yes(&lmsr_pool(0, 0)).unwrap(), PRICE_SCALE / 2);
+        assert!(price_yes(&cp_pool(0, 0, 0)).is_err());
+    }
+
+    #[test]
+    fn price_impact_is_relative_to_the_starting_price() {
+        let pool = cp_pool(500_000, 500_000, 0);
+        let quote = quote_buy(&pool, true, 100_000).unwrap();
+        let after = Pool { yes_amount: quote.yes_amount, no_amount: quote.no_amount, ..pool };
+        // YES moves from 0.5 to 600,000 / (416,667 + 600,000) ≈ 0.5902, i.e. +18.03%
+        assert_eq!(price_impact_bps(&pool, &after, true).unwrap(), 1803);
+        // NO moves by the same amount, also from 0.5
+        assert_eq!(price_impact_bps(&pool, &after, false).unwrap(), 1803);
+        assert_eq!(price_impact_bps(&pool, &pool, true).unwrap(), 0);
+    }
+
+    #[test]
+    fn exact_out_quotes_reject_trades_beyond_the_pool() {
+        assert!(quote_sell_exact_out(&cp_pool(500_000, 500_000, 200), true, 500_000).is_err());
+        assert!(quote_sell(&lmsr_pool(1_000, 0), true, 1_001).is_err());
+    }
+
+    #[test]
+    fn lmsr_quotes_move_only_the_traded_side() {
+        let quote = quote_buy(&lmsr_pool(0, 0), false, 10_000_000).unwrap();
+        assert_eq!((quote.yes_amount, quote.no_amount), (0, quote.tokens_out));
+        let exact = quote_buy_exact_out(&lmsr_pool(0, 0), false, quote.tokens_out).unwrap();
+        assert!(exact.amount_in <= quote.amount_in);
+
+        let pool = lmsr_pool(0, quote.tokens_out);
+        let sell = quote_sell_exact_out(&pool, false, 5_000_000).unwrap();
+        assert_eq!(sell.collateral_out, 5_000_000);
+        assert!(quote_sell(&pool, false, sell.tokens_in).unwrap().collateral_out >= 5_000_000);
+    }
+
+    #[test]
+    fn creator_fee_comes_out_of_the_protocol_share() {
+        let pool = Pool { creator_fee_bps: 50, ..cp_pool(1_000_000, 1_000_000, 200) };
+        let quote = quote_buy(&pool, true, 100_000).unwrap();
+        assert_eq!((quote.fee, quote.lp_fee, quote.creator_fee, quote.protocol_fee), (2_000, 500, 500, 1_000));
+        assert_eq!(quote_buy(&cp_pool(1_000_000, 1_000_000, 200), true, 100_000).unwrap().fee, quote.fee);
+    }
+
+    #[test]
+    fn dynamic_fee_ramps_to_the_maximum_at_lock() {
+        let schedule = FeeSchedule { max_fee_bps: 600, volatility_factor_bps: 0 };
+        assert_eq!(dynamic_fee_bps(200, &schedule, 1_000, 2_000, 900, 0), 200);
+        assert_eq!(dynamic_fee_bps(200, &schedule, 1_000, 2_000, 1_500, 0), 400);
+        assert_eq!(dynamic_fee_bps(200, &schedule, 1_000, 2_000, 2_500, 0), 600);
+        let volatile = FeeSchedule { volatility_factor_bps: 5000, ..schedule };
+        assert_eq!(dynamic_fee_bps(200, &volatile, 1_000, 2_000, 1_500, 100), 450);
+        assert_eq!(dynamic_fee_bps(200, &volatile, 1_000, 2_000, 2_000, 5_000), MAX_FEE_BPS);
+    }
+
+    #[test]
+    fn order_collateral_rounds_as_requested() {
+        assert_eq!(collateral_at_price(3, 3333, false).unwrap(), 0);
+        assert_eq!(collateral_at_price(3, 3333, true).unwrap(), 1);
+        assert_eq!(collateral_at_price(10_000, 4500, true).unwrap(), 4500);
+    }
+
+    proptest! {
+        #[test]
+        fn fee_is_rounded_up(amount in 0..=u64::MAX / 2, fee_bps in 0u16..=1000) {
+            let fee = fee_for(amount, fee_bps).unwrap() as u128;
+            let exact = amount as u128 * fee_bps as u128;
+            prop_assert!(fee * BPS >= exact);
+            prop_assert!(fee * BPS < exact + BPS);
+        }
+
+        #[test]
+        fn dynamic_fee_never_decreases_over_time(
+            base in 0u16..=MAX_FEE_BPS, max in 0u16..=MAX_FEE_BPS, factor in 0u16..=20000,
+            price_move in 0u16..=10000, duration in 1i64..1_000_000_000, t1 in -10i64..1_100_000_000, dt in 0i64..1_000_000_000,
+        ) {
+            let schedule = FeeSchedule { max_fee_bps: max, volatility_factor_bps: factor };
+            let earlier = dynamic_fee_bps(base, &schedule, 0, duration, t1, price_move);
+            let later = dynamic_fee_bps(base, &schedule, 0, duration, t1 + dt, price_move);
+            prop_assert!(earlier <= later);
+            prop_assert!(earlier >= base && later <= MAX_FEE_BPS.max(base));
+        }
+
+        #[test]
+        fn fee_split_adds_up(fee in 0..=u64::MAX / 1000, fee_bps in 0u16..=1000, lp_share in 0u16..=1000) {
+            let lp_fee_bps = lp_share.min(fee_bps);
+            let (lp_fee, protocol_fee) = split_fee(fee, fee_bps, lp_fee_bps).unwrap();
+            prop_assert_eq!(lp_fee + protocol_fee, fee);
+            prop_assert!(lp_fee as u128 * fee_bps as u128 <= fee as u128 * lp_fee_bps as u128);
+        }
+
+        #[test]
+        fn gross_for_net_is_the_smallest_sufficient_amount(net in 1..=u64::MAX / 2, fee_bps in 0u16..=1000) {
+            let gross = gross_for_net(net, fee_bps).unwrap();
+            prop_assert!(gross - fee_for(gross, fee_bps).unwrap() >= net);
+            prop_assert!(gross - 1 - fee_for(gross - 1, fee_bps).unwrap() < net);
+        }
+
+        #[test]
+        fn buying_never_decreases_k(
+            yes in 1_000u64..1_000_000_000_000, no in 1_000u64..1_000_000_000_000,
+            amount in 1u64..1_000_000_000_000, fee_bps in 0u16..=1000, is_yes: bool,
+        ) {
+            let pool = cp_pool(yes, no, fee_bps);
+            if let Ok(quote) = quote_buy(&pool, is_yes, amount) {
+                prop_assert!(product(quote.yes_amount, quote.no_amount) >= product(yes, no));
+                prop_assert_eq!(quote.input_after_fee + quote.fee, amount);
+                prop_assert_eq!(quote.lp_fee + quote.protocol_fee, quote.fee);
+            }
+        }
+
+        #[test]
+        fn selling_never_decreases_k(
+            yes in 1_000u64..1_000_000_000_000, no in 1_000u64..1_000_000_000_000,
+            tokens in 1u64..1_000_000_000_000, fee_bps in 0u16..=1000, is_yes: bool,
+        ) {
+            let pool = cp_pool(yes, no, fee_bps);
+            if let Ok(quote) = quote_sell(&pool, is_yes, tokens) {
+                prop_assert!(product(quote.yes_amount, quote.no_amount) >= product(yes, no));
+                prop_assert_eq!(quote.collateral_out + quote.fee, quote.collateral_before_fee);
+            }
+        }
+
+        #[test]
+        fn exact_out_quotes_never_decrease_k(
+            yes in 1_000u64..1_000_000_000_000, no in 1_000u64..1_000_000_000_000,
+            out in 1u64..1_000_000_000_000, fee_bps in 0u16..=1000, is_yes: bool,
+        ) {
+            let pool = cp_pool(yes, no, fee_bps);
+            if let Ok(quote) = quote_buy_exact_out(&pool, is_yes, out) {
+                prop_assert_eq!(quote.tokens_out, out);
+                prop_assert!(product(quote.yes_amount, quote.no_amount) >= product(yes, no));
+                // Paying the quoted amount through `quote_buy` gets at least as many tokens
+                prop_assert!(quote_buy(&pool, is_yes, quote.amount_in).unwrap().tokens_out >= out);
+            }
+            if let Ok(quote) = quote_sell_exact_out(&pool, is_yes, out) {
+                prop_assert_eq!(quote.collateral_out, out);
+                prop_assert!(product(quote.yes_amount, quote.no_amount) >= product(yes, no));
+                prop_assert!(quote_sell(&pool, is_yes, quote.tokens_in).unwrap().collateral_out >= out);
+            }
+        }
+
+        #[test]
+        fn selling_a_minted_set_never_profits(
+            yes in 1_000u64..1_000_000_000_000, no in 1_000u64..1_000_000_000_000,
+            sets in 1u64..1_000_000_000_000, fee_bps in 0u16..=1000, yes_first: bool,
+        ) {
+            let pool = cp_pool(yes, no, fee_bps);
+            if let Ok(first) = quote_sell(&pool, yes_first, sets) {
+                let after = Pool { yes_amount: first.yes_amount, no_amount: first.no_amount, ..pool };
+                let second = quote_sell(&after, !yes_first, sets).map_or(0, |quote| quote.collateral_out);
+                prop_assert!(first.collateral_out + second <= sets);
+            }
+        }
+
+        #[test]
+        fn round_trips_never_profit(
+            yes in 1_000u64..1_000_000_000_000, no in 1_000u64..1_000_000_000_000,
+            amount in 1u64..1_000_000_000_000, fee_bps in 0u16..=1000, is_yes: bool,
+        ) {
+            let pool = cp_pool(yes, no, fee_bps);
+            if let Ok(buy) = quote_buy(&pool, is_yes, amount) {
+                let after = Pool { yes_amount: buy.yes_amount, no_amount: buy.no_amount, ..pool };
+                if let Ok(sell) = quote_sell(&after, is_yes, buy.tokens_out) {
+                    prop_assert!(sell.collateral_out <= amount);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,371 @@
+use anchor_lang::prelude::*;
+use pyth_sdk_solana::state::SolanaPriceAccount;
+
+use crate::{GhostOddsError, OracleKind, PYTH_PROGRAM_ID, PYTH_RECEIVER_PROGRAM_ID, SWITCHBOARD_PROGRAM_ID};
+
+/// Anchor discriminator of the receiver's `PriceUpdateV2` account: SHA256("account:PriceUpdateV2")[0..8].
+pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+/// Anchor discriminator of Switchboard's `PullFeedAccountData`: SHA256("account:PullFeedAccountData")[0..8].
+pub const PULL_FEED_DISCRIMINATOR: [u8; 8] = [196, 27, 108, 196, 10, 215, 219, 40];
+
+/// Byte offsets into the zero-copy `PullFeedAccountData` (after the discriminator), as laid out by
+/// `switchboard-on-demand` 0.3.8. `pull_feed_layout_matches_switchboard` checks them against a
+/// `repr(C)` mirror of that struct.
+const PULL_FEED_RESULT_VALUE_OFFSET: usize = 2256;
+const PULL_FEED_RESULT_STD_DEV_OFFSET: usize = 2272;
+const PULL_FEED_RESULT_SUBMISSION_IDX_OFFSET: usize = 2353;
+const PULL_FEED_RESULT_SLOT_OFFSET: usize = 2360;
+const PULL_FEED_SUBMISSION_TIMESTAMPS_OFFSET: usize = 2944;
+const PULL_FEED_LEN: usize = 3200;
+
+/// Switchboard results are fixed point with 18 decimals.
+const SWITCHBOARD_DECIMALS: u32 = 18;
+/// Exponent Switchboard results are rescaled to so they fit an `OraclePrice`.
+const SWITCHBOARD_EXPO: i32 = -9;
+
+/// A price read from any supported oracle account, before normalization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+/// Mirror of `pyth_solana_receiver_sdk::price_update::VerificationLevel`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+/// Mirror of `pythnet_sdk::messages::PriceFeedMessage`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+/// Mirror of `pyth_solana_receiver_sdk::price_update::PriceUpdateV2` (without the discriminator).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+/// Read the settlement price of an oracle-bound market from the backend's account format.
+pub fn read_price(kind: OracleKind, info: &AccountInfo, feed_id: &[u8; 32]) -> Result<OraclePrice> {
+    match kind {
+        OracleKind::PythLegacy => read_pyth_legacy(info, feed_id),
+        OracleKind::PythPull => read_pyth_pull(info, feed_id),
+        OracleKind::Switchboard => read_switchboard(info, feed_id),
+    }
+}
+
+/// Whether a market settling on `kind` at `expires_at` can no longer get a price published
+/// within `max_staleness` of expiry. Legacy Pyth and Switchboard feed accounts only hold their
+/// latest price, so that happens once `expires_at + max_staleness` has passed; a `PriceUpdateV2`
+/// for any publish time can still be posted, so pull-oracle markets never run out of prices.
+pub fn settlement_window_closed(kind: OracleKind, expires_at: i64, now: i64) -> Result<bool> {
+    if kind == OracleKind::PythPull {
+        return Ok(false);
+    }
+    let window_end = expires_at.checked_add(kind.max_staleness() as i64).ok_or(GhostOddsError::MathOverflow)?;
+    Ok(now > window_end)
+}
+
+/// Feed ID of a legacy Pyth v2 price account (its own address).
+pub fn pyth_legacy_feed_id(info: &AccountInfo) -> Result<[u8; 32]> {
+    require!(*info.owner == PYTH_PROGRAM_ID, GhostOddsError::InvalidOracle);
+    let price_feed = SolanaPriceAccount::account_info_to_feed(info)
+        .map_err(|_| GhostOddsError::InvalidOracle)?;
+    Ok(price_feed.id.to_bytes())
+}
+
+/// Read the latest price from a legacy Pyth v2 (push oracle) price account.
+pub fn read_pyth_legacy(info: &AccountInfo, feed_id: &[u8; 32]) -> Result<OraclePrice> {
+    require!(*info.owner == PYTH_PROGRAM_ID, GhostOddsError::InvalidOracle);
+    let price_feed = SolanaPriceAccount::account_info_to_feed(info)
+        .map_err(|_| GhostOddsError::InvalidOracle)?;
+    require!(price_feed.id.to_bytes() == *feed_id, GhostOddsError::OracleFeedMismatch);
+    let price = price_feed.get_price_unchecked();
+    Ok(OraclePrice { price: price.price, conf: price.conf, expo: price.expo, publish_time: price.publish_time })
+}
+
+/// Read a verified price from a Pyth receiver `PriceUpdateV2` (pull oracle) account.
+pub fn read_pyth_pull(info: &AccountInfo, feed_id: &[u8; 32]) -> Result<OraclePrice> {
+    require!(*info.owner == PYTH_RECEIVER_PROGRAM_ID, GhostOddsError::InvalidOracle);
+    let data = info.try_borrow_data()?;
+    price_from_update(&parse_price_update(&data)?, feed_id)
+}
+
+/// Feed ID of a Switchboard On-Demand pull feed (its own address).
+pub fn switchboard_feed_id(info: &AccountInfo) -> Result<[u8; 32]> {
+    require!(*info.owner == SWITCHBOARD_PROGRAM_ID, GhostOddsError::InvalidOracle);
+    let data = info.try_borrow_data()?;
+    parse_pull_feed(&data)?;
+    Ok(info.key().to_bytes())
+}
+
+/// Read the current result of a Switchboard On-Demand pull feed.
+pub fn read_switchboard(info: &AccountInfo, feed_id: &[u8; 32]) -> Result<OraclePrice> {
+    require!(*info.owner == SWITCHBOARD_PROGRAM_ID, GhostOddsError::InvalidOracle);
+    require!(info.key().to_bytes() == *feed_id, GhostOddsError::OracleFeedMismatch);
+    let data = info.try_borrow_data()?;
+    parse_pull_feed(&data)
+}
+
+fn parse_pull_feed(data: &[u8]) -> Result<OraclePrice> {
+    require!(
+        data.len() >= 8 + PULL_FEED_LEN && data[..8] == PULL_FEED_DISCRIMINATOR,
+        GhostOddsError::InvalidOracle
+    );
+    let feed = &data[8..];
+    let read_i128 = |offset: usize| i128::from_le_bytes(feed[offset..offset + 16].try_into().unwrap());
+    let read_i64 = |offset: usize| i64::from_le_bytes(feed[offset..offset + 8].try_into().unwrap());
+
+    // A zero slot means the feed has never produced a result
+    require!(read_i64(PULL_FEED_RESULT_SLOT_OFFSET) != 0, GhostOddsError::InvalidOracle);
+    let submission_idx = feed[PULL_FEED_RESULT_SUBMISSION_IDX_OFFSET] as usize;
+    require!(submission_idx < 32, GhostOddsError::InvalidOracle);
+
+    let scale = 10i128.pow(SWITCHBOARD_DECIMALS - SWITCHBOARD_EXPO.unsigned_abs());
+    let price = i64::try_from(read_i128(PULL_FEED_RESULT_VALUE_OFFSET) / scale)
+        .map_err(|_| GhostOddsError::MathOverflow)?;
+    let conf = u64::try_from(read_i128(PULL_FEED_RESULT_STD_DEV_OFFSET) / scale)
+        .map_err(|_| GhostOddsError::InvalidOracle)?;
+    Ok(OraclePrice {
+        price,
+        conf,
+        expo: SWITCHBOARD_EXPO,
+        publish_time: read_i64(PULL_FEED_SUBMISSION_TIMESTAMPS_OFFSET + submission_idx * 8),
+    })
+}
+
+fn parse_price_update(data: &[u8]) -> Result<PriceUpdateV2> {
+    require!(
+        data.len() >= 8 && data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR,
+        GhostOddsError::InvalidOracle
+    );
+    PriceUpdateV2::deserialize(&mut &data[8..]).map_err(|_| GhostOddsError::InvalidOracle.into())
+}
+
+fn price_from_update(update: &PriceUpdateV2, feed_id: &[u8; 32]) -> Result<OraclePrice> {
+    // Partially verified updates carry fewer guardian signatures than the Wormhole quorum
+    require!(
+        update.verification_level == VerificationLevel::Full,
+        GhostOddsError::InsufficientVerification
+    );
+    let message = &update.price_message;
+    require!(message.feed_id == *feed_id, GhostOddsError::OracleFeedMismatch);
+    Ok(OraclePrice {
+        price: message.price,
+        conf: message.conf,
+        expo: message.exponent,
+        publish_time: message.publish_time,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL_USD_FEED_ID: [u8; 32] = [0xef; 32];
+
+    fn price_update(verification_level: VerificationLevel, feed_id: [u8; 32]) -> PriceUpdateV2 {
+        PriceUpdateV2 {
+            write_authority: Pubkey::new_unique(),
+            verification_level,
+            price_message: PriceFeedMessage {
+                feed_id,
+                price: 15_000_000_000,
+                conf: 15_000_000,
+                exponent: -8,
+                publish_time: 1_700_000_000,
+                prev_publish_time: 1_699_999_999,
+                ema_price: 14_990_000_000,
+                ema_conf: 14_000_000,
+            },
+            posted_slot: 42,
+        }
+    }
+
+    fn account_data(update: &PriceUpdateV2) -> Vec<u8> {
+        let mut data = PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+        update.serialize(&mut data).unwrap();
+        data
+    }
+
+    fn read_fixture(data: &mut [u8], owner: &Pubkey) -> Result<OraclePrice> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 1_000_000;
+        let info = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+        read_pyth_pull(&info, &SOL_USD_FEED_ID)
+    }
+
+    #[test]
+    fn reads_fully_verified_update() {
+        let mut data = account_data(&price_update(VerificationLevel::Full, SOL_USD_FEED_ID));
+        let price = read_fixture(&mut data, &PYTH_RECEIVER_PROGRAM_ID).unwrap();
+        assert_eq!(
+            price,
+            OraclePrice { price: 15_000_000_000, conf: 15_000_000, expo: -8, publish_time: 1_700_000_000 }
+        );
+    }
+
+    #[test]
+    fn rejects_partially_verified_update() {
+        let update = price_update(VerificationLevel::Partial { num_signatures: 5 }, SOL_USD_FEED_ID);
+        let mut data = account_data(&update);
+        let err = read_fixture(&mut data, &PYTH_RECEIVER_PROGRAM_ID).unwrap_err();
+        assert_eq!(err, GhostOddsError::InsufficientVerification.into());
+    }
+
+    #[test]
+    fn rejects_other_feed() {
+        let mut data = account_data(&price_update(VerificationLevel::Full, [0xe6; 32]));
+        let err = read_fixture(&mut data, &PYTH_RECEIVER_PROGRAM_ID).unwrap_err();
+        assert_eq!(err, GhostOddsError::OracleFeedMismatch.into());
+    }
+
+    #[test]
+    fn rejects_wrong_owner() {
+        let mut data = account_data(&price_update(VerificationLevel::Full, SOL_USD_FEED_ID));
+        let err = read_fixture(&mut data, &Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, GhostOddsError::InvalidOracle.into());
+    }
+
+    /// Field-for-field mirror of `switchboard_on_demand::PullFeedAccountData` (0.3.8).
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct OracleSubmission {
+        oracle: Pubkey,
+        slot: u64,
+        landed_at: u64,
+        value: i128,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct CurrentResult {
+        value: i128,
+        std_dev: i128,
+        mean: i128,
+        range: i128,
+        min_value: i128,
+        max_value: i128,
+        num_samples: u8,
+        submission_idx: u8,
+        padding1: [u8; 6],
+        slot: u64,
+        min_slot: u64,
+        max_slot: u64,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct CompactResult {
+        std_dev: f32,
+        mean: f32,
+        slot: u64,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct PullFeedAccountData {
+        submissions: [OracleSubmission; 32],
+        authority: Pubkey,
+        queue: Pubkey,
+        feed_hash: [u8; 32],
+        initialized_at: i64,
+        permissions: u64,
+        max_variance: u64,
+        min_responses: u32,
+        name: [u8; 32],
+        padding1: [u8; 2],
+        historical_result_idx: u8,
+        min_sample_size: u8,
+        last_update_timestamp: i64,
+        lut_slot: u64,
+        _reserved1: [u8; 32],
+        result: CurrentResult,
+        max_staleness: u32,
+        padding2: [u8; 12],
+        historical_results: [CompactResult; 32],
+        _ebuf4: [u8; 8],
+        _ebuf3: [u8; 24],
+        submission_timestamps: [i64; 32],
+    }
+
+    #[test]
+    fn pull_feed_layout_matches_switchboard() {
+        use std::mem::{offset_of, size_of};
+        let result = offset_of!(PullFeedAccountData, result);
+        assert_eq!(result + offset_of!(CurrentResult, value), PULL_FEED_RESULT_VALUE_OFFSET);
+        assert_eq!(result + offset_of!(CurrentResult, std_dev), PULL_FEED_RESULT_STD_DEV_OFFSET);
+        assert_eq!(result + offset_of!(CurrentResult, submission_idx), PULL_FEED_RESULT_SUBMISSION_IDX_OFFSET);
+        assert_eq!(result + offset_of!(CurrentResult, slot), PULL_FEED_RESULT_SLOT_OFFSET);
+        assert_eq!(offset_of!(PullFeedAccountData, submission_timestamps), PULL_FEED_SUBMISSION_TIMESTAMPS_OFFSET);
+        assert_eq!(size_of::<PullFeedAccountData>(), PULL_FEED_LEN);
+    }
+
+    fn pull_feed_data(value: i128, std_dev: i128, timestamp: i64) -> Vec<u8> {
+        let mut data = PULL_FEED_DISCRIMINATOR.to_vec();
+        data.resize(8 + PULL_FEED_LEN, 0);
+        let feed = &mut data[8..];
+        feed[PULL_FEED_RESULT_VALUE_OFFSET..][..16].copy_from_slice(&value.to_le_bytes());
+        feed[PULL_FEED_RESULT_STD_DEV_OFFSET..][..16].copy_from_slice(&std_dev.to_le_bytes());
+        feed[PULL_FEED_RESULT_SUBMISSION_IDX_OFFSET] = 3;
+        feed[PULL_FEED_RESULT_SLOT_OFFSET..][..8].copy_from_slice(&42u64.to_le_bytes());
+        feed[PULL_FEED_SUBMISSION_TIMESTAMPS_OFFSET + 3 * 8..][..8].copy_from_slice(&timestamp.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn reads_switchboard_result() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 1_000_000;
+        let mut data = pull_feed_data(150_250_000_000_000_000_000, 75_000_000_000_000_000, 1_700_000_000);
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &SWITCHBOARD_PROGRAM_ID, false, 0);
+        assert_eq!(
+            read_switchboard(&info, &key.to_bytes()).unwrap(),
+            OraclePrice { price: 150_250_000_000, conf: 75_000_000, expo: -9, publish_time: 1_700_000_000 }
+        );
+        let err = read_switchboard(&info, &Pubkey::new_unique().to_bytes()).unwrap_err();
+        assert_eq!(err, GhostOddsError::OracleFeedMismatch.into());
+    }
+
+    #[test]
+    fn rejects_switchboard_feed_without_result() {
+        let mut data = pull_feed_data(150_250_000_000_000_000_000, 0, 1_700_000_000);
+        data[8 + PULL_FEED_RESULT_SLOT_OFFSET..][..8].fill(0);
+        assert_eq!(parse_pull_feed(&data).unwrap_err(), GhostOddsError::InvalidOracle.into());
+    }
+
+    #[test]
+    fn closes_settlement_window_of_latest_price_feeds() {
+        let expires_at = 1_700_000_000;
+        assert!(!settlement_window_closed(OracleKind::PythLegacy, expires_at, expires_at + 300).unwrap());
+        assert!(settlement_window_closed(OracleKind::PythLegacy, expires_at, expires_at + 301).unwrap());
+        assert!(!settlement_window_closed(OracleKind::Switchboard, expires_at, expires_at + 600).unwrap());
+        assert!(settlement_window_closed(OracleKind::Switchboard, expires_at, expires_at + 601).unwrap());
+        assert!(!settlement_window_closed(OracleKind::PythPull, expires_at, expires_at + 86_400).unwrap());
+    }
+
+    #[test]
+    fn rejects_wrong_discriminator() {
+        let mut data = account_data(&price_update(VerificationLevel::Full, SOL_USD_FEED_ID));
+        data[0] ^= 0xff;
+        let err = read_fixture(&mut data, &PYTH_RECEIVER_PROGRAM_ID).unwrap_err();
+        assert_eq!(err, GhostOddsError::InvalidOracle.into());
+    }
+}
@@ -44,10 +44,18 @@ describe("ghostodds", () => {
 
   const user = Keypair.generate();
   const FEE_BPS = 200;
+  const LP_FEE_BPS = 50;
   const INITIAL_LIQUIDITY = 1_000_000;
 
+  // Oracle feed accounts loaded from tests/fixtures (see Anchor.toml)
+  const SOL_USD_FEED = new PublicKey("J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix");
+  const BTC_USD_FEED = new PublicKey("HovQMDrbAgAYPCmHVSrezcSmkMtXSSUsLDFANExrZh2J");
+  const SWITCHBOARD_SOL_USD_FEED = new PublicKey("3Szu5JUiCrQyL9P7qW8xtrxtRaQYyQmCgEeW42YHVi1R");
+  // Pyth SOL/USD feed ID, carried by every PriceUpdateV2 posted for this feed
+  const SOL_USD_FEED_ID = Buffer.from("ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d", "hex");
+
   // Shared market 0 accounts
-  let m0: { market: PublicKey; yesMint: PublicKey; noMint: PublicKey; vault: PublicKey };
+  let m0: ReturnType<typeof deriveMarketPdas>;
   let userCollateral0: PublicKey;
   let userYes0: PublicKey;
   let userNo0: PublicKey;
@@ -60,7 +68,10 @@ describe("ghostodds", () => {
     const [yesMint] = PublicKey.findProgramAddressSync([Buffer.from("yes_mint"), buf], program.programId);
     const [noMint] = PublicKey.findProgramAddressSync([Buffer.from("no_mint"), buf], program.programId);
     const [vault] = PublicKey.findProgramAddressSync([Buffer.from("vault"), buf], program.programId);
-    return { market, yesMint, noMint, vault };
+    const [lpMint] = PublicKey.findProgramAddressSync([Buffer.from("lp_mint"), buf], program.programId);
+    const [authorityLpTokens] = PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_tokens"), buf, authority.publicKey.toBuffer()], program.programId);
+    return { market, yesMint, noMint, vault, lpMint, authorityLpTokens };
   }
 
   function derivePositionPda(marketId: number, userKey: PublicKey) {
@@ -71,6 +82,11 @@ describe("ghostodds", () => {
     return pda;
   }
 
+  function oracleFeedPda(feedId: Buffer) {
+    const [pda] = PublicKey.findProgramAddressSync([Buffer.from("oracle_feed"), feedId], program.programId);
+    return pda;
+  }
+
   const getExpiry = () => new anchor.BN(Math.floor(Date.now() / 1000) + 48 * 3600);
 
   before(async () => {
@@ -94,7 +110,7 @@ describe("ghostodds", () => {
 
   describe("1. Platform Initialization", () => {
     it("initializes the platform", async () => {
-      await program.methods.initializePlatform(FEE_BPS)
+      await program.methods.initializePlatform(FEE_BPS, LP_FEE_BPS)
         .accounts({
           platform: platformPda, authority: authority.publicKey,
           treasury: treasuryTokenAccount, systemProgram: SystemProgram.programId,
@@ -104,12 +120,13 @@ describe("ghostodds", () => {
       expect(p.authority.toString()).to.equal(authority.publicKey.toString());
       expect(p.marketCount.toNumber()).to.equal(0);
       expect(p.feeBps).to.equal(FEE_BPS);
+      expect(p.lpFeeBps).to.equal(LP_FEE_BPS);
       expect(p.treasury.toString()).to.equal(treasuryTokenAccount.toString());
     });
 
     it("rejects duplicate init", async () => {
       try {
-        await program.methods.initializePlatform(FEE_BPS)
+        await program.methods.initializePlatform(FEE_BPS, LP_FEE_BPS)
           .accounts({
             platform: platformPda, authority: authority.publicKey,
             treasury: Keypair.generate().publicKey, systemProgram: SystemProgram.programId,
@@ -117,6 +134,36 @@ describe("ghostodds", () => {
         expect.fail("Should throw");
       } catch (err) { /* expected */ }
     });
+
+    it("registers the symbols of the oracle feeds markets use", async () => {
+      for (const [feedId, symbol] of [
+        [SOL_USD_FEED.toBuffer(), "SOL/USD"],
+        [BTC_USD_FEED.toBuffer(), "BTC/USD"],
+        [SWITCHBOARD_SOL_USD_FEED.toBuffer(), "SOL/USD"],
+        [SOL_USD_FEED_ID, "SOL/USD"],
+      ] as [Buffer, string][]) {
+        await program.methods.registerOracleFeed(Array.from(feedId), symbol)
+          .accounts({
+            platform: platformPda, oracleFeed: oracleFeedPda(feedId), authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          }).rpc();
+      }
+      const feed = await program.account.oracleFeed.fetch(oracleFeedPda(BTC_USD_FEED.toBuffer()));
+      expect(feed.symbol).to.equal("BTC/USD");
+    });
+
+    it("only lets the platform authority register feeds", async () => {
+      try {
+        await program.methods.registerOracleFeed(Array.from(SOL_USD_FEED.toBytes()), "BTC/USD")
+          .accounts({
+            platform: platformPda, oracleFeed: oracleFeedPda(SOL_USD_FEED.toBuffer()), authority: user.publicKey,
+            systemProgram: SystemProgram.programId,
+          }).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("Unauthorized");
+      }
+    });
   });
 
   describe("2. Market Creation", () => {
@@ -125,20 +172,25 @@ describe("ghostodds", () => {
       await program.methods.createMarket(
         "Will SOL reach $200?", "SOL price prediction", "crypto", "pyth:SOL/USD",
         new anchor.BN(200_000_000), 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY),
+        Array.from(SOL_USD_FEED.toBytes()), null, null, null,
       ).accounts({
         platform: platformPda, market: m0.market, yesMint: m0.yesMint,
-        noMint: m0.noMint, collateralMint, vault: m0.vault,
-        authorityCollateral, authority: authority.publicKey,
+        noMint: m0.noMint, collateralMint, vault: m0.vault, lpMint: m0.lpMint,
+        authorityCollateral, authorityLpTokens: m0.authorityLpTokens, authority: authority.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
-        rent: SYSVAR_RENT_PUBKEY,
+        rent: SYSVAR_RENT_PUBKEY, oracleAccount: SOL_USD_FEED, oracleFeed: oracleFeedPda(SOL_USD_FEED.toBuffer()),
       }).rpc();
 
       const m = await program.account.market.fetch(m0.market);
       expect(m.marketId.toNumber()).to.equal(0);
       expect(m.question).to.equal("Will SOL reach $200?");
       expect(m.status).to.equal(0);
-      expect(m.yesAmount.toNumber()).to.equal(INITIAL_LIQUIDITY / 2);
-      expect(m.noAmount.toNumber()).to.equal(INITIAL_LIQUIDITY / 2);
+      expect(m.yesAmount.toNumber()).to.equal(INITIAL_LIQUIDITY);
+      expect(m.noAmount.toNumber()).to.equal(INITIAL_LIQUIDITY);
+      expect(m.oracleKind).to.deep.equal({ pythLegacy: {} });
+      expect(m.oracleFeed.toString()).to.equal(SOL_USD_FEED.toString());
+      expect(m.settlementPrice).to.be.null;
+      expect(m.settlementPublishTime).to.be.null;
 
       // Setup user accounts for market 0
       userPos0 = derivePositionPda(0, user.publicKey);
@@ -147,13 +199,70 @@ describe("ghostodds", () => {
       userYes0 = await createRawTokenAccount(provider.connection, authority.payer, m0.yesMint, user.publicKey);
       userNo0 = await createRawTokenAccount(provider.connection, authority.payer, m0.noMint, user.publicKey);
     });
+
+    it("rejects an oracle market without a price feed", async () => {
+      const m = deriveMarketPdas(1);
+      try {
+        await program.methods.createMarket(
+          "Will SOL reach $300?", "SOL price prediction", "crypto", "pyth:SOL/USD",
+          new anchor.BN(300_000_000), 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, null, null, null,
+        ).accounts({
+          platform: platformPda, market: m.market, yesMint: m.yesMint,
+          noMint: m.noMint, collateralMint, vault: m.vault, lpMint: m.lpMint,
+          authorityCollateral, authorityLpTokens: m.authorityLpTokens, authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY, oracleAccount: null,
+        }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("OracleRequired");
+      }
+    });
+
+    it("rejects a resolution source that disagrees with the feed binding", async () => {
+      const m = deriveMarketPdas(1);
+      try {
+        await program.methods.createMarket(
+          "Will SOL reach $300?", "SOL price prediction", "crypto", "CoinGecko",
+          new anchor.BN(300_000_000), 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, null, null, null,
+        ).accounts({
+          platform: platformPda, market: m.market, yesMint: m.yesMint,
+          noMint: m.noMint, collateralMint, vault: m.vault, lpMint: m.lpMint,
+          authorityCollateral, authorityLpTokens: m.authorityLpTokens, authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY, oracleAccount: SOL_USD_FEED, oracleFeed: oracleFeedPda(SOL_USD_FEED.toBuffer()),
+        }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("ResolutionSourceMismatch");
+      }
+    });
+
+    it("rejects a resolution source naming another feed's symbol", async () => {
+      const m = deriveMarketPdas(1);
+      try {
+        await program.methods.createMarket(
+          "Will BTC reach $300?", "BTC price prediction", "crypto", "Pyth: BTC/USD",
+          new anchor.BN(300_000_000), 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, null, null, null,
+        ).accounts({
+          platform: platformPda, market: m.market, yesMint: m.yesMint,
+          noMint: m.noMint, collateralMint, vault: m.vault, lpMint: m.lpMint,
+          authorityCollateral, authorityLpTokens: m.authorityLpTokens, authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY, oracleAccount: SOL_USD_FEED, oracleFeed: oracleFeedPda(SOL_USD_FEED.toBuffer()),
+        }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("ResolutionSourceMismatch");
+      }
+    });
   });
 
   describe("3. Buy YES tokens", () => {
     it("buys YES tokens and fees reach treasury", async () => {
       const treasuryBefore = await getAccount(provider.connection, treasuryTokenAccount);
 
-      await program.methods.buyOutcome(new anchor.BN(100_000), true, new anchor.BN(0))
+      await program.methods.buyOutcome(new anchor.BN(100_000), true, new anchor.BN(0), null, null)
         .accounts({
           market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint,
           vault: m0.vault, treasury: treasuryTokenAccount,
@@ -165,17 +274,17 @@ describe("ghostodds", () => {
       const ya = await getAccount(provider.connection, userYes0);
       expect(Number(ya.amount)).to.be.greaterThan(0);
 
-      // Verify fee reached treasury
+      // Verify the protocol share of the fee reached treasury
       const treasuryAfter = await getAccount(provider.connection, treasuryTokenAccount);
       const feeReceived = Number(treasuryAfter.amount) - Number(treasuryBefore.amount);
-      // Fee = ceil(100_000 * 200 / 10000) = 2000
-      expect(feeReceived).to.equal(2000);
+      // Fee = ceil(100_000 * 200 / 10000) = 2000, of which 2000 * 50 / 200 = 500 stays with LPs
+      expect(feeReceived).to.equal(1500);
     });
   });
 
   describe("4. Buy NO tokens", () => {
     it("buys NO tokens", async () => {
-      await program.methods.buyOutcome(new anchor.BN(100_000), false, new anchor.BN(0))
+      await program.methods.buyOutcome(new anchor.BN(100_000), false, new anchor.BN(0), null, null)
         .accounts({
           market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint,
           vault: m0.vault, treasury: treasuryTokenAccount,
@@ -196,7 +305,7 @@ describe("ghostodds", () => {
       const treasuryBefore = await getAccount(provider.connection, treasuryTokenAccount);
       const sellAmount = new anchor.BN(Math.floor(Number(yesBefore.amount) / 2));
 
-      await program.methods.sellOutcome(sellAmount, true, new anchor.BN(0))
+      await program.methods.sellOutcome(sellAmount, true, new anchor.BN(0), null, null)
         .accounts({
           market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint,
           vault: m0.vault, treasury: treasuryTokenAccount,
@@ -218,7 +327,7 @@ describe("ghostodds", () => {
       const noBefore = await getAccount(provider.connection, userNo0);
       const sellAmount = new anchor.BN(Math.floor(Number(noBefore.amount) / 2));
 
-      await program.methods.sellOutcome(sellAmount, false, new anchor.BN(0))
+      await program.methods.sellOutcome(sellAmount, false, new anchor.BN(0), null, null)
         .accounts({
           market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint,
           vault: m0.vault, treasury: treasuryTokenAccount,
@@ -243,7 +352,7 @@ describe("ghostodds", () => {
     it("rejects resolution before expiry", async () => {
       try {
         await program.methods.resolveMarket(true)
-          .accounts({ market: m0.market, resolver: authority.publicKey, pythPriceAccount: null }).rpc();
+          .accounts({ market: m0.market, resolver: authority.publicKey, oracleAccount: null, priceSamples: null }).rpc();
         expect.fail("Should throw");
       } catch (err: any) {
         expect(err.toString()).to.include("MarketNotExpired");
@@ -255,7 +364,7 @@ describe("ghostodds", () => {
     it("rejects resolution by non-authority", async () => {
       try {
         await program.methods.resolveMarket(true)
-          .accounts({ market: m0.market, resolver: user.publicKey, pythPriceAccount: null }).signers([user]).rpc();
+          .accounts({ market: m0.market, resolver: user.publicKey, oracleAccount: null, priceSamples: null }).signers([user]).rpc();
         expect.fail("Should throw");
       } catch (err: any) {
         expect(err.toString()).to.include("Error");
@@ -263,6 +372,18 @@ describe("ghostodds", () => {
     });
   });
 
+  describe("9b. Resolution rejects a feed other than the bound one", () => {
+    it("rejects the BTC feed for a SOL market", async () => {
+      try {
+        await program.methods.resolveMarket(true)
+          .accounts({ market: m0.market, resolver: authority.publicKey, oracleAccount: BTC_USD_FEED, priceSamples: null }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("OracleFeedMismatch");
+      }
+    });
+  });
+
   describe("10. Cancel market", () => {
     let m1: ReturnType<typeof deriveMarketPdas>;
 
@@ -270,13 +391,13 @@ describe("ghostodds", () => {
       m1 = deriveMarketPdas(1);
       await program.methods.createMarket(
         "Will BTC hit 100k?", "BTC prediction", "crypto", "pyth:BTC/USD",
-        new anchor.BN(100_000_000_000), 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY),
+        new anchor.BN(100_000_000_000), 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, null, null, null,
       ).accounts({
         platform: platformPda, market: m1.market, yesMint: m1.yesMint,
-        noMint: m1.noMint, collateralMint, vault: m1.vault,
-        authorityCollateral, authority: authority.publicKey,
+        noMint: m1.noMint, collateralMint, vault: m1.vault, lpMint: m1.lpMint,
+        authorityCollateral, authorityLpTokens: m1.authorityLpTokens, authority: authority.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
-        rent: SYSVAR_RENT_PUBKEY,
+        rent: SYSVAR_RENT_PUBKEY, oracleAccount: BTC_USD_FEED, oracleFeed: oracleFeedPda(BTC_USD_FEED.toBuffer()),
       }).rpc();
     });
 
@@ -292,7 +413,7 @@ describe("ghostodds", () => {
     it("rejects cancel by non-authority", async () => {
       try {
         await program.methods.cancelMarket()
-          .accounts({ market: m0.market, authority: user.publicKey }).signers([user]).rpc();
+          .accounts({ platform: platformPda, market: m0.market, authority: user.publicKey }).signers([user]).rpc();
         expect.fail("Should throw");
       } catch (err: any) {
         expect(err.toString()).to.include("Unauthorized");
@@ -316,7 +437,7 @@ describe("ghostodds", () => {
   describe("13. Zero amount rejected", () => {
     it("rejects buy with zero amount", async () => {
       try {
-        await program.methods.buyOutcome(new anchor.BN(0), true, new anchor.BN(0))
+        await program.methods.buyOutcome(new anchor.BN(0), true, new anchor.BN(0), null, null)
           .accounts({
             market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint,
             vault: m0.vault, treasury: treasuryTokenAccount,
@@ -334,7 +455,7 @@ describe("ghostodds", () => {
   describe("14. Slippage protection", () => {
     it("rejects excessive slippage", async () => {
       try {
-        await program.methods.buyOutcome(new anchor.BN(1000), true, new anchor.BN(999_999_999))
+        await program.methods.buyOutcome(new anchor.BN(1000), true, new anchor.BN(999_999_999), null, null)
           .accounts({
             market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint,
             vault: m0.vault, treasury: treasuryTokenAccount,
@@ -355,4 +476,1422 @@ describe("ghostodds", () => {
       expect(p.totalVolume.toNumber()).to.be.greaterThan(0);
     });
   });
+
+  describe("16. Pyth pull-oracle market", () => {
+    it("binds the market to a feed ID without a price account", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const marketId = p.marketCount.toNumber();
+      const m = deriveMarketPdas(marketId);
+      await program.methods.createMarket(
+        "Will SOL reach $250?", "SOL price prediction", "crypto", "pyth:SOL/USD",
+        new anchor.BN(250_000_000), 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY),
+        Array.from(SOL_USD_FEED_ID), null, null, null,
+      ).accounts({
+        platform: platformPda, market: m.market, yesMint: m.yesMint,
+        noMint: m.noMint, collateralMint, vault: m.vault, lpMint: m.lpMint,
+        authorityCollateral, authorityLpTokens: m.authorityLpTokens, authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY, oracleAccount: null, oracleFeed: oracleFeedPda(SOL_USD_FEED_ID),
+      }).rpc();
+
+      const market = await program.account.market.fetch(m.market);
+      expect(market.oracleKind).to.deep.equal({ pythPull: {} });
+      expect(market.oracleFeed).to.be.null;
+      expect(Buffer.from(market.oracleFeedId).equals(SOL_USD_FEED_ID)).to.be.true;
+    });
+  });
+
+  describe("17. Between (range) market", () => {
+    const range = (lower: number, upper: number) => ({
+      lower: new anchor.BN(lower), upper: new anchor.BN(upper), lowerInclusive: true, upperInclusive: false,
+    });
+
+    it("stores the range bounds", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const m = deriveMarketPdas(p.marketCount.toNumber());
+      await program.methods.createMarket(
+        "Will SOL close between $150 and $200?", "SOL price range", "crypto", "pyth:SOL/USD",
+        new anchor.BN(150_000_000), 2, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null,
+        range(150_000_000, 200_000_000), null, null,
+      ).accounts({
+        platform: platformPda, market: m.market, yesMint: m.yesMint,
+        noMint: m.noMint, collateralMint, vault: m.vault, lpMint: m.lpMint,
+        authorityCollateral, authorityLpTokens: m.authorityLpTokens, authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY, oracleAccount: SOL_USD_FEED, oracleFeed: oracleFeedPda(SOL_USD_FEED.toBuffer()),
+      }).rpc();
+
+      const market = await program.account.market.fetch(m.market);
+      expect(market.resolutionOperator).to.equal(2);
+      expect(market.resolutionRange.lower.toNumber()).to.equal(150_000_000);
+      expect(market.resolutionRange.upper.toNumber()).to.equal(200_000_000);
+      expect(market.resolutionRange.lowerInclusive).to.be.true;
+      expect(market.resolutionRange.upperInclusive).to.be.false;
+    });
+
+    it("rejects an inverted range", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const m = deriveMarketPdas(p.marketCount.toNumber());
+      try {
+        await program.methods.createMarket(
+          "Will SOL close between $200 and $150?", "SOL price range", "crypto", "pyth:SOL/USD",
+          new anchor.BN(200_000_000), 2, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null,
+          range(200_000_000, 150_000_000), null, null,
+        ).accounts({
+          platform: platformPda, market: m.market, yesMint: m.yesMint,
+          noMint: m.noMint, collateralMint, vault: m.vault, lpMint: m.lpMint,
+          authorityCollateral, authorityLpTokens: m.authorityLpTokens, authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY, oracleAccount: SOL_USD_FEED, oracleFeed: oracleFeedPda(SOL_USD_FEED.toBuffer()),
+        }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("InvalidRange");
+      }
+    });
+  });
+
+  describe("18. Switchboard market", () => {
+    it("detects the Switchboard backend from the feed account", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const m = deriveMarketPdas(p.marketCount.toNumber());
+      await program.methods.createMarket(
+        "Will SOL reach $220?", "SOL price prediction", "crypto", "switchboard:SOL/USD",
+        new anchor.BN(220_000_000), 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, null, null, null,
+      ).accounts({
+        platform: platformPda, market: m.market, yesMint: m.yesMint,
+        noMint: m.noMint, collateralMint, vault: m.vault, lpMint: m.lpMint,
+        authorityCollateral, authorityLpTokens: m.authorityLpTokens, authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY, oracleAccount: SWITCHBOARD_SOL_USD_FEED, oracleFeed: oracleFeedPda(SWITCHBOARD_SOL_USD_FEED.toBuffer()),
+      }).rpc();
+
+      const market = await program.account.market.fetch(m.market);
+      expect(market.oracleKind).to.deep.equal({ switchboard: {} });
+      expect(market.oracleFeed.toString()).to.equal(SWITCHBOARD_SOL_USD_FEED.toString());
+    });
+
+    it("rejects a Pyth resolution source for a Switchboard feed", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const m = deriveMarketPdas(p.marketCount.toNumber());
+      try {
+        await program.methods.createMarket(
+          "Will SOL reach $220?", "SOL price prediction", "crypto", "pyth:SOL/USD",
+          new anchor.BN(220_000_000), 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, null, null, null,
+        ).accounts({
+          platform: platformPda, market: m.market, yesMint: m.yesMint,
+          noMint: m.noMint, collateralMint, vault: m.vault, lpMint: m.lpMint,
+          authorityCollateral, authorityLpTokens: m.authorityLpTokens, authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY, oracleAccount: SWITCHBOARD_SOL_USD_FEED, oracleFeed: oracleFeedPda(SWITCHBOARD_SOL_USD_FEED.toBuffer()),
+        }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("ResolutionSourceMismatch");
+      }
+    });
+  });
+
+  describe("19. TWAP market", () => {
+    let mt: ReturnType<typeof deriveMarketPdas>;
+    let priceSamples: PublicKey;
+
+    it("creates a market settling on a 30 minute TWAP", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const marketId = p.marketCount.toNumber();
+      mt = deriveMarketPdas(marketId);
+      const buf = Buffer.alloc(8);
+      buf.writeBigUInt64LE(BigInt(marketId));
+      [priceSamples] = PublicKey.findProgramAddressSync([Buffer.from("price_samples"), buf], program.programId);
+
+      await program.methods.createMarket(
+        "Will SOL average above $200 into expiry?", "SOL 30m TWAP", "crypto", "pyth:SOL/USD",
+        new anchor.BN(200_000_000), 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, null,
+        { window: new anchor.BN(1800), minSamples: 10 }, null,
+      ).accounts({
+        platform: platformPda, market: mt.market, yesMint: mt.yesMint,
+        noMint: mt.noMint, collateralMint, vault: mt.vault, lpMint: mt.lpMint,
+        authorityCollateral, authorityLpTokens: mt.authorityLpTokens, authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY, oracleAccount: SOL_USD_FEED, oracleFeed: oracleFeedPda(SOL_USD_FEED.toBuffer()),
+      }).rpc();
+
+      const market = await program.account.market.fetch(mt.market);
+      expect(market.twap.window.toNumber()).to.equal(1800);
+      expect(market.twap.minSamples).to.equal(10);
+    });
+
+    it("rejects a sample published before the averaging window", async () => {
+      try {
+        await program.methods.recordPriceSample()
+          .accounts({
+            market: mt.market, priceSamples, oracleAccount: SOL_USD_FEED,
+            recorder: user.publicKey, systemProgram: SystemProgram.programId,
+          }).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("OutsideTwapWindow");
+      }
+    });
+  });
+
+  describe("20. Optimistic resolution", () => {
+    let mm: ReturnType<typeof deriveMarketPdas>;
+    let proposal: PublicKey;
+    let bondVault: PublicKey;
+
+    it("creates a manually resolved market", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const marketId = p.marketCount.toNumber();
+      mm = deriveMarketPdas(marketId);
+      const buf = Buffer.alloc(8);
+      buf.writeBigUInt64LE(BigInt(marketId));
+      [proposal] = PublicKey.findProgramAddressSync([Buffer.from("proposal"), buf], program.programId);
+      [bondVault] = PublicKey.findProgramAddressSync([Buffer.from("bond_vault"), buf], program.programId);
+
+      await program.methods.createMarket(
+        "Will the mainnet upgrade ship this quarter?", "Protocol roadmap", "tech", "Official announcement",
+        null, 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, null, null, null,
+      ).accounts({
+        platform: platformPda, market: mm.market, yesMint: mm.yesMint,
+        noMint: mm.noMint, collateralMint, vault: mm.vault, lpMint: mm.lpMint,
+        authorityCollateral, authorityLpTokens: mm.authorityLpTokens, authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY, oracleAccount: null,
+      }).rpc();
+
+      const market = await program.account.market.fetch(mm.market);
+      expect(market.resolutionValue).to.be.null;
+    });
+
+    it("rejects a proposal before the authority grace period has passed", async () => {
+      try {
+        await program.methods.proposeOutcome(true)
+          .accounts({
+            market: mm.market, proposal, bondVault, collateralMint,
+            proposerCollateral: userCollateral0, proposer: user.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+            rent: SYSVAR_RENT_PUBKEY,
+          }).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("GracePeriodActive");
+      }
+    });
+  });
+
+  describe("21. Invalid resolution", () => {
+    it("rejects invalidation by non-authority", async () => {
+      try {
+        await program.methods.resolveInvalid(5000)
+          .accounts({ platform: platformPda, market: m0.market, authority: user.publicKey }).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("Unauthorized");
+      }
+    });
+
+    it("rejects invalidation before expiry", async () => {
+      try {
+        await program.methods.resolveInvalid(null)
+          .accounts({ platform: platformPda, market: m0.market, authority: authority.publicKey }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("MarketNotExpired");
+      }
+    });
+  });
+
+  describe("22. Scalar market", () => {
+    const bounds = { lower: new anchor.BN(100_000_000), upper: new anchor.BN(300_000_000), lowerInclusive: true, upperInclusive: true };
+
+    it("creates a LONG/SHORT market over $100 - $300", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const m = deriveMarketPdas(p.marketCount.toNumber());
+      await program.methods.createMarket(
+        "SOL price on Dec 31 between $100 and $300", "SOL scalar", "crypto", "pyth:SOL/USD",
+        null, 5, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, bounds, null, null,
+      ).accounts({
+        platform: platformPda, market: m.market, yesMint: m.yesMint,
+        noMint: m.noMint, collateralMint, vault: m.vault, lpMint: m.lpMint,
+        authorityCollateral, authorityLpTokens: m.authorityLpTokens, authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY, oracleAccount: SOL_USD_FEED, oracleFeed: oracleFeedPda(SOL_USD_FEED.toBuffer()),
+      }).rpc();
+
+      const market = await program.account.market.fetch(m.market);
+      expect(market.resolutionOperator).to.equal(5);
+      expect(market.resolutionValue).to.be.null;
+      expect(market.oracleKind).to.not.be.null;
+      expect(market.resolutionRange.lower.toNumber()).to.equal(100_000_000);
+      expect(market.resolutionRange.upper.toNumber()).to.equal(300_000_000);
+    });
+
+    it("rejects a target value on a scalar market", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const m = deriveMarketPdas(p.marketCount.toNumber());
+      try {
+        await program.methods.createMarket(
+          "SOL price on Dec 31 between $100 and $300", "SOL scalar", "crypto", "pyth:SOL/USD",
+          new anchor.BN(200_000_000), 5, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, bounds, null, null,
+        ).accounts({
+          platform: platformPda, market: m.market, yesMint: m.yesMint,
+          noMint: m.noMint, collateralMint, vault: m.vault, lpMint: m.lpMint,
+          authorityCollateral, authorityLpTokens: m.authorityLpTokens, authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY, oracleAccount: SOL_USD_FEED, oracleFeed: oracleFeedPda(SOL_USD_FEED.toBuffer()),
+        }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("InvalidOperator");
+      }
+    });
+
+    it("rejects a manually resolved scalar market", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const m = deriveMarketPdas(p.marketCount.toNumber());
+      try {
+        await program.methods.createMarket(
+          "SOL price on Dec 31 between $100 and $300", "SOL scalar", "crypto", "CoinGecko",
+          null, 5, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, bounds, null, null,
+        ).accounts({
+          platform: platformPda, market: m.market, yesMint: m.yesMint,
+          noMint: m.noMint, collateralMint, vault: m.vault, lpMint: m.lpMint,
+          authorityCollateral, authorityLpTokens: m.authorityLpTokens, authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY, oracleAccount: null,
+        }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("OracleRequired");
+      }
+    });
+  });
+
+  describe("23. Categorical market", () => {
+    let marketId: number;
+    let mc: ReturnType<typeof deriveMarketPdas>;
+    let outcomePool: PublicKey;
+    let outcomeMints: PublicKey[];
+
+    const deriveCategoricalPdas = (id: number, outcomeCount: number) => {
+      const buf = Buffer.alloc(8);
+      buf.writeBigUInt64LE(BigInt(id));
+      const [pool] = PublicKey.findProgramAddressSync([Buffer.from("outcome_pool"), buf], program.programId);
+      const mints = Array.from({ length: outcomeCount }, (_, i) =>
+        PublicKey.findProgramAddressSync([Buffer.from("outcome_mint"), buf, Buffer.from([i])], program.programId)[0]);
+      return { pool, mints };
+    };
+
+    it("creates a three-outcome market with one mint per outcome", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      marketId = p.marketCount.toNumber();
+      mc = deriveMarketPdas(marketId);
+      ({ pool: outcomePool, mints: outcomeMints } = deriveCategoricalPdas(marketId, 3));
+
+      await program.methods.createCategoricalMarket(
+        "Which chain tops TVL at year end?", "Chain TVL race", "crypto", "DefiLlama",
+        3, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY),
+      ).accounts({
+        platform: platformPda, market: mc.market, outcomePool, collateralMint, vault: mc.vault,
+        authorityCollateral, authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
+      }).remainingAccounts(outcomeMints.map(pubkey => ({ pubkey, isSigner: false, isWritable: true })))
+        .rpc();
+
+      const market = await program.account.market.fetch(mc.market);
+      expect(market.outcomeCount).to.equal(3);
+      const pool = await program.account.outcomePool.fetch(outcomePool);
+      // The deposit is held as INITIAL_LIQUIDITY complete sets
+      expect(pool.reserves.slice(0, 3).map((r: anchor.BN) => r.toNumber()))
+        .to.deep.equal([INITIAL_LIQUIDITY, INITIAL_LIQUIDITY, INITIAL_LIQUIDITY]);
+    });
+
+    it("buys and sells an outcome by index", async () => {
+      const userOutcome = await createRawTokenAccount(provider.connection, authority.payer, outcomeMints[2], user.publicKey);
+      await program.methods.buyCategorical(2, new anchor.BN(100_000), new anchor.BN(1))
+        .accounts({
+          market: mc.market, outcomePool, platform: platformPda, outcomeMint: outcomeMints[2], vault: mc.vault,
+          treasury: treasuryTokenAccount, userCollateral: userCollateral0, userOutcomeTokens: userOutcome,
+          user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+        }).signers([user]).rpc();
+
+      const bought = Number((await getAccount(provider.connection, userOutcome)).amount);
+      // 98,000 after fees mints as many sets, plus outcome 2 tokens swapped for the other outcomes
+      expect(bought).to.be.greaterThan(98_000);
+      let pool = await program.account.outcomePool.fetch(outcomePool);
+      expect(pool.supplies[2].toNumber()).to.equal(bought);
+      // The bought outcome is now the most expensive, i.e. has the smallest reserve
+      expect(pool.reserves[2].toNumber()).to.be.lessThan(pool.reserves[0].toNumber());
+
+      const collateralBefore = Number((await getAccount(provider.connection, userCollateral0)).amount);
+      await program.methods.sellCategorical(2, new anchor.BN(bought), new anchor.BN(1))
+        .accounts({
+          market: mc.market, outcomePool, platform: platformPda, outcomeMint: outcomeMints[2], vault: mc.vault,
+          treasury: treasuryTokenAccount, userCollateral: userCollateral0, userOutcomeTokens: userOutcome,
+          user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+        }).signers([user]).rpc();
+      pool = await program.account.outcomePool.fetch(outcomePool);
+      expect(pool.supplies[2].toNumber()).to.equal(0);
+      const received = Number((await getAccount(provider.connection, userCollateral0)).amount) - collateralBefore;
+      expect(received).to.be.greaterThan(90_000).and.at.most(98_000);
+      // Every outstanding set is still backed by one unit in the vault
+      const vault = Number((await getAccount(provider.connection, mc.vault)).amount);
+      for (let i = 0; i < 3; i++) {
+        expect(pool.reserves[i].toNumber() + pool.supplies[i].toNumber()).to.be.at.most(vault);
+      }
+    });
+
+    it("rejects a categorical market with only two outcomes", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const id = p.marketCount.toNumber();
+      const m = deriveMarketPdas(id);
+      const { pool, mints } = deriveCategoricalPdas(id, 2);
+      try {
+        await program.methods.createCategoricalMarket(
+          "Heads or tails?", "Coin flip", "misc", "Referee",
+          2, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY),
+        ).accounts({
+          platform: platformPda, market: m.market, outcomePool: pool, collateralMint, vault: m.vault,
+          authorityCollateral, authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
+        }).remainingAccounts(mints.map(pubkey => ({ pubkey, isSigner: false, isWritable: true })))
+          .rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("InvalidOutcomeCount");
+      }
+    });
+
+    it("rejects binary resolution of a categorical market", async () => {
+      try {
+        await program.methods.resolveMarket(true)
+          .accounts({ market: mc.market, resolver: authority.publicKey, oracleAccount: null, priceSamples: null }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("NotBinaryMarket");
+      }
+    });
+
+    it("returns the vault to the creator once a cancelled market owes nothing", async () => {
+      await program.methods.cancelMarket()
+        .accounts({ market: mc.market, authority: authority.publicKey }).rpc();
+      const vault = Number((await getAccount(provider.connection, mc.vault)).amount);
+      const collateralBefore = Number((await getAccount(provider.connection, authorityCollateral)).amount);
+      const withdrawAccounts = {
+        market: mc.market, outcomePool, vault: mc.vault, authorityCollateral,
+        authority: authority.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+      };
+      await program.methods.withdrawCategoricalSurplus().accounts(withdrawAccounts).rpc();
+      expect(Number((await getAccount(provider.connection, authorityCollateral)).amount)).to.equal(collateralBefore + vault);
+
+      try {
+        await program.methods.withdrawCategoricalSurplus().accounts(withdrawAccounts).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("ZeroAmount");
+      }
+    });
+
+    it("leaves the rounding dust of cancelled refunds to the creator", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const id = p.marketCount.toNumber();
+      const m = deriveMarketPdas(id);
+      const { pool, mints } = deriveCategoricalPdas(id, 3);
+      await program.methods.createCategoricalMarket(
+        "Which chain ships its upgrade first?", "Upgrade race", "crypto", "Release notes",
+        3, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY),
+      ).accounts({
+        platform: platformPda, market: m.market, outcomePool: pool, collateralMint, vault: m.vault,
+        authorityCollateral, authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
+      }).remainingAccounts(mints.map(pubkey => ({ pubkey, isSigner: false, isWritable: true })))
+        .rpc();
+
+      const userOutcome = await createRawTokenAccount(provider.connection, authority.payer, mints[0], user.publicKey);
+      const tradeAccounts = {
+        market: m.market, outcomePool: pool, platform: platformPda, outcomeMint: mints[0], vault: m.vault,
+        treasury: treasuryTokenAccount, userCollateral: userCollateral0, userOutcomeTokens: userOutcome,
+        user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+      };
+      await program.methods.buyCategorical(0, new anchor.BN(100_000), new anchor.BN(1))
+        .accounts(tradeAccounts).signers([user]).rpc();
+      // Sell back enough to leave a holding that does not split evenly across three outcomes
+      const bought = Number((await getAccount(provider.connection, userOutcome)).amount);
+      await program.methods.sellCategorical(0, new anchor.BN(3_000 + (bought - 1) % 3), new anchor.BN(1))
+        .accounts(tradeAccounts).signers([user]).rpc();
+      const held = Number((await getAccount(provider.connection, userOutcome)).amount);
+      expect(held % 3).to.equal(1);
+
+      await program.methods.cancelMarket()
+        .accounts({ market: m.market, authority: authority.publicKey }).rpc();
+      const withdrawAccounts = {
+        market: m.market, outcomePool: pool, vault: m.vault, authorityCollateral,
+        authority: authority.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+      };
+      // The holder's claim is reserved rounded up while the tokens are outstanding
+      await program.methods.withdrawCategoricalSurplus().accounts(withdrawAccounts).rpc();
+      expect(Number((await getAccount(provider.connection, m.vault)).amount)).to.equal(Math.ceil(held / 3));
+
+      const collateralBefore = Number((await getAccount(provider.connection, userCollateral0)).amount);
+      await program.methods.redeemCategoricalCancelled(0)
+        .accounts({
+          market: m.market, outcomePool: pool, outcomeMint: mints[0], vault: m.vault,
+          userCollateral: userCollateral0, userOutcomeTokens: userOutcome,
+          user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+        }).signers([user]).rpc();
+      expect(Number((await getAccount(provider.connection, userCollateral0)).amount))
+        .to.equal(collateralBefore + Math.floor(held / 3));
+
+      // The unit rounded off the refund stays in the vault until the creator withdraws it
+      expect(Number((await getAccount(provider.connection, m.vault)).amount)).to.equal(1);
+      const creatorBefore = Number((await getAccount(provider.connection, authorityCollateral)).amount);
+      await program.methods.withdrawCategoricalSurplus().accounts(withdrawAccounts).rpc();
+      expect(Number((await getAccount(provider.connection, authorityCollateral)).amount)).to.equal(creatorBefore + 1);
+      expect(Number((await getAccount(provider.connection, m.vault)).amount)).to.equal(0);
+    });
+  });
+
+  describe("24. Complete sets", () => {
+    const setAccounts = () => ({
+      market: m0.market, yesMint: m0.yesMint, noMint: m0.noMint, vault: m0.vault,
+      userCollateral: userCollateral0, userYesTokens: userYes0, userNoTokens: userNo0,
+      userPosition: userPos0, user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+    });
+
+    it("mints 1 YES + 1 NO per unit of collateral without moving the AMM", async () => {
+      const before = await program.account.market.fetch(m0.market);
+      const yesBefore = Number((await getAccount(provider.connection, userYes0)).amount);
+      const noBefore = Number((await getAccount(provider.connection, userNo0)).amount);
+      const vaultBefore = Number((await getAccount(provider.connection, m0.vault)).amount);
+
+      await program.methods.mintCompleteSet(new anchor.BN(250_000))
+        .accounts({ ...setAccounts(), systemProgram: SystemProgram.programId }).signers([user]).rpc();
+
+      expect(Number((await getAccount(provider.connection, userYes0)).amount)).to.equal(yesBefore + 250_000);
+      expect(Number((await getAccount(provider.connection, userNo0)).amount)).to.equal(noBefore + 250_000);
+      expect(Number((await getAccount(provider.connection, m0.vault)).amount)).to.equal(vaultBefore + 250_000);
+      const after = await program.account.market.fetch(m0.market);
+      expect(after.yesAmount.toNumber()).to.equal(before.yesAmount.toNumber());
+      expect(after.noAmount.toNumber()).to.equal(before.noAmount.toNumber());
+    });
+
+    it("merges complete sets back into collateral", async () => {
+      const collateralBefore = Number((await getAccount(provider.connection, userCollateral0)).amount);
+      await program.methods.mergeCompleteSet(new anchor.BN(250_000))
+        .accounts(setAccounts()).signers([user]).rpc();
+      expect(Number((await getAccount(provider.connection, userCollateral0)).amount)).to.equal(collateralBefore + 250_000);
+    });
+  });
+
+  describe("25. Liquidity provider shares", () => {
+    let userLp0: PublicKey;
+    const lpAccounts = () => ({
+      market: m0.market, yesMint: m0.yesMint, noMint: m0.noMint, lpMint: m0.lpMint, vault: m0.vault,
+      userCollateral: userCollateral0, userYesTokens: userYes0, userNoTokens: userNo0, userLpTokens: userLp0,
+      userPosition: userPos0, user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+    });
+
+    it("gave the creator one LP share per reserve unit", async () => {
+      const creatorShares = Number((await getAccount(provider.connection, m0.authorityLpTokens)).amount);
+      expect(creatorShares).to.equal(INITIAL_LIQUIDITY);
+    });
+
+    it("adds liquidity at the current price and returns the unmatched side", async () => {
+      userLp0 = await createRawTokenAccount(provider.connection, authority.payer, m0.lpMint, user.publicKey);
+      const before = await program.account.market.fetch(m0.market);
+      const yesBefore = Number((await getAccount(provider.connection, userYes0)).amount);
+      const noBefore = Number((await getAccount(provider.connection, userNo0)).amount);
+
+      await program.methods.addLiquidity(new anchor.BN(200_000), new anchor.BN(1))
+        .accounts(lpAccounts()).signers([user]).rpc();
+
+      const after = await program.account.market.fetch(m0.market);
+      expect(after.totalLiquidity.toNumber()).to.equal(before.totalLiquidity.toNumber() + 200_000);
+      expect(Number((await getAccount(provider.connection, userLp0)).amount)).to.be.greaterThan(0);
+      // Price is unchanged up to rounding
+      const priceBefore = before.noAmount.toNumber() / (before.yesAmount.toNumber() + before.noAmount.toNumber());
+      const priceAfter = after.noAmount.toNumber() / (after.yesAmount.toNumber() + after.noAmount.toNumber());
+      expect(priceAfter).to.be.closeTo(priceBefore, 1e-4);
+      // Only the side with the smaller reserve is handed back
+      const yesReturned = Number((await getAccount(provider.connection, userYes0)).amount) - yesBefore;
+      const noReturned = Number((await getAccount(provider.connection, userNo0)).amount) - noBefore;
+      expect(Math.min(yesReturned, noReturned)).to.equal(0);
+    });
+
+    it("removes liquidity for collateral plus imbalanced outcome tokens", async () => {
+      const shares = Number((await getAccount(provider.connection, userLp0)).amount);
+      const collateralBefore = Number((await getAccount(provider.connection, userCollateral0)).amount);
+      await program.methods.removeLiquidity(new anchor.BN(shares), new anchor.BN(1))
+        .accounts(lpAccounts()).signers([user]).rpc();
+      expect(Number((await getAccount(provider.connection, userLp0)).amount)).to.equal(0);
+      expect(Number((await getAccount(provider.connection, userCollateral0)).amount)).to.be.greaterThan(collateralBefore);
+    });
+
+    it("pays out a settled market's surplus over what token holders are owed", async () => {
+      // Market 1 was cancelled without trades, so no token holder has a claim on its vault
+      const m1 = deriveMarketPdas(1);
+      const collateralBefore = Number((await getAccount(provider.connection, authorityCollateral)).amount);
+      await program.methods.removeLiquidity(new anchor.BN(INITIAL_LIQUIDITY), new anchor.BN(INITIAL_LIQUIDITY))
+        .accounts({
+          market: m1.market, yesMint: m1.yesMint, noMint: m1.noMint, lpMint: m1.lpMint, vault: m1.vault,
+          userCollateral: authorityCollateral,
+          userYesTokens: await createRawTokenAccount(provider.connection, authority.payer, m1.yesMint, authority.publicKey),
+          userNoTokens: await createRawTokenAccount(provider.connection, authority.payer, m1.noMint, authority.publicKey),
+          userLpTokens: m1.authorityLpTokens, userPosition: derivePositionPda(1, authority.publicKey),
+          user: authority.publicKey, tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        }).rpc();
+      expect(Number((await getAccount(provider.connection, authorityCollateral)).amount))
+        .to.equal(collateralBefore + INITIAL_LIQUIDITY);
+      expect(Number((await getAccount(provider.connection, m1.vault)).amount)).to.equal(0);
+    });
+  });
+
+  describe("26. LMSR market", () => {
+    let marketId: number;
+    let ml: ReturnType<typeof deriveMarketPdas>;
+    const createLmsr = (m: ReturnType<typeof deriveMarketPdas>, b: number) =>
+      program.methods.createMarket(
+        "Will SOL reach $400?", "SOL price prediction", "crypto", "CoinGecko",
+        null, 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, null, null, new anchor.BN(b),
+      ).accounts({
+        platform: platformPda, market: m.market, yesMint: m.yesMint,
+        noMint: m.noMint, collateralMint, vault: m.vault, lpMint: m.lpMint,
+        authorityCollateral, authorityLpTokens: m.authorityLpTokens, authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY, oracleAccount: null,
+      }).rpc();
+
+    it("rejects a liquidity parameter the initial liquidity cannot cover", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      try {
+        // b * ln(2) = 1,386,295 > INITIAL_LIQUIDITY
+        await createLmsr(deriveMarketPdas(p.marketCount.toNumber()), 2_000_000);
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("LmsrUnderfunded");
+      }
+    });
+
+    it("creates an LMSR market with no outstanding shares", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      marketId = p.marketCount.toNumber();
+      ml = deriveMarketPdas(marketId);
+      await createLmsr(ml, 1_000_000);
+
+      const market = await program.account.market.fetch(ml.market);
+      expect(market.curve).to.deep.equal({ lmsr: {} });
+      expect(market.lmsrB.toNumber()).to.equal(1_000_000);
+      expect(market.yesAmount.toNumber()).to.equal(0);
+      expect(market.noAmount.toNumber()).to.equal(0);
+      expect(market.lpFeeBps).to.equal(0);
+      // The subsidy is represented by LP shares, redeemable once the market settles
+      expect(Number((await getAccount(provider.connection, ml.authorityLpTokens)).amount)).to.equal(INITIAL_LIQUIDITY);
+    });
+
+    it("buys YES at about 50c from a fresh market", async () => {
+      const userYes = await createRawTokenAccount(provider.connection, authority.payer, ml.yesMint, user.publicKey);
+      const userNo = await createRawTokenAccount(provider.connection, authority.payer, ml.noMint, user.publicKey);
+      await program.methods.buyOutcome(new anchor.BN(10_000), true, new anchor.BN(1), null, null)
+        .accounts({
+          market: ml.market, platform: platformPda, yesMint: ml.yesMint, noMint: ml.noMint,
+          vault: ml.vault, treasury: treasuryTokenAccount,
+          userCollateral: userCollateral0, userYesTokens: userYes, userNoTokens: userNo,
+          userPosition: derivePositionPda(marketId, user.publicKey), user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        }).signers([user]).rpc();
+
+      const bought = Number((await getAccount(provider.connection, userYes)).amount);
+      // 9,800 collateral after fees buys a little under 19,600 YES at a price starting at 0.5
+      expect(bought).to.be.greaterThan(19_400).and.lessThan(19_600);
+      const market = await program.account.market.fetch(ml.market);
+      expect(market.yesAmount.toNumber()).to.equal(bought);
+      expect(market.noAmount.toNumber()).to.equal(0);
+    });
+
+    it("returns the unspent subsidy to the creator after settlement", async () => {
+      await program.methods.cancelMarket()
+        .accounts({ market: ml.market, authority: authority.publicKey }).rpc();
+      const outstanding = (await program.account.market.fetch(ml.market)).yesAmount.toNumber();
+      const vaultBefore = Number((await getAccount(provider.connection, ml.vault)).amount);
+      const collateralBefore = Number((await getAccount(provider.connection, authorityCollateral)).amount);
+
+      await program.methods.removeLiquidity(new anchor.BN(INITIAL_LIQUIDITY), new anchor.BN(1))
+        .accounts({
+          market: ml.market, yesMint: ml.yesMint, noMint: ml.noMint, lpMint: ml.lpMint, vault: ml.vault,
+          userCollateral: authorityCollateral,
+          userYesTokens: await createRawTokenAccount(provider.connection, authority.payer, ml.yesMint, authority.publicKey),
+          userNoTokens: await createRawTokenAccount(provider.connection, authority.payer, ml.noMint, authority.publicKey),
+          userLpTokens: ml.authorityLpTokens, userPosition: derivePositionPda(marketId, authority.publicKey),
+          user: authority.publicKey, tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        }).rpc();
+
+      // Cancelled tokens are refunded at half a unit each; the creator takes everything else
+      const owed = Math.ceil(outstanding / 2);
+      expect(Number((await getAccount(provider.connection, authorityCollateral)).amount))
+        .to.equal(collateralBefore + vaultBefore - owed);
+      expect(Number((await getAccount(provider.connection, ml.vault)).amount)).to.equal(owed);
+    });
+  });
+
+  describe("27. Exact-output trades", () => {
+    const tradeAccounts = () => ({
+      market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint,
+      vault: m0.vault, treasury: treasuryTokenAccount,
+      userCollateral: userCollateral0, userYesTokens: userYes0, userNoTokens: userNo0,
+      userPosition: userPos0, user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+    });
+    const buyAccounts = () => ({ ...tradeAccounts(), systemProgram: SystemProgram.programId });
+
+    it("buys exactly the requested number of tokens", async () => {
+      const yesBefore = Number((await getAccount(provider.connection, userYes0)).amount);
+      const collateralBefore = Number((await getAccount(provider.connection, userCollateral0)).amount);
+      await program.methods.buyOutcomeExactOut(new anchor.BN(50_000), true, new anchor.BN(100_000))
+        .accounts(buyAccounts()).signers([user]).rpc();
+      expect(Number((await getAccount(provider.connection, userYes0)).amount)).to.equal(yesBefore + 50_000);
+      const spent = collateralBefore - Number((await getAccount(provider.connection, userCollateral0)).amount);
+      expect(spent).to.be.greaterThan(0).and.at.most(100_000);
+    });
+
+    it("rejects a buy costing more than max_amount_in", async () => {
+      try {
+        await program.methods.buyOutcomeExactOut(new anchor.BN(50_000), true, new anchor.BN(1_000))
+          .accounts(buyAccounts()).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("SlippageExceeded");
+      }
+    });
+
+    it("sells for exactly the requested collateral", async () => {
+      const collateralBefore = Number((await getAccount(provider.connection, userCollateral0)).amount);
+      const yesBefore = Number((await getAccount(provider.connection, userYes0)).amount);
+      await program.methods.sellOutcomeExactOut(new anchor.BN(10_000), true, new anchor.BN(yesBefore))
+        .accounts(tradeAccounts()).signers([user]).rpc();
+      expect(Number((await getAccount(provider.connection, userCollateral0)).amount)).to.equal(collateralBefore + 10_000);
+      expect(Number((await getAccount(provider.connection, userYes0)).amount)).to.be.lessThan(yesBefore);
+    });
+
+    it("rejects a sell burning more than max_tokens_in", async () => {
+      try {
+        await program.methods.sellOutcomeExactOut(new anchor.BN(10_000), true, new anchor.BN(1))
+          .accounts(tradeAccounts()).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("SlippageExceeded");
+      }
+    });
+  });
+
+  describe("28. Trade guards", () => {
+    const tradeAccounts = () => ({
+      market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint,
+      vault: m0.vault, treasury: treasuryTokenAccount,
+      userCollateral: userCollateral0, userYesTokens: userYes0, userNoTokens: userNo0,
+      userPosition: userPos0, user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+    });
+
+    it("rejects a buy landing after its deadline", async () => {
+      const deadline = new anchor.BN(Math.floor(Date.now() / 1000) - 60);
+      try {
+        await program.methods.buyOutcome(new anchor.BN(10_000), true, new anchor.BN(0), deadline, null)
+          .accounts({ ...tradeAccounts(), systemProgram: SystemProgram.programId }).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("DeadlineExceeded");
+      }
+    });
+
+    it("rejects a buy moving the price more than allowed", async () => {
+      try {
+        await program.methods.buyOutcome(new anchor.BN(200_000), true, new anchor.BN(0), null, 10)
+          .accounts({ ...tradeAccounts(), systemProgram: SystemProgram.programId }).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("PriceImpactTooHigh");
+      }
+    });
+
+    it("accepts a small sell within both guards", async () => {
+      const deadline = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
+      const yesBefore = Number((await getAccount(provider.connection, userYes0)).amount);
+      await program.methods.sellOutcome(new anchor.BN(1_000), true, new anchor.BN(0), deadline, 500)
+        .accounts(tradeAccounts()).signers([user]).rpc();
+      expect(Number((await getAccount(provider.connection, userYes0)).amount)).to.equal(yesBefore - 1_000);
+    });
+  });
+
+  describe("29. Order book", () => {
+    const seed = (prefix: string) =>
+      PublicKey.findProgramAddressSync([Buffer.from(prefix), new anchor.BN(0).toArrayLike(Buffer, "le", 8)], program.programId)[0];
+    const orderBook = seed("order_book");
+    const bookAccounts = () => ({
+      orderBook, orderEscrow: seed("order_escrow"), yesEscrow: seed("yes_escrow"), noEscrow: seed("no_escrow"),
+    });
+    const orderAccounts = () => ({
+      market: m0.market, ...bookAccounts(),
+      userCollateral: userCollateral0, userYesTokens: userYes0, userNoTokens: userNo0,
+      userPosition: userPos0, user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+    });
+    const placeAccounts = () => ({
+      ...orderAccounts(), vault: m0.vault, treasury: treasuryTokenAccount, systemProgram: SystemProgram.programId,
+    });
+
+    it("creates the order book and its escrows", async () => {
+      await program.methods.createOrderBook().accounts({
+        market: m0.market, ...bookAccounts(), collateralMint, yesMint: m0.yesMint, noMint: m0.noMint,
+        payer: authority.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
+      }).rpc();
+      const book = await program.account.orderBook.fetch(orderBook);
+      expect(book.orders).to.have.length(0);
+    });
+
+    it("rejects an order priced at 100%", async () => {
+      try {
+        await program.methods.placeOrder(true, true, 10000, new anchor.BN(100_000))
+          .accounts(placeAccounts()).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("InvalidOrderPrice");
+      }
+    });
+
+    it("rejects an order below the minimum quantity", async () => {
+      try {
+        await program.methods.placeOrder(true, true, 100, new anchor.BN(1_000))
+          .accounts(placeAccounts()).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("OrderTooSmall");
+      }
+    });
+
+    it("fills a cheap resting ask before the AMM, charging the trading fee", async () => {
+      await program.methods.placeOrder(true, false, 100, new anchor.BN(100_000))
+        .accounts(placeAccounts()).signers([user]).rpc();
+      await program.methods.buyOutcome(new anchor.BN(10_000), true, new anchor.BN(0), null, null)
+        .accounts({
+          market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint,
+          vault: m0.vault, treasury: treasuryTokenAccount, ...bookAccounts(),
+          userCollateral: userCollateral0, userYesTokens: userYes0, userNoTokens: userNo0,
+          userPosition: userPos0, user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        }).signers([user]).rpc();
+      // The filled ask frees its slot for the maker's claim
+      const book = await program.account.orderBook.fetch(orderBook);
+      expect(book.orders).to.have.length(0);
+      expect(book.claims[0].owner.toBase58()).to.equal(user.publicKey.toBase58());
+      expect(book.claims[0].collateral.toNumber()).to.equal(1_000);
+    });
+
+    it("pays out a filled ask on claim", async () => {
+      const collateralBefore = Number((await getAccount(provider.connection, userCollateral0)).amount);
+      await program.methods.claimOrderProceeds().accounts(orderAccounts()).signers([user]).rpc();
+      expect(Number((await getAccount(provider.connection, userCollateral0)).amount)).to.equal(collateralBefore + 1_000);
+      expect((await program.account.orderBook.fetch(orderBook)).claims).to.have.length(0);
+    });
+
+    it("refunds a resting bid on cancel", async () => {
+      const collateralBefore = Number((await getAccount(provider.connection, userCollateral0)).amount);
+      await program.methods.placeOrder(true, true, 100, new anchor.BN(100_000))
+        .accounts(placeAccounts()).signers([user]).rpc();
+      expect(Number((await getAccount(provider.connection, userCollateral0)).amount)).to.equal(collateralBefore - 1_000);
+      await program.methods.cancelOrder(new anchor.BN(1)).accounts(orderAccounts()).signers([user]).rpc();
+      expect(Number((await getAccount(provider.connection, userCollateral0)).amount)).to.equal(collateralBefore);
+    });
+
+    it("charges a taker crossing the book the same trading fee", async () => {
+      await program.methods.placeOrder(true, false, 100, new anchor.BN(100_000)).accounts(placeAccounts()).signers([user]).rpc();
+      const collateralBefore = Number((await getAccount(provider.connection, userCollateral0)).amount);
+      await program.methods.placeOrder(true, true, 100, new anchor.BN(100_000)).accounts(placeAccounts()).signers([user]).rpc();
+      // 1_000 for the fill plus the 2% fee
+      expect(Number((await getAccount(provider.connection, userCollateral0)).amount)).to.equal(collateralBefore - 1_020);
+      await program.methods.claimOrderProceeds().accounts(orderAccounts()).signers([user]).rpc();
+    });
+  });
+
+  describe("30. Conditional orders", () => {
+    const idBuf = new anchor.BN(0).toArrayLike(Buffer, "le", 8);
+    const [conditionalOrder] = PublicKey.findProgramAddressSync(
+      [Buffer.from("conditional_order"), idBuf, user.publicKey.toBuffer()], program.programId);
+    const [escrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("conditional_escrow"), idBuf, user.publicKey.toBuffer()], program.programId);
+    const placeAccounts = () => ({
+      market: m0.market, conditionalOrder, escrow, escrowMint: m0.yesMint, userSource: userYes0,
+      userPosition: userPos0, user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
+    });
+    const executeAccounts = () => ({
+      market: m0.market, platform: platformPda, conditionalOrder, escrow,
+      yesMint: m0.yesMint, noMint: m0.noMint, vault: m0.vault, treasury: treasuryTokenAccount,
+      ownerCollateral: userCollateral0, ownerOutcomeTokens: userYes0, ownerPosition: userPos0,
+      owner: user.publicKey, keeper: authority.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+    });
+
+    it("rejects a stop-loss buy", async () => {
+      try {
+        await program.methods.placeConditionalOrder({ stopLoss: {} }, true, true, 3000, new anchor.BN(1_000), new anchor.BN(0), new anchor.BN(0))
+          .accounts({ ...placeAccounts(), escrowMint: collateralMint, userSource: userCollateral0 }).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("InvalidConditionalOrder");
+      }
+    });
+
+    it("keeps a stop-loss until its trigger is reached, then cancels it", async () => {
+      const yesBefore = Number((await getAccount(provider.connection, userYes0)).amount);
+      const trackedBefore = (await program.account.userPosition.fetch(userPos0)).yesTokens.toNumber();
+      await program.methods.placeConditionalOrder({ stopLoss: {} }, true, false, 100, new anchor.BN(1_000), new anchor.BN(0), new anchor.BN(0))
+        .accounts(placeAccounts()).signers([user]).rpc();
+      expect(Number((await getAccount(provider.connection, userYes0)).amount)).to.equal(yesBefore - 1_000);
+      try {
+        await program.methods.executeConditionalOrder().accounts(executeAccounts()).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("TriggerNotReached");
+      }
+      await program.methods.cancelConditionalOrder().accounts({
+        market: m0.market, conditionalOrder, escrow, userDestination: userYes0,
+        userPosition: userPos0, user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+      }).signers([user]).rpc();
+      expect(Number((await getAccount(provider.connection, userYes0)).amount)).to.equal(yesBefore);
+      // Only what placement took off the position comes back
+      expect((await program.account.userPosition.fetch(userPos0)).yesTokens.toNumber()).to.equal(trackedBefore);
+    });
+
+    it("lets a keeper execute a triggered take-profit for the tip", async () => {
+      const tip = 10_000;
+      await program.methods.placeConditionalOrder({ takeProfit: {} }, true, false, 100, new anchor.BN(1_000), new anchor.BN(1), new anchor.BN(tip))
+        .accounts(placeAccounts()).signers([user]).rpc();
+      const collateralBefore = Number((await getAccount(provider.connection, userCollateral0)).amount);
+      await program.methods.executeConditionalOrder().accounts(executeAccounts()).rpc();
+      expect(Number((await getAccount(provider.connection, userCollateral0)).amount)).to.be.greaterThan(collateralBefore);
+      expect(await provider.connection.getAccountInfo(conditionalOrder)).to.be.null;
+      expect(await provider.connection.getAccountInfo(escrow)).to.be.null;
+    });
+  });
+
+  describe("31. Dynamic fees", () => {
+    it("rejects a schedule above the platform maximum", async () => {
+      try {
+        await program.methods.setFeeSchedule({ maxFeeBps: 2000, volatilityFactorBps: 0 })
+          .accounts({ platform: platformPda, market: m0.market, authority: authority.publicKey }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("InvalidFeeSchedule");
+      }
+    });
+
+    it("bounds how far one schedule raises the fee", async () => {
+      try {
+        await program.methods.setFeeSchedule({ maxFeeBps: 800, volatilityFactorBps: 0 })
+          .accounts({ platform: platformPda, market: m0.market, authority: authority.publicKey }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("FeeIncreaseTooLarge");
+      }
+    });
+
+    it("rejects a schedule set by someone other than the platform authority", async () => {
+      try {
+        await program.methods.setFeeSchedule({ maxFeeBps: 600, volatilityFactorBps: 0 })
+          .accounts({ platform: platformPda, market: m0.market, authority: user.publicKey }).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("Unauthorized");
+      }
+    });
+
+    it("charges at least the base fee under a schedule, then clears it", async () => {
+      await program.methods.setFeeSchedule({ maxFeeBps: 600, volatilityFactorBps: 5000 })
+        .accounts({ platform: platformPda, market: m0.market, authority: authority.publicKey }).rpc();
+      let market = await program.account.market.fetch(m0.market);
+      expect(market.feeSchedule).to.deep.equal({ maxFeeBps: 600, volatilityFactorBps: 5000 });
+
+      const treasuryBefore = Number((await getAccount(provider.connection, treasuryTokenAccount)).amount);
+      await program.methods.buyOutcome(new anchor.BN(100_000), false, new anchor.BN(0), null, null)
+        .accounts({
+          market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint,
+          vault: m0.vault, treasury: treasuryTokenAccount,
+          userCollateral: userCollateral0, userYesTokens: userYes0, userNoTokens: userNo0,
+          userPosition: userPos0, user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        }).signers([user]).rpc();
+      const protocolFee = Number((await getAccount(provider.connection, treasuryTokenAccount)).amount) - treasuryBefore;
+      expect(protocolFee).to.be.at.least(100_000 * (FEE_BPS - LP_FEE_BPS) / 10000);
+
+      await program.methods.setFeeSchedule(null).accounts({ platform: platformPda, market: m0.market, authority: authority.publicKey }).rpc();
+      market = await program.account.market.fetch(m0.market);
+      expect(market.feeSchedule).to.be.null;
+    });
+  });
+
+  describe("32. Platform admin", () => {
+    const configAccounts = (treasury: PublicKey) => ({
+      platform: platformPda, currentTreasury: treasuryTokenAccount, treasury, authority: authority.publicKey,
+    });
+
+    it("updates the platform fees", async () => {
+      await program.methods.updatePlatformConfig(300, LP_FEE_BPS, new anchor.BN(0), 0).accounts(configAccounts(treasuryTokenAccount)).rpc();
+      expect((await program.account.platform.fetch(platformPda)).feeBps).to.equal(300);
+      await program.methods.updatePlatformConfig(FEE_BPS, LP_FEE_BPS, new anchor.BN(0), 0).accounts(configAccounts(treasuryTokenAccount)).rpc();
+    });
+
+    it("rejects a fee above the maximum", async () => {
+      try {
+        await program.methods.updatePlatformConfig(2000, LP_FEE_BPS, new anchor.BN(0), 0).accounts(configAccounts(treasuryTokenAccount)).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("FeeTooHigh");
+      }
+    });
+
+    it("rejects a treasury holding another mint", async () => {
+      try {
+        await program.methods.updatePlatformConfig(FEE_BPS, LP_FEE_BPS, new anchor.BN(0), 0).accounts(configAccounts(userYes0)).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("InvalidTreasury");
+      }
+    });
+
+    it("hands the authority over in two steps", async () => {
+      await program.methods.proposeAuthority(user.publicKey)
+        .accounts({ platform: platformPda, authority: authority.publicKey }).rpc();
+      try {
+        await program.methods.acceptAuthority().accounts({ platform: platformPda, newAuthority: authority.publicKey }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("Unauthorized");
+      }
+      await program.methods.acceptAuthority()
+        .accounts({ platform: platformPda, newAuthority: user.publicKey }).signers([user]).rpc();
+      expect((await program.account.platform.fetch(platformPda)).authority.toBase58()).to.equal(user.publicKey.toBase58());
+
+      // Hand it back for the remaining tests
+      await program.methods.proposeAuthority(authority.publicKey)
+        .accounts({ platform: platformPda, authority: user.publicKey }).signers([user]).rpc();
+      await program.methods.acceptAuthority().accounts({ platform: platformPda, newAuthority: authority.publicKey }).rpc();
+      const platform = await program.account.platform.fetch(platformPda);
+      expect(platform.authority.toBase58()).to.equal(authority.publicKey.toBase58());
+      expect(platform.pendingAuthority).to.be.null;
+    });
+  });
+
+  describe("33. Emergency pause", () => {
+    const guardian = Keypair.generate();
+    const buyAccounts = () => ({
+      market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint,
+      vault: m0.vault, treasury: treasuryTokenAccount,
+      userCollateral: userCollateral0, userYesTokens: userYes0, userNoTokens: userNo0,
+      userPosition: userPos0, user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    });
+
+    before(async () => {
+      await program.methods.setGuardian(guardian.publicKey)
+        .accounts({ platform: platformPda, authority: authority.publicKey }).rpc();
+    });
+
+    it("rejects a pause from an unknown key", async () => {
+      try {
+        await program.methods.pausePlatform().accounts({ platform: platformPda, signer: user.publicKey }).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("Unauthorized");
+      }
+    });
+
+    it("blocks trading while the platform is paused", async () => {
+      await program.methods.pausePlatform().accounts({ platform: platformPda, signer: guardian.publicKey }).signers([guardian]).rpc();
+      try {
+        await program.methods.buyOutcome(new anchor.BN(10_000), true, new anchor.BN(0), null, null)
+          .accounts(buyAccounts()).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("PlatformPaused");
+      }
+      await program.methods.unpausePlatform().accounts({ platform: platformPda, signer: authority.publicKey }).rpc();
+      await program.methods.buyOutcome(new anchor.BN(10_000), true, new anchor.BN(0), null, null)
+        .accounts(buyAccounts()).signers([user]).rpc();
+    });
+
+    it("pauses and unpauses a single market", async () => {
+      const pauseAccounts = { platform: platformPda, market: m0.market, signer: guardian.publicKey };
+      await program.methods.pauseMarket().accounts(pauseAccounts).signers([guardian]).rpc();
+      expect((await program.account.market.fetch(m0.market)).status).to.equal(7);
+      try {
+        await program.methods.buyOutcome(new anchor.BN(10_000), true, new anchor.BN(0), null, null)
+          .accounts(buyAccounts()).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("MarketNotActive");
+      }
+      await program.methods.unpauseMarket().accounts(pauseAccounts).signers([guardian]).rpc();
+      expect((await program.account.market.fetch(m0.market)).status).to.equal(0);
+    });
+
+    it("blocks complete sets, liquidity, orders, conditional orders, categorical trades and resolution while paused", async () => {
+      const id = new anchor.BN(0).toArrayLike(Buffer, "le", 8);
+      const seed = (prefix: string) => PublicKey.findProgramAddressSync([Buffer.from(prefix), id], program.programId)[0];
+      const userLp = await createRawTokenAccount(provider.connection, authority.payer, m0.lpMint, user.publicKey);
+
+      // A fresh categorical market to trade and resolve
+      const p = await program.account.platform.fetch(platformPda);
+      const mc = deriveMarketPdas(p.marketCount.toNumber());
+      const mcId = p.marketCount.toArrayLike(Buffer, "le", 8);
+      const [outcomePool] = PublicKey.findProgramAddressSync([Buffer.from("outcome_pool"), mcId], program.programId);
+      const outcomeMints = [0, 1, 2].map(i =>
+        PublicKey.findProgramAddressSync([Buffer.from("outcome_mint"), mcId, Buffer.from([i])], program.programId)[0]);
+      await program.methods.createCategoricalMarket(
+        "Which chain ships first?", "Release race", "crypto", "Release notes", 3, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY),
+      ).accounts({
+        platform: platformPda, market: mc.market, outcomePool, collateralMint, vault: mc.vault,
+        authorityCollateral, authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
+      }).remainingAccounts(outcomeMints.map(pubkey => ({ pubkey, isSigner: false, isWritable: true }))).rpc();
+      const userOutcome = await createRawTokenAccount(provider.connection, authority.payer, outcomeMints[0], user.publicKey);
+
+      const userAccounts = {
+        userCollateral: userCollateral0, userYesTokens: userYes0, userNoTokens: userNo0,
+        userPosition: userPos0, user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+      };
+      const calls = [
+        program.methods.mintCompleteSet(new anchor.BN(1_000))
+          .accounts({ market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint, vault: m0.vault, ...userAccounts })
+          .signers([user]),
+        program.methods.mergeCompleteSet(new anchor.BN(1_000))
+          .accounts({ market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint, vault: m0.vault, ...userAccounts })
+          .signers([user]),
+        program.methods.addLiquidity(new anchor.BN(10_000), new anchor.BN(1))
+          .accounts({
+            market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint, lpMint: m0.lpMint,
+            vault: m0.vault, userLpTokens: userLp, ...userAccounts,
+          })
+          .signers([user]),
+        program.methods.removeLiquidity(new anchor.BN(1_000), new anchor.BN(0))
+          .accounts({
+            market: m0.market, platform: platformPda, yesMint: m0.yesMint, noMint: m0.noMint, lpMint: m0.lpMint,
+            vault: m0.vault, userLpTokens: userLp, ...userAccounts,
+          })
+          .signers([user]),
+        program.methods.placeOrder(true, true, 5000, new anchor.BN(100_000))
+          .accounts({
+            market: m0.market, platform: platformPda, orderBook: seed("order_book"), orderEscrow: seed("order_escrow"),
+            yesEscrow: seed("yes_escrow"), noEscrow: seed("no_escrow"), vault: m0.vault, treasury: treasuryTokenAccount,
+            ...userAccounts,
+          })
+          .signers([user]),
+        program.methods.placeConditionalOrder({ limit: {} }, true, true, 5000, new anchor.BN(1_000), new anchor.BN(0), new anchor.BN(0))
+          .accounts({
+            market: m0.market, platform: platformPda,
+            conditionalOrder: PublicKey.findProgramAddressSync(
+              [Buffer.from("conditional_order"), id, user.publicKey.toBuffer()], program.programId)[0],
+            escrow: PublicKey.findProgramAddressSync(
+              [Buffer.from("conditional_escrow"), id, user.publicKey.toBuffer()], program.programId)[0],
+            escrowMint: collateralMint, userSource: userCollateral0, userPosition: userPos0, user: user.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId, rent: SYSVAR_RENT_PUBKEY,
+          })
+          .signers([user]),
+        program.methods.buyCategorical(0, new anchor.BN(10_000), new anchor.BN(1))
+          .accounts({
+            market: mc.market, outcomePool, platform: platformPda, outcomeMint: outcomeMints[0], vault: mc.vault,
+            treasury: treasuryTokenAccount, userCollateral: userCollateral0, userOutcomeTokens: userOutcome,
+            user: user.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([user]),
+        program.methods.resolveCategorical(0)
+          .accounts({ market: mc.market, outcomePool, platform: platformPda, authority: authority.publicKey, resolverRole: null }),
+        program.methods.resolveInvalid(null)
+          .accounts({ market: m0.market, platform: platformPda, authority: authority.publicKey }),
+      ];
+
+      await program.methods.pausePlatform().accounts({ platform: platformPda, signer: guardian.publicKey }).signers([guardian]).rpc();
+      try {
+        for (const call of calls) {
+          try {
+            await call.rpc();
+            expect.fail("Should throw");
+          } catch (err: any) {
+            expect(err.toString()).to.include("PlatformPaused");
+          }
+        }
+      } finally {
+        await program.methods.unpausePlatform().accounts({ platform: platformPda, signer: authority.publicKey }).rpc();
+      }
+    });
+  });
+
+  describe("34. Roles", () => {
+    const ROLE_GUARDIAN = 4;
+    const [userRole] = PublicKey.findProgramAddressSync([Buffer.from("role"), user.publicKey.toBuffer()], program.programId);
+    const pauseAccounts = () => ({ platform: platformPda, market: m0.market, signer: user.publicKey, role: userRole });
+
+    it("rejects unknown role flags", async () => {
+      try {
+        await program.methods.grantRole(user.publicKey, 16)
+          .accounts({ platform: platformPda, role: userRole, authority: authority.publicKey }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("InvalidRoleFlags");
+      }
+    });
+
+    it("rejects grants from anyone but the platform authority", async () => {
+      try {
+        await program.methods.grantRole(user.publicKey, ROLE_GUARDIAN)
+          .accounts({ platform: platformPda, role: userRole, authority: user.publicKey }).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("Unauthorized");
+      }
+    });
+
+    it("lets a guardian pause a market until the role is revoked", async () => {
+      await program.methods.grantRole(user.publicKey, ROLE_GUARDIAN)
+        .accounts({ platform: platformPda, role: userRole, authority: authority.publicKey }).rpc();
+      expect((await program.account.role.fetch(userRole)).flags).to.equal(ROLE_GUARDIAN);
+      await program.methods.pauseMarket().accounts(pauseAccounts()).signers([user]).rpc();
+      await program.methods.unpauseMarket().accounts(pauseAccounts()).signers([user]).rpc();
+
+      await program.methods.revokeRole(user.publicKey, ROLE_GUARDIAN)
+        .accounts({ platform: platformPda, role: userRole, authority: authority.publicKey }).rpc();
+      try {
+        await program.methods.pauseMarket().accounts(pauseAccounts()).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("Unauthorized");
+      }
+    });
+
+    it("lets a resolver invalidate a market it did not create", async () => {
+      const ROLE_RESOLVER = 2;
+      const invalidAccounts = () => ({ market: m0.market, platform: platformPda, authority: user.publicKey, resolverRole: userRole });
+      await program.methods.grantRole(user.publicKey, ROLE_RESOLVER)
+        .accounts({ platform: platformPda, role: userRole, authority: authority.publicKey }).rpc();
+      try {
+        await program.methods.resolveInvalid(null).accounts(invalidAccounts()).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        // Authorized, but market 0 has not expired yet
+        expect(err.toString()).to.include("MarketNotExpired");
+      }
+
+      await program.methods.revokeRole(user.publicKey, ROLE_RESOLVER)
+        .accounts({ platform: platformPda, role: userRole, authority: authority.publicKey }).rpc();
+      try {
+        await program.methods.resolveInvalid(null).accounts(invalidAccounts()).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("Unauthorized");
+      }
+    });
+
+    it("gives a creator no resolution rights over its own markets", async () => {
+      const ROLE_CREATOR = 1;
+      await program.methods.grantRole(user.publicKey, ROLE_CREATOR)
+        .accounts({ platform: platformPda, role: userRole, authority: authority.publicKey }).rpc();
+      const p = await program.account.platform.fetch(platformPda);
+      const mr = deriveMarketPdas(p.marketCount.toNumber());
+      const [userLpTokens] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lp_tokens"), p.marketCount.toArrayLike(Buffer, "le", 8), user.publicKey.toBuffer()], program.programId);
+      await program.methods.createMarket(
+        "Will the launch ship on time?", "Launch date", "tech", "Official announcement",
+        null, 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, null, null, null,
+      ).accounts({
+        platform: platformPda, creatorRole: userRole, market: mr.market, creatorBondVault: null, treasury: null,
+        yesMint: mr.yesMint, noMint: mr.noMint, collateralMint, vault: mr.vault, lpMint: mr.lpMint,
+        authorityCollateral: userCollateral0, authorityLpTokens: userLpTokens, authority: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY, oracleAccount: null, oracleFeed: null,
+      }).signers([user]).rpc();
+      expect((await program.account.market.fetch(mr.market)).status).to.equal(0);
+
+      const attempts = [
+        program.methods.resolveMarket(true).accounts({
+          market: mr.market, platform: platformPda, resolver: user.publicKey, resolverRole: userRole,
+          oracleAccount: null, priceSamples: null,
+        }),
+        program.methods.resolveInvalid(10000).accounts({
+          market: mr.market, platform: platformPda, authority: user.publicKey, resolverRole: userRole,
+        }),
+      ];
+      for (const attempt of attempts) {
+        try {
+          await attempt.signers([user]).rpc();
+          expect.fail("Should throw");
+        } catch (err: any) {
+          expect(err.toString()).to.include("Unauthorized");
+        }
+      }
+
+      await program.methods.revokeRole(user.publicKey, ROLE_CREATOR)
+        .accounts({ platform: platformPda, role: userRole, authority: authority.publicKey }).rpc();
+    });
+  });
+
+  describe("35. Community markets", () => {
+    const buf = Buffer.alloc(8);
+    const [creatorBondVault] = PublicKey.findProgramAddressSync([Buffer.from("creator_bond"), buf], program.programId);
+    const reviewAccounts = (curator: PublicKey) => ({
+      platform: platformPda, curatorRole: null, market: m0.market, vault: m0.vault, creatorBondVault,
+      creatorCollateral: authorityCollateral, creator: authority.publicKey, treasury: treasuryTokenAccount,
+      curator, tokenProgram: TOKEN_PROGRAM_ID,
+    });
+
+    it("rejects a creator fee larger than the protocol share", async () => {
+      try {
+        await program.methods.updatePlatformConfig(FEE_BPS, LP_FEE_BPS, new anchor.BN(1_000_000), FEE_BPS)
+          .accounts({
+            platform: platformPda, currentTreasury: treasuryTokenAccount, treasury: treasuryTokenAccount,
+            authority: authority.publicKey,
+          }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("InvalidFeeSplit");
+      }
+    });
+
+    it("only reviews markets that are pending", async () => {
+      try {
+        await program.methods.approveMarket().accounts(reviewAccounts(authority.publicKey)).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("MarketNotPendingReview");
+      }
+    });
+
+    it("rejects reviews from anyone without the curator role", async () => {
+      try {
+        await program.methods.rejectMarket().accounts(reviewAccounts(user.publicKey)).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("Unauthorized");
+      }
+    });
+
+    it("only lets the creator withdraw markets that are pending review", async () => {
+      try {
+        await program.methods.withdrawUnreviewedMarket()
+          .accounts({
+            market: m0.market, vault: m0.vault, creatorBondVault, creatorCollateral: authorityCollateral,
+            creator: authority.publicKey, tokenProgram: TOKEN_PROGRAM_ID,
+          }).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("MarketNotPendingReview");
+      }
+    });
+
+    it("keeps resolution away from the community creator", async () => {
+      const p = await program.account.platform.fetch(platformPda);
+      const mu = deriveMarketPdas(p.marketCount.toNumber());
+      const [userLpTokens] = PublicKey.findProgramAddressSync(
+        [Buffer.from("lp_tokens"), p.marketCount.toArrayLike(Buffer, "le", 8), user.publicKey.toBuffer()], program.programId);
+      await program.methods.createMarket(
+        "Will the community vote pass?", "Governance vote", "politics", "Forum vote",
+        null, 0, getExpiry(), new anchor.BN(INITIAL_LIQUIDITY), null, null, null, null,
+      ).accounts({
+        platform: platformPda, creatorRole: null, market: mu.market, creatorBondVault: null, treasury: treasuryTokenAccount,
+        yesMint: mu.yesMint, noMint: mu.noMint, collateralMint, vault: mu.vault, lpMint: mu.lpMint,
+        authorityCollateral: userCollateral0, authorityLpTokens: userLpTokens, authority: user.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID, systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY, oracleAccount: null, oracleFeed: null,
+      }).signers([user]).rpc();
+      const market = await program.account.market.fetch(mu.market);
+      expect(market.creator.toString()).to.equal(user.publicKey.toString());
+      expect(market.authority.toString()).to.equal(authority.publicKey.toString());
+
+      await program.methods.approveMarket().accounts({
+        ...reviewAccounts(authority.publicKey), market: mu.market, vault: mu.vault,
+        creatorBondVault: PublicKey.findProgramAddressSync(
+          [Buffer.from("creator_bond"), p.marketCount.toArrayLike(Buffer, "le", 8)], program.programId)[0],
+        creatorCollateral: userCollateral0, creator: user.publicKey,
+      }).rpc();
+
+      const attempts = [
+        program.methods.resolveMarket(true).accounts({
+          market: mu.market, platform: platformPda, resolver: user.publicKey, resolverRole: null,
+          oracleAccount: null, priceSamples: null,
+        }),
+        program.methods.resolveInvalid(10000).accounts({
+          market: mu.market, platform: platformPda, authority: user.publicKey, resolverRole: null,
+        }),
+        program.methods.cancelMarket().accounts({ market: mu.market, authority: user.publicKey, role: null }),
+      ];
+      for (const attempt of attempts) {
+        try {
+          await attempt.signers([user]).rpc();
+          expect.fail("Should throw");
+        } catch (err: any) {
+          expect(err.toString()).to.include("Unauthorized");
+        }
+      }
+    });
+  });
+
+  describe("36. Market maintenance", () => {
+    const updateAccounts = (signer: PublicKey) => ({
+      platform: platformPda, curatorRole: null, market: m0.market, authority: signer,
+    });
+
+    it("updates the market metadata", async () => {
+      await program.methods.updateMarketMetadata("Will BTC reach $150k?", "BTC price prediction", "crypto")
+        .accounts(updateAccounts(authority.publicKey)).rpc();
+      const market = await program.account.market.fetch(m0.market);
+      expect(market.question).to.equal("Will BTC reach $150k?");
+      expect(market.description).to.equal("BTC price prediction");
+    });
+
+    it("keeps the creation length limits", async () => {
+      try {
+        await program.methods.updateMarketMetadata("x".repeat(129), "", "crypto")
+          .accounts(updateAccounts(authority.publicKey)).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("QuestionTooLong");
+      }
+    });
+
+    it("rejects edits from anyone without the curator role", async () => {
+      try {
+        await program.methods.updateMarketMetadata("Hijacked", "", "crypto")
+          .accounts(updateAccounts(user.publicKey)).signers([user]).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("Unauthorized");
+      }
+    });
+
+    it("only moves expiry later", async () => {
+      const { expiresAt } = await program.account.market.fetch(m0.market);
+      try {
+        await program.methods.extendMarketExpiry(expiresAt).accounts(updateAccounts(authority.publicKey)).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("ExpiryNotExtended");
+      }
+    });
+
+    it("caps how far expiry moves at once", async () => {
+      const { expiresAt } = await program.account.market.fetch(m0.market);
+      try {
+        await program.methods.extendMarketExpiry(expiresAt.add(new anchor.BN(31 * 86400)))
+          .accounts(updateAccounts(authority.publicKey)).rpc();
+        expect.fail("Should throw");
+      } catch (err: any) {
+        expect(err.toString()).to.include("ExpiryExtensionTooLong");
+      }
+    });
+
+    it("extends expiry and recomputes the lock time", async () => {
+      const { expiresAt } = await program.account.market.fetch(m0.market);
+      const extended = expiresAt.add(new anchor.BN(86400));
+      await program.methods.extendMarketExpiry(extended).accounts(updateAccounts(authority.publicKey)).rpc();
+      const market = await program.account.market.fetch(m0.market);
+      expect(market.expiresAt.toNumber()).to.equal(extended.toNumber());
+      expect(market.lockTime.toNumber()).to.equal(extended.toNumber() - 43200);
+    });
+  });
 });